@@ -146,12 +146,19 @@ impl Account {
 	/// return the nonce associated with this account.
 	pub fn nonce(&self) -> &U256 { &self.nonce }
 
-	#[cfg(test)]
 	/// return the code hash associated with this account.
 	pub fn code_hash(&self) -> H256 {
 		self.code_hash.clone().unwrap_or(SHA3_EMPTY)
 	}
 
+	/// Whether the account has no code, no balance and the nonce accounts start with (EIP-161).
+	pub fn is_empty(&self, start_nonce: &U256) -> bool {
+		self.balance.is_zero() && &self.nonce == start_nonce && match self.code_hash {
+			Some(ref h) => *h == SHA3_EMPTY,
+			None => self.code_cache.is_empty(),
+		}
+	}
+
 	/// returns the account's code. If `None` then the code cache isn't available -
 	/// get someone who knows to call `note_code`.
 	pub fn code(&self) -> Option<&[u8]> {
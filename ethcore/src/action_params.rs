@@ -57,8 +57,14 @@ pub struct ActionParams {
 	pub value: ActionValue,
 	/// Code being executed.
 	pub code: Option<Bytes>,
+	/// Hash of the code being executed, used to look up its analysis in the EVM cache.
+	/// `SHA3_EMPTY` if unknown.
+	pub code_hash: H256,
 	/// Input data.
-	pub data: Option<Bytes>
+	pub data: Option<Bytes>,
+	/// Whether the code may not modify the state, as when run by a `STATICCALL`
+	/// or by a call nested in one.
+	pub is_static: bool,
 }
 
 impl Default for ActionParams {
@@ -73,23 +79,28 @@ impl Default for ActionParams {
 			gas_price: U256::zero(),
 			value: ActionValue::Transfer(U256::zero()),
 			code: None,
-			data: None
+			code_hash: SHA3_EMPTY,
+			data: None,
+			is_static: false,
 		}
 	}
 }
 
 impl From<ethjson::vm::Transaction> for ActionParams {
 	fn from(t: ethjson::vm::Transaction) -> Self {
+		let code: Vec<u8> = t.code.into();
 		ActionParams {
 			code_address: Address::new(),
 			address: t.address.into(),
 			sender: t.sender.into(),
 			origin: t.origin.into(),
-			code: Some(t.code.into()),
+			code_hash: code.sha3(),
+			code: Some(code),
 			data: Some(t.data.into()),
 			gas: t.gas.into(),
 			gas_price: t.gas_price.into(),
 			value: ActionValue::Transfer(t.value.into()),
+			is_static: false,
 		}
 	}
 }
@@ -0,0 +1,447 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A blockchain engine that supports a non-instant BFT proof-of-authority: time is divided
+//! into fixed-duration steps and the authorities take turns to seal, one per step.
+
+use common::*;
+use account_provider::AccountProvider;
+use block::*;
+use spec::{CommonParams, Spec};
+use engine::*;
+use evm::Schedule;
+use ethjson;
+use time::get_time;
+use validator_set::{ValidatorSet, new_validator_set};
+use client::Client;
+use std::sync::Weak;
+
+/// `AuthorityRound` params.
+pub struct AuthorityRoundParams {
+	/// Gas limit divisor.
+	pub gas_limit_bound_divisor: U256,
+	/// Duration of a step, in seconds.
+	pub step_duration: u64,
+	/// Valid authorities, in the order in which they propose.
+	pub validators: Box<ValidatorSet>,
+}
+
+impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
+	fn from(p: ethjson::spec::AuthorityRoundParams) -> Self {
+		AuthorityRoundParams {
+			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
+			step_duration: p.step_duration.into(),
+			validators: new_validator_set(p.validators),
+		}
+	}
+}
+
+/// Engine using `AuthorityRound` proof-of-authority consensus: the authority whose turn it
+/// is in the current step seals, and the step is recorded in the seal.
+pub struct AuthorityRound {
+	params: CommonParams,
+	our_params: AuthorityRoundParams,
+	builtins: BTreeMap<Address, Builtin>,
+	client: RwLock<Option<Weak<Client>>>,
+	/// Step during the last tick.
+	last_step: Mutex<u64>,
+	/// Step of the last block we sealed.
+	last_sealed_step: Mutex<u64>,
+	/// Current UNIX time, in seconds.
+	clock: Box<Fn() -> u64 + Send + Sync>,
+}
+
+impl AuthorityRound {
+	/// Create a new instance of AuthorityRound engine
+	pub fn new(params: CommonParams, our_params: AuthorityRoundParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		AuthorityRound {
+			params: params,
+			our_params: our_params,
+			builtins: builtins,
+			client: RwLock::new(None),
+			last_step: Mutex::new(0),
+			last_sealed_step: Mutex::new(0),
+			clock: Box::new(|| get_time().sec as u64),
+		}
+	}
+
+	/// Take the current time from `clock` rather than from the system time.
+	pub fn with_clock<F>(mut self, clock: F) -> Self where F: Fn() -> u64 + Send + Sync + 'static {
+		self.clock = Box::new(clock);
+		self
+	}
+
+	/// The current step, derived from the clock.
+	pub fn step(&self) -> u64 {
+		(self.clock)() / max(self.our_params.step_duration, 1)
+	}
+
+	/// The authority which is to propose block `number` in `step` on top of block `parent`, if there are any authorities.
+	pub fn step_proposer(&self, parent: &H256, number: BlockNumber, step: u64) -> Option<Address> {
+		let validators = self.our_params.validators.validators(parent, number);
+		match validators.is_empty() {
+			true => None,
+			false => Some(validators[(step % validators.len() as u64) as usize].clone()),
+		}
+	}
+
+	/// Whether `address` is to propose block `number` in `step` on top of block `parent`.
+	pub fn is_step_proposer(&self, parent: &H256, number: BlockNumber, step: u64, address: &Address) -> bool {
+		self.step_proposer(parent, number, step).map_or(false, |proposer| &proposer == address)
+	}
+}
+
+/// Step recorded in the seal of `header`.
+fn header_step(header: &Header) -> Result<u64, DecoderError> {
+	UntrustedRlp::new(&header.seal()[0]).as_val()
+}
+
+/// Signature of the proposer recorded in the seal of `header`.
+fn header_signature(header: &Header) -> Result<H520, DecoderError> {
+	UntrustedRlp::new(&header.seal()[1]).as_val()
+}
+
+/// Recover the account which signed `header` from its seal.
+fn recover_signer(header: &Header) -> Result<Address, Error> {
+	let sig = try!(header_signature(header));
+	Ok(Address::from(try!(ec::recover(&sig, &header.bare_hash())).sha3()))
+}
+
+impl Engine for AuthorityRound {
+	fn name(&self) -> &str { "AuthorityRound" }
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+	// Two fields - the step and the signature
+	fn seal_fields(&self) -> usize { 2 }
+
+	fn params(&self) -> &CommonParams { &self.params }
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+
+	/// Additional engine-specific information for the user/developer concerning `header`.
+	fn extra_info(&self, header: &Header) -> HashMap<String, String> {
+		hash_map![
+			"step".to_owned() => header_step(header).map(|s| s.to_string()).unwrap_or_else(|_| "invalid".to_owned()),
+			"signer".to_owned() => self.signer(header).map_or_else(|| "invalid".to_owned(), |s| format!("0x{}", s.hex()))
+		]
+	}
+
+	fn signer(&self, header: &Header) -> Option<Address> {
+		match header.seal.len() == self.seal_fields() {
+			true => recover_signer(header).ok(),
+			false => None,
+		}
+	}
+
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_homestead();
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
+		header.difficulty = parent.difficulty;
+		header.gas_limit = {
+			let gas_limit = parent.gas_limit;
+			let bound_divisor = self.our_params.gas_limit_bound_divisor;
+			if gas_limit < gas_floor_target {
+				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else {
+				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
+			}
+		};
+		header.note_dirty();
+	}
+
+	/// Attempt to seal the block internally.
+	///
+	/// Only succeeds if the block's author is the proposer for the current step
+	/// and no block has been sealed in this step yet.
+	fn generate_seal(&self, block: &ExecutedBlock, accounts: Option<&AccountProvider>) -> Option<Vec<Bytes>> {
+		let header = block.header();
+		let step = self.step();
+		let mut last_sealed_step = self.last_sealed_step.lock();
+		if step <= *last_sealed_step {
+			trace!(target: "authorityround", "generate_seal: already sealed in step {}", *last_sealed_step);
+			return None;
+		}
+		if !self.is_step_proposer(header.parent_hash(), header.number(), step, header.author()) {
+			trace!(target: "authorityround", "generate_seal: not our turn in step {}", step);
+			return None;
+		}
+
+		if let Some(ap) = accounts {
+			// account should be pernamently unlocked, otherwise sealing will fail
+			if let Ok(signature) = ap.sign(*header.author(), header.bare_hash()) {
+				*last_sealed_step = step;
+				return Some(vec![encode(&step).to_vec(), encode(&signature).to_vec()]);
+			} else {
+				trace!(target: "authorityround", "generate_seal: FAIL: accounts secret key unavailable");
+			}
+		} else {
+			trace!(target: "authorityround", "generate_seal: FAIL: accounts not provided");
+		}
+		None
+	}
+
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
+		if header.seal.len() != self.seal_fields() {
+			return Err(From::from(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: header.seal.len() }
+			)));
+		}
+
+		// allow for clock drift of up to one step.
+		let step = try!(header_step(header));
+		let max_step = self.step() + 1;
+		if step > max_step {
+			return Err(From::from(BlockError::InvalidStep(OutOfBounds { min: None, max: Some(max_step), found: step })));
+		}
+		Ok(())
+	}
+
+	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
+		// check the signature is legit.
+		try!(recover_signer(header));
+		Ok(())
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
+		// we should not calculate difficulty for genesis blocks
+		if header.number() == 0 {
+			return Err(From::from(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() })));
+		}
+
+		// at most one block per step.
+		let step = try!(header_step(header));
+		let parent_step = try!(header_step(parent));
+		if step <= parent_step {
+			return Err(From::from(BlockError::InvalidStep(OutOfBounds { min: Some(parent_step + 1), max: None, found: step })));
+		}
+
+		// check the signature is by the proposer of the step.
+		let signer = try!(recover_signer(header));
+		if !self.is_step_proposer(header.parent_hash(), header.number(), step, &signer) {
+			return try!(Err(BlockError::InvalidSeal));
+		}
+
+		if header.difficulty() != parent.difficulty() {
+			return Err(From::from(BlockError::InvalidDifficulty(Mismatch { expected: *parent.difficulty(), found: *header.difficulty() })))
+		}
+		let gas_limit_divisor = self.our_params.gas_limit_bound_divisor;
+		let min_gas = parent.gas_limit - parent.gas_limit / gas_limit_divisor;
+		let max_gas = parent.gas_limit + parent.gas_limit / gas_limit_divisor;
+		if header.gas_limit <= min_gas || header.gas_limit >= max_gas {
+			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit })));
+		}
+		Ok(())
+	}
+
+	fn verify_transaction_basic(&self, t: &SignedTransaction, header: &Header) -> result::Result<(), Error> {
+		try!(t.check_low_s());
+		self.verify_transaction_chain_id(t, header)
+	}
+
+	fn verify_transaction(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
+		t.sender().map(|_|()) // Perform EC recovery and cache sender
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		self.our_params.validators.register_client(client.clone());
+		*self.client.write() = Some(client);
+	}
+
+	fn tick(&self) {
+		let step = self.step();
+		{
+			let mut last_step = self.last_step.lock();
+			if *last_step == step {
+				return;
+			}
+			*last_step = step;
+		}
+		// it may be our turn now: let the miner try to seal its pending block.
+		trace!(target: "authorityround", "tick: entering step {}", step);
+		if let Some(client) = self.client.read().as_ref().and_then(|c| c.upgrade()) {
+			client.update_sealing();
+		}
+	}
+}
+
+/// Create a new test chain spec with `AuthorityRound` consensus engine.
+/// Steps are long enough for every test to run in step 0, the step of the genesis block.
+pub fn new_test_round() -> Spec { Spec::load(include_bytes!("../res/test_authority_round.json")).expect("invalid chain spec") }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use common::*;
+	use block::*;
+	use tests::helpers::*;
+	use account_provider::AccountProvider;
+	use client::{BlockChainClient, Client, ClientConfig};
+	use miner::{Miner, MinerService, GasPricer};
+	use spec::Spec;
+	use devtools::RandomTempPath;
+	use validator_set::SimpleList;
+	use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+	/// Test spec with one second steps, alternating between the two authorities, whose engine reads the time from `now`.
+	fn spec_with_clock(now: Arc<AtomicUsize>) -> Spec {
+		let mut spec = new_test_round();
+		let validators = vec![
+			Address::from_str("9cce34f7ab185c7aba1b7c8140d620b4bda941d6").unwrap(),
+			Address::from_str("7d577a597b2742b498cb5cf0c26cdcd726d39e6e").unwrap(),
+		];
+		let params = AuthorityRoundParams {
+			gas_limit_bound_divisor: 0x0400.into(),
+			step_duration: 1,
+			validators: Box::new(SimpleList::new(validators)),
+		};
+		let engine = AuthorityRound::new(spec.params.clone(), params, BTreeMap::new());
+		spec.engine = Box::new(engine.with_clock(move || now.load(AtomicOrdering::SeqCst) as u64));
+		spec
+	}
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = new_test_round().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.version().major >= 1);
+	}
+
+	#[test]
+	fn can_do_seal_verification_fail() {
+		let engine = new_test_round().engine;
+		let header: Header = Header::default();
+
+		let verify_result = engine.verify_block_basic(&header, None);
+
+		match verify_result {
+			Err(Error::Block(BlockError::InvalidSealArity(_))) => {},
+			Err(_) => { panic!("should be block seal-arity mismatch error (got {:?})", verify_result); },
+			_ => { panic!("Should be error, got Ok"); },
+		}
+	}
+
+	#[test]
+	fn rejects_future_step() {
+		let engine = new_test_round().engine;
+		let mut header: Header = Header::default();
+		header.set_seal(vec![encode(&1u64).to_vec(), encode(&H520::default()).to_vec()]);
+		assert!(engine.verify_block_basic(&header, None).is_ok());
+
+		header.set_seal(vec![encode(&2u64).to_vec(), encode(&H520::default()).to_vec()]);
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Block(BlockError::InvalidStep(_))) => {},
+			r => panic!("should be invalid step error (got {:?})", r),
+		}
+	}
+
+	#[test]
+	fn rejects_step_not_after_parent() {
+		let engine = new_test_round().engine;
+		let mut parent: Header = Header::default();
+		parent.set_seal(vec![encode(&4u64).to_vec(), encode(&H520::default()).to_vec()]);
+		let mut header: Header = Header::default();
+		header.set_number(1);
+		header.set_seal(vec![encode(&4u64).to_vec(), encode(&H520::default()).to_vec()]);
+
+		match engine.verify_block_family(&header, &parent, None) {
+			Err(Error::Block(BlockError::InvalidStep(_))) => {},
+			r => panic!("should be invalid step error (got {:?})", r),
+		}
+	}
+
+	#[test]
+	fn seals_and_verifies_on_our_turn_only() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account("".sha3(), "").unwrap();
+		tap.unlock_account_permanently(addr, "".into()).unwrap();
+		let other = Address::from_str("7d577a597b2742b498cb5cf0c26cdcd726d39e6e").unwrap();
+
+		let now = Arc::new(AtomicUsize::new(2));
+		let spec = spec_with_clock(now.clone());
+		let engine = &spec.engine;
+		let genesis_header = spec.genesis_header();
+		let vm_factory = Default::default();
+		let open_block = |author: Address| {
+			let mut db_result = get_temp_journal_db();
+			let mut db = db_result.take();
+			spec.ensure_db_good(db.as_hashdb_mut());
+			let last_hashes = vec![genesis_header.hash()];
+			OpenBlock::new(engine.deref(), &vm_factory, Default::default(), false, db, &genesis_header, last_hashes, author, (3141562.into(), 31415620.into()), vec![]).unwrap().close_and_lock()
+		};
+
+		// the second authority has to wait for its turn
+		let b = open_block(other);
+		assert!(engine.generate_seal(b.block(), Some(&tap)).is_none());
+
+		let b = open_block(addr);
+		let seal = engine.generate_seal(b.block(), Some(&tap)).unwrap();
+		let sealed = b.try_seal(engine.deref(), seal).ok().expect("seal is valid");
+		assert!(engine.verify_block_unordered(sealed.header(), None).is_ok());
+		assert!(engine.verify_block_family(sealed.header(), &genesis_header, None).is_ok());
+
+		// only one block is sealed per step
+		let b = open_block(addr);
+		assert!(engine.generate_seal(b.block(), Some(&tap)).is_none());
+		now.store(4, AtomicOrdering::SeqCst);
+		assert!(engine.generate_seal(b.block(), Some(&tap)).is_some());
+
+		// the same block claimed for the other authority's step is rejected
+		let mut header = sealed.header().clone();
+		let signature = header.seal()[1].clone();
+		header.set_seal(vec![encode(&1u64).to_vec(), signature]);
+		match engine.verify_block_family(&header, &genesis_header, None) {
+			Err(Error::Block(BlockError::InvalidSeal)) => {},
+			r => panic!("should be invalid seal error (got {:?})", r),
+		}
+	}
+
+	#[test]
+	fn seals_pending_block_when_our_step_comes() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr = tap.insert_account("".sha3(), "").unwrap();
+		tap.unlock_account_permanently(addr, "".into()).unwrap();
+		// the other authority's step
+		let now = Arc::new(AtomicUsize::new(1));
+
+		let dir = RandomTempPath::new();
+		let miner = Miner::new(Default::default(), GasPricer::new_fixed(0.into()), spec_with_clock(now.clone()), Some(tap));
+		miner.set_author(addr);
+		let client = Client::new(ClientConfig::default(), spec_with_clock(now.clone()), dir.as_path(), miner.clone(), IoChannel::disconnected()).unwrap();
+		let engine = client.engine();
+
+		let transaction = Transaction {
+			action: Action::Call(Address::default()),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::zero(),
+			nonce: engine.account_start_nonce(),
+		}.sign(&"".sha3(), None);
+		miner.import_own_transaction(client.deref(), transaction).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_number, 0);
+
+		// the step timer seals the pending block as soon as it is our turn
+		now.store(2, AtomicOrdering::SeqCst);
+		engine.tick();
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_number, 1);
+	}
+}
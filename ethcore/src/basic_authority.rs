@@ -23,16 +23,18 @@ use spec::{CommonParams, Spec};
 use engine::*;
 use evm::Schedule;
 use ethjson;
+use validator_set::{ValidatorSet, new_validator_set};
+use client::Client;
+use std::sync::Weak;
 
 /// `BasicAuthority` params.
-#[derive(Debug, PartialEq)]
 pub struct BasicAuthorityParams {
 	/// Gas limit divisor.
 	pub gas_limit_bound_divisor: U256,
 	/// Block duration.
 	pub duration_limit: u64,
 	/// Valid signatories.
-	pub authorities: HashSet<Address>,
+	pub validators: Box<ValidatorSet>,
 }
 
 impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
@@ -40,7 +42,10 @@ impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
 		BasicAuthorityParams {
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
 			duration_limit: p.duration_limit.into(),
-			authorities: p.authorities.into_iter().map(Into::into).collect::<HashSet<_>>(),
+			validators: new_validator_set(match (p.validators, p.authorities) {
+				(Some(validators), _) => validators,
+				(None, authorities) => ethjson::spec::ValidatorSet::List(authorities.unwrap_or_else(Vec::new)),
+			}),
 		}
 	}
 }
@@ -74,10 +79,21 @@ impl Engine for BasicAuthority {
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
 
 	/// Additional engine-specific information for the user/developer concerning `header`.
-	fn extra_info(&self, _header: &Header) -> HashMap<String, String> { hash_map!["signature".to_owned() => "TODO".to_owned()] }
+	fn extra_info(&self, header: &Header) -> HashMap<String, String> {
+		hash_map!["signer".to_owned() => self.signer(header).map_or_else(|| "invalid".to_owned(), |s| format!("0x{}", s.hex()))]
+	}
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_homestead()
+	fn signer(&self, header: &Header) -> Option<Address> {
+		match header.seal.len() == self.seal_fields() {
+			true => recover_signer(header).ok(),
+			false => None,
+		}
+	}
+
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_homestead();
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
@@ -104,6 +120,10 @@ impl Engine for BasicAuthority {
 	/// This operation is synchronous and may (quite reasonably) not be available, in which `false` will
 	/// be returned.
 	fn generate_seal(&self, block: &ExecutedBlock, accounts: Option<&AccountProvider>) -> Option<Vec<Bytes>> {
+		if !self.our_params.validators.contains(block.header().parent_hash(), block.header().number(), block.header().author()) {
+			trace!(target: "basicauthority", "generate_seal: FAIL: author is not a validator");
+			return None;
+		}
 		if let Some(ap) = accounts {
 			let header = block.header();
 			let message = header.bare_hash();
@@ -132,11 +152,7 @@ impl Engine for BasicAuthority {
 
 	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
 		// check the signature is legit.
-		let sig = try!(UntrustedRlp::new(&header.seal[0]).as_val::<H520>());
-		let signer = Address::from(try!(ec::recover(&sig, &header.bare_hash())).sha3());
-		if !self.our_params.authorities.contains(&signer) {
-			return try!(Err(BlockError::InvalidSeal));
-		}
+		try!(recover_signer(header));
 		Ok(())
 	}
 
@@ -146,6 +162,12 @@ impl Engine for BasicAuthority {
 			return Err(From::from(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() })));
 		}
 
+		// check the signer is a validator of this block.
+		let signer = try!(recover_signer(header));
+		if !self.our_params.validators.contains(header.parent_hash(), header.number(), &signer) {
+			return try!(Err(BlockError::InvalidSeal));
+		}
+
 		// Check difficulty is correct given the two timestamps.
 		if header.difficulty() != parent.difficulty() {
 			return Err(From::from(BlockError::InvalidDifficulty(Mismatch { expected: *parent.difficulty(), found: *header.difficulty() })))
@@ -159,14 +181,24 @@ impl Engine for BasicAuthority {
 		Ok(())
 	}
 
-	fn verify_transaction_basic(&self, t: &SignedTransaction, _header: &Header) -> result::Result<(), Error> {
+	fn verify_transaction_basic(&self, t: &SignedTransaction, header: &Header) -> result::Result<(), Error> {
 		try!(t.check_low_s());
-		Ok(())
+		self.verify_transaction_chain_id(t, header)
 	}
 
 	fn verify_transaction(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
 		t.sender().map(|_|()) // Perform EC recovery and cache sender
 	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		self.our_params.validators.register_client(client);
+	}
+}
+
+/// Recover the account which signed `header` from its seal.
+fn recover_signer(header: &Header) -> Result<Address, Error> {
+	let sig = try!(UntrustedRlp::new(&header.seal[0]).as_val::<H520>());
+	Ok(Address::from(try!(ec::recover(&sig, &header.bare_hash())).sha3()))
 }
 
 impl Header {
@@ -177,7 +209,7 @@ impl Header {
 }
 
 /// Create a new test chain spec with `BasicAuthority` consensus engine.
-pub fn new_test_authority() -> Spec { Spec::load(include_bytes!("../res/test_authority.json")) }
+pub fn new_test_authority() -> Spec { Spec::load(include_bytes!("../res/test_authority.json")).expect("invalid chain spec") }
 
 #[cfg(test)]
 mod tests {
@@ -186,6 +218,7 @@ mod tests {
 	use block::*;
 	use tests::helpers::*;
 	use account_provider::AccountProvider;
+	use validator_set::{ValidatorSet, SimpleList, Multi};
 
 	#[test]
 	fn has_valid_metadata() {
@@ -258,4 +291,83 @@ mod tests {
 		let seal = engine.generate_seal(b.block(), Some(&tap)).unwrap();
 		assert!(b.try_seal(engine.deref(), seal).is_ok());
 	}
+
+	#[test]
+	fn recovers_signer() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account("".sha3(), "").unwrap();
+		tap.unlock_account_permanently(addr, "".into()).unwrap();
+
+		let engine = new_test_authority().engine;
+		let mut header: Header = Header::default();
+		let signature = tap.sign(addr, header.bare_hash()).unwrap();
+		header.set_seal(vec![rlp::encode(&signature).to_vec()]);
+
+		assert_eq!(engine.signer(&header), Some(addr));
+		assert_eq!(engine.extra_info(&header)["signer"], format!("0x{}", addr.hex()));
+
+		header.set_seal(vec![]);
+		assert_eq!(engine.signer(&header), None);
+	}
+
+	#[test]
+	fn verifies_transaction_chain_id() {
+		let engine = new_test_authority().engine;
+		let secret = "".sha3();
+		let transaction = || Transaction {
+			action: Action::Create,
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas: U256::from(100_000),
+			value: U256::zero(),
+			data: vec![],
+		};
+		let mut header = Header::default();
+		header.set_number(1);
+		assert!(engine.verify_transaction_basic(&transaction().sign(&secret, None), &header).is_ok());
+		assert!(engine.verify_transaction_basic(&transaction().sign(&secret, Some(0x69)), &header).is_err());
+
+		header.set_number(2);
+		assert!(engine.verify_transaction_basic(&transaction().sign(&secret, None), &header).is_ok());
+		assert!(engine.verify_transaction_basic(&transaction().sign(&secret, Some(0x69)), &header).is_ok());
+		match engine.verify_transaction_basic(&transaction().sign(&secret, Some(1)), &header) {
+			Err(Error::Transaction(TransactionError::InvalidChainId(1))) => {},
+			other => panic!("should be an invalid chain id error (got {:?})", other),
+		}
+	}
+
+	#[test]
+	fn verifies_signer_against_validators_of_block() {
+		let tap = AccountProvider::transient_provider();
+		let first = tap.insert_account("".sha3(), "").unwrap();
+		tap.unlock_account_permanently(first, "".into()).unwrap();
+		let second = tap.insert_account("1".sha3(), "").unwrap();
+		tap.unlock_account_permanently(second, "".into()).unwrap();
+
+		let mut sets: BTreeMap<BlockNumber, Box<ValidatorSet>> = BTreeMap::new();
+		sets.insert(0, Box::new(SimpleList::new(vec![first])));
+		sets.insert(2, Box::new(SimpleList::new(vec![second])));
+		let our_params = BasicAuthorityParams {
+			gas_limit_bound_divisor: 0x400.into(),
+			duration_limit: 13,
+			validators: Box::new(Multi::new(sets)),
+		};
+		let engine = BasicAuthority::new(new_test_authority().engine.params().clone(), our_params, BTreeMap::new());
+
+		let mut parent = Header::default();
+		parent.set_gas_limit(3141562.into());
+		let sealed = |number: BlockNumber, signer: Address| {
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_gas_limit(3141562.into());
+			let signature = tap.sign(signer, header.bare_hash()).unwrap();
+			header.set_seal(vec![rlp::encode(&signature).to_vec()]);
+			header
+		};
+
+		assert!(engine.verify_block_family(&sealed(1, first), &parent, None).is_ok());
+		assert!(engine.verify_block_family(&sealed(1, second), &parent, None).is_err());
+		assert!(engine.verify_block_family(&sealed(2, first), &parent, None).is_err());
+		assert!(engine.verify_block_family(&sealed(2, second), &parent, None).is_ok());
+	}
 }
@@ -19,18 +19,67 @@ use crypto::sha2::Sha256;
 use crypto::ripemd160::Ripemd160;
 use crypto::digest::Digest;
 use ethjson;
-use ethabi;
-use hackishlibsnarkbindings;
-use ethabi::spec::ParamType;
-use ethabi::Token;
+use error::Error;
+use header::BlockNumber;
+use bn;
+use zksnark;
+
+/// Error returned by a built-in contract which could not process its input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinError(pub &'static str);
+
+impl fmt::Display for BuiltinError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Builtin failed ({})", self.0)
+	}
+}
+
+/// Storage of the account a built-in contract lives at, for builtins which keep state across calls.
+pub trait BuiltinStorage {
+	/// Get the value of the storage at `key`.
+	fn storage_at(&self, key: &H256) -> H256;
+	/// Set the value of the storage at `key`. Fails if the storage may not be changed, as in a static call.
+	fn set_storage(&mut self, key: H256, value: H256) -> Result<(), BuiltinError>;
+}
+
+/// In-memory storage, for running builtins outside of any state.
+impl BuiltinStorage for HashMap<H256, H256> {
+	fn storage_at(&self, key: &H256) -> H256 {
+		self.get(key).cloned().unwrap_or_else(H256::new)
+	}
+
+	fn set_storage(&mut self, key: H256, value: H256) -> Result<(), BuiltinError> {
+		self.insert(key, value);
+		Ok(())
+	}
+}
+
+/// Executor of a built-in contract: runs with the input being the first argument and
+/// the output being placed into the second; the third is the storage of the builtin's account.
+/// An executor which fails must leave the output untouched; storage changes are reverted.
+pub type BuiltinExec = Box<Fn(&[u8], &mut [u8], &mut BuiltinStorage) -> Result<(), BuiltinError>>;
+
+/// Size of the output a built-in contract produces for the given input data.
+pub type BuiltinOutputSize = fn(&[u8]) -> usize;
+
+/// Gas cost of running a built-in contract on the given input data.
+pub type BuiltinCost = Box<Fn(&[u8]) -> U256>;	// TODO: U256 should be bignum.
 
 /// Definition of a contract whose implementation is built-in.
 pub struct Builtin {
-	/// The gas cost of running this built-in for the given size of input data.
-	pub cost: Box<Fn(usize) -> U256>,	// TODO: U256 should be bignum.
+	/// The name under which the executor of this built-in is registered.
+	pub name: String,
+	/// The block number from which this built-in is active.
+	pub activate_at: BlockNumber,
+	/// The block number from which this built-in is no longer active, if any.
+	pub deactivate_at: Option<BlockNumber>,
+	/// The gas cost of running this built-in for the given input data.
+	pub cost: BuiltinCost,
 	/// Run this built-in function with the input being the first argument and the output
 	/// being placed into the second.
-	pub execute: Box<Fn(&[u8], &mut [u8])>,
+	pub execute: BuiltinExec,
+	/// The size of the output of this built-in for the given input data.
+	pub output_size: BuiltinOutputSize,
 }
 
 // Rust does not mark closurer that do not capture as Sync
@@ -40,40 +89,114 @@ unsafe impl Send for Builtin {}
 
 impl fmt::Debug for Builtin {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "<Builtin>")
+		write!(f, "<Builtin {}>", self.name)
 	}
 }
 
 impl Builtin {
-	/// Create a new object from components.
-	pub fn new(cost: Box<Fn(usize) -> U256>, execute: Box<Fn(&[u8], &mut [u8])>) -> Builtin {
-		Builtin {cost: cost, execute: execute}
+	/// Create a new object from components. It is active from genesis.
+	pub fn new(name: &str, cost: BuiltinCost, execute: BuiltinExec, output_size: BuiltinOutputSize) -> Builtin {
+		Builtin {name: name.to_owned(), activate_at: 0, deactivate_at: None, cost: cost, execute: execute, output_size: output_size}
 	}
 
 	/// Create a new object from a builtin-function name with a linear cost associated with input size.
-	pub fn from_named_linear(name: &str, base_cost: usize, word_cost: usize) -> Builtin {
-		let cost = Box::new(move|s: usize| -> U256 {
-			U256::from(base_cost) + U256::from(word_cost) * U256::from((s + 31) / 32)
-		});
+	/// Returns `None` if no builtin is registered under `name`.
+	pub fn from_named_linear(name: &str, base_cost: usize, word_cost: usize) -> Option<Builtin> {
+		new_builtin(name).map(|(exec, output_size)| Self::new(name, linear_cost(base_cost, word_cost), exec, output_size))
+	}
+
+	/// Create a new object from its spec description.
+	/// Fails with `Error::UnknownBuiltinName` if no builtin is registered under the given name.
+	pub fn from_json(b: ethjson::spec::Builtin) -> Result<Builtin, Error> {
+		let cost = match b.pricing {
+			ethjson::spec::Pricing::Linear(linear) => linear_cost(linear.base, linear.word),
+			ethjson::spec::Pricing::AltBn128Pairing(pairing) => pairing_cost(pairing.base, pairing.pair),
+			ethjson::spec::Pricing::ZkSnark(p) => zksnark_cost(p.base, p.vk_word, p.input_word, zksnark::verify_arg_lens),
+			ethjson::spec::Pricing::ZkSnarkRegistry(p) => zksnark_registry_cost(p.base, p.vk_word, p.input_word),
+			ethjson::spec::Pricing::ZkSnarkBatch(p) => zksnark_batch_cost(p.base, p.vk_word, p.proof, p.input_word),
+		};
+
+		match new_builtin(&b.name) {
+			Some((exec, output_size)) => Ok(Builtin {
+				activate_at: b.activate_at.map_or(0, Into::into),
+				deactivate_at: b.deactivate_at.map(Into::into),
+				.. Self::new(&b.name, cost, exec, output_size)
+			}),
+			None => Err(Error::UnknownBuiltinName(b.name)),
+		}
+	}
 
-		Self::new(cost, new_builtin_exec(name))
+	/// Whether this built-in is active at block number `at`.
+	pub fn is_active(&self, at: BlockNumber) -> bool {
+		at >= self.activate_at && self.deactivate_at.map_or(true, |deactivate_at| at < deactivate_at)
 	}
 
 	/// Simple forwarder for cost.
-	pub fn cost(&self, s: usize) -> U256 { (*self.cost)(s) }
+	pub fn cost(&self, input: &[u8]) -> U256 { (*self.cost)(input) }
 
 	/// Simple forwarder for execute.
-	pub fn execute(&self, input: &[u8], output: &mut[u8]) { (*self.execute)(input, output); }
+	pub fn execute(&self, input: &[u8], output: &mut[u8], storage: &mut BuiltinStorage) -> Result<(), BuiltinError> { (*self.execute)(input, output, storage) }
+
+	/// Simple forwarder for output size.
+	pub fn output_size(&self, input: &[u8]) -> usize { (self.output_size)(input) }
 }
 
-impl From<ethjson::spec::Builtin> for Builtin {
-	fn from(b: ethjson::spec::Builtin) -> Self {
-		match b.pricing {
-			ethjson::spec::Pricing::Linear(linear) => {
-				Self::from_named_linear(b.name.as_ref(), linear.base, linear.word)
-			}
-		}
-	}
+/// Output of a single 32-byte word, whatever the input.
+fn word_output(_input: &[u8]) -> usize { 32 }
+
+/// Output as long as the input.
+fn input_sized_output(input: &[u8]) -> usize { input.len() }
+
+/// Output of a G1 point, encoded as its two 32-byte coordinates.
+fn g1_output(_input: &[u8]) -> usize { 64 }
+
+/// Cost of `base` plus `word` for each 32-byte word of input.
+fn linear_cost(base: usize, word: usize) -> BuiltinCost {
+	Box::new(move |input: &[u8]| -> U256 {
+		U256::from(base) + U256::from(word) * U256::from((input.len() + 31) / 32)
+	})
+}
+
+/// Cost of `base` plus `pair` for each (G1, G2) pair given to the pairing check.
+fn pairing_cost(base: usize, pair: usize) -> BuiltinCost {
+	Box::new(move |input: &[u8]| -> U256 {
+		U256::from(base) + U256::from(pair) * U256::from(input.len() / 192)
+	})
+}
+
+/// Cost of `base` plus `vk_word` for each 32-byte word of the verification key and `input_word`
+/// for each 32-byte word of the primary input of a zkSNARK call, whose lengths are given by `arg_lens`.
+/// Inputs which are not valid ABI are charged `base` only; they fail on execution anyway.
+fn zksnark_cost(base: usize, vk_word: usize, input_word: usize, arg_lens: fn(&[u8]) -> Option<(usize, usize)>) -> BuiltinCost {
+	Box::new(move |input: &[u8]| -> U256 {
+		let words = |len: usize| U256::from(len / 32) + U256::from((len % 32 != 0) as usize);
+		let (vk_len, input_len) = arg_lens(input).unwrap_or((0, 0));
+		U256::from(base) + U256::from(vk_word) * words(vk_len) + U256::from(input_word) * words(input_len)
+	})
+}
+
+/// Cost of a call to the verification key registry: as `zksnark_cost`, plus `zksnark::REGISTER_SLOT_GAS`
+/// for each storage slot a registration writes.
+fn zksnark_registry_cost(base: usize, vk_word: usize, input_word: usize) -> BuiltinCost {
+	let cost = zksnark_cost(base, vk_word, input_word, zksnark::registry_arg_lens);
+	Box::new(move |input: &[u8]| -> U256 {
+		let slots = match zksnark::registry_arg_lens(input) {
+			Some((vk_len, _)) if vk_len > 0 => zksnark::register_slots(vk_len),
+			_ => 0,
+		};
+		(*cost)(input) + U256::from(zksnark::REGISTER_SLOT_GAS) * U256::from(slots)
+	})
+}
+
+/// Cost of a batch zkSNARK call: `base` plus `vk_word` for each 32-byte word of the verification key,
+/// which is set up once for the whole batch, plus `proof` for each proof and `input_word` for each
+/// 32-byte word of the primary inputs. Inputs which cannot be decoded are charged `base` only.
+fn zksnark_batch_cost(base: usize, vk_word: usize, proof: usize, input_word: usize) -> BuiltinCost {
+	Box::new(move |input: &[u8]| -> U256 {
+		let words = |len: usize| U256::from(len / 32) + U256::from((len % 32 != 0) as usize);
+		let (vk_len, proofs, input_len) = zksnark::batch_sizes(input).unwrap_or((0, 0, 0));
+		U256::from(base) + U256::from(vk_word) * words(vk_len) + U256::from(proof) * U256::from(proofs) + U256::from(input_word) * words(input_len)
+	})
 }
 
 /// Copy a bunch of bytes to a destination; if the `src` is too small to fill `dest`,
@@ -85,101 +208,219 @@ pub fn copy_to(src: &[u8], dest: &mut[u8]) {
 	}
 }
 
+type SharedExec = Arc<Fn(&[u8], &mut [u8], &mut BuiltinStorage) -> Result<(), BuiltinError> + Send + Sync>;
+
+lazy_static! {
+	static ref REGISTRY: RwLock<HashMap<String, (SharedExec, BuiltinOutputSize)>> = {
+		let mut natives: HashMap<String, (SharedExec, BuiltinOutputSize)> = HashMap::new();
+		natives.insert("identity".to_owned(), (Arc::new(identity_exec), input_sized_output));
+		natives.insert("ecrecover".to_owned(), (Arc::new(ecrecover_exec), word_output));
+		natives.insert("sha256".to_owned(), (Arc::new(sha256_exec), word_output));
+		natives.insert("ripemd160".to_owned(), (Arc::new(ripemd160_exec), word_output));
+		natives.insert(zksnark::BUILTIN_NAME.to_owned(), (Arc::new(zksnark::verify_exec), word_output));
+		natives.insert(zksnark::PGHR13_BUILTIN_NAME.to_owned(), (Arc::new(zksnark::pghr13_verify_exec), word_output));
+		natives.insert("zkSNARK_registry".to_owned(), (Arc::new(zksnark::registry_exec), word_output));
+		natives.insert("zkSNARK_batch".to_owned(), (Arc::new(zksnark::batch_exec), word_output));
+		natives.insert("alt_bn128_add".to_owned(), (Arc::new(bn128_add_exec), g1_output));
+		natives.insert("alt_bn128_mul".to_owned(), (Arc::new(bn128_mul_exec), g1_output));
+		natives.insert("alt_bn128_pairing".to_owned(), (Arc::new(bn128_pairing_exec), word_output));
+		RwLock::new(natives)
+	};
+}
+
+/// Register a native builtin under `name`, producing `output_size(input)` bytes of output,
+/// making it available to chain specs loaded afterwards.
+/// Registering under an existing name replaces the previous implementation.
+pub fn register_builtin<F>(name: &str, output_size: BuiltinOutputSize, exec: F) where F: Fn(&[u8], &mut [u8], &mut BuiltinStorage) -> Result<(), BuiltinError> + Send + Sync + 'static {
+	REGISTRY.write().insert(name.to_owned(), (Arc::new(exec), output_size));
+}
+
+/// Determine whether a builtin is registered under `name`.
+pub fn is_builtin_registered(name: &str) -> bool {
+	REGISTRY.read().contains_key(name)
+}
+
 /// Create a new builtin executor according to `name`.
-/// TODO: turn in to a factory with dynamic registration.
-pub fn new_builtin_exec(name: &str) -> Box<Fn(&[u8], &mut [u8])> {
-	match name {
-        "zkSNARK" => Box::new(move|input: &[u8], output: &mut[u8]| {
-            let outlen = output.len();
-            for i in 0..output.len() {
-                output[i] = 0;
-            }
-            let abitype = [ParamType::Bytes, ParamType::Bytes, ParamType::Bytes];
-            let v = input[4..].to_vec();
-            let decode = ethabi::Decoder::decode(&abitype, v);
-            if let Ok(tokens) = decode {
-                if tokens.len() == 3 {
-                    if let Token::Bytes(ref v1) = tokens[0] {
-                        if let Token::Bytes(ref v2) = tokens[1] {
-                            if let Token::Bytes(ref v3) = tokens[2] {
-                                let res = hackishlibsnarkbindings::snark_verify(v1, v2, v3);
-                                if res {
-                                    output[outlen - 1] = 1;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-		}),
-		"identity" => Box::new(move|input: &[u8], output: &mut[u8]| {
-			for i in 0..min(input.len(), output.len()) {
-				output[i] = input[i];
-			}
-		}),
-		"ecrecover" => Box::new(move|input: &[u8], output: &mut[u8]| {
-			#[repr(packed)]
-			#[derive(Debug)]
-			struct InType {
-				hash: H256,
-				v: H256,
-				r: H256,
-				s: H256,
-			}
-			let mut it: InType = InType { hash: H256::new(), v: H256::new(), r: H256::new(), s: H256::new() };
-			it.copy_raw(input);
-			if it.v == H256::from(&U256::from(27)) || it.v == H256::from(&U256::from(28)) {
-				let s = Signature::from_rsv(&it.r, &it.s, it.v[31] - 27);
-				if ec::is_valid(&s) {
-					if let Ok(p) = ec::recover(&s, &it.hash) {
-						let r = p.as_slice().sha3();
-						// NICE: optimise and separate out into populate-like function
-						for i in 0..min(32, output.len()) {
-							output[i] = if i < 12 {0} else {r[i]};
-						}
-					}
+/// Returns `None` if no builtin is registered under `name`.
+pub fn new_builtin_exec(name: &str) -> Option<BuiltinExec> {
+	new_builtin(name).map(|(exec, _)| exec)
+}
+
+/// Create a new builtin executor according to `name`, along with the size of its output.
+fn new_builtin(name: &str) -> Option<(BuiltinExec, BuiltinOutputSize)> {
+	REGISTRY.read().get(name).cloned().map(|(exec, output_size)| {
+		let exec = Box::new(move|input: &[u8], output: &mut[u8], storage: &mut BuiltinStorage| exec(input, output, storage)) as BuiltinExec;
+		(exec, output_size)
+	})
+}
+
+fn identity_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	for i in 0..min(input.len(), output.len()) {
+		output[i] = input[i];
+	}
+	Ok(())
+}
+
+fn ecrecover_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	#[repr(packed)]
+	#[derive(Debug)]
+	struct InType {
+		hash: H256,
+		v: H256,
+		r: H256,
+		s: H256,
+	}
+	let mut it: InType = InType { hash: H256::new(), v: H256::new(), r: H256::new(), s: H256::new() };
+	it.copy_raw(input);
+	if it.v == H256::from(&U256::from(27)) || it.v == H256::from(&U256::from(28)) {
+		let s = Signature::from_rsv(&it.r, &it.s, it.v[31] - 27);
+		if ec::is_valid(&s) {
+			if let Ok(p) = ec::recover(&s, &it.hash) {
+				let r = p.as_slice().sha3();
+				// NICE: optimise and separate out into populate-like function
+				for i in 0..min(32, output.len()) {
+					output[i] = if i < 12 {0} else {r[i]};
 				}
 			}
-		}),
-		"sha256" => Box::new(move|input: &[u8], output: &mut[u8]| {
-			let mut sha = Sha256::new();
-			sha.input(input);
-			if output.len() >= 32 {
-				sha.result(output);
-			} else {
-				let mut ret = H256::new();
-				sha.result(ret.as_slice_mut());
-				copy_to(&ret, output);
-			}
-		}),
-		"ripemd160" => Box::new(move|input: &[u8], output: &mut[u8]| {
-			let mut sha = Ripemd160::new();
-			sha.input(input);
-			let mut ret = H256::new();
-			sha.result(&mut ret.as_slice_mut()[12..32]);
-			copy_to(&ret, output);
-		}),
-		_ => {
-			panic!("invalid builtin name {}", name);
 		}
 	}
+	Ok(())
+}
+
+fn sha256_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let mut sha = Sha256::new();
+	sha.input(input);
+	if output.len() >= 32 {
+		sha.result(output);
+	} else {
+		let mut ret = H256::new();
+		sha.result(ret.as_slice_mut());
+		copy_to(&ret, output);
+	}
+	Ok(())
+}
+
+fn ripemd160_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let mut sha = Ripemd160::new();
+	sha.input(input);
+	let mut ret = H256::new();
+	sha.result(&mut ret.as_slice_mut()[12..32]);
+	copy_to(&ret, output);
+	Ok(())
+}
+
+fn read_fq(reader: &mut Read) -> Result<bn::Fq, BuiltinError> {
+	let mut buf = [0u8; 32];
+	reader.read_exact(&mut buf[..]).expect("readers are zero-extended or hold whole points; qed");
+	bn::Fq::from_slice(&buf[..]).map_err(|_| BuiltinError("invalid field element"))
+}
+
+/// Read a 32-byte big-endian scalar. `reader` must hold at least 32 bytes.
+pub fn read_fr(reader: &mut Read) -> Result<bn::Fr, BuiltinError> {
+	let mut buf = [0u8; 32];
+	reader.read_exact(&mut buf[..]).expect("readers are zero-extended or hold whole scalars; qed");
+	bn::Fr::from_slice(&buf[..]).map_err(|_| BuiltinError("invalid scalar"))
+}
+
+/// Read a G1 point encoded as its two 32-byte coordinates. `reader` must hold at least 64 bytes.
+pub fn read_g1(reader: &mut Read) -> Result<bn::G1, BuiltinError> {
+	use bn::Group;
+
+	let x = try!(read_fq(reader));
+	let y = try!(read_fq(reader));
+	// the point at infinity is encoded as (0, 0)
+	if x.is_zero() && y.is_zero() {
+		Ok(bn::G1::zero())
+	} else {
+		bn::AffineG1::new(x, y).map(Into::into).map_err(|_| BuiltinError("point not on curve"))
+	}
+}
+
+/// Read a G2 point encoded as its four 32-byte coordinates, imaginary parts first.
+/// `reader` must hold at least 128 bytes.
+pub fn read_g2(reader: &mut Read) -> Result<bn::G2, BuiltinError> {
+	use bn::Group;
+
+	// coordinates are encoded with the imaginary part first
+	let x_im = try!(read_fq(reader));
+	let x_re = try!(read_fq(reader));
+	let y_im = try!(read_fq(reader));
+	let y_re = try!(read_fq(reader));
+	let x = bn::Fq2::new(x_re, x_im);
+	let y = bn::Fq2::new(y_re, y_im);
+	if x.is_zero() && y.is_zero() {
+		Ok(bn::G2::zero())
+	} else {
+		bn::AffineG2::new(x, y).map(Into::into).map_err(|_| BuiltinError("point not on curve"))
+	}
+}
+
+fn write_g1(point: bn::G1, output: &mut [u8]) {
+	let mut buf = [0u8; 64];
+	// the point at infinity is left as (0, 0)
+	if let Some(affine) = bn::AffineG1::from_jacobian(point) {
+		affine.x().to_big_endian(&mut buf[0..32]).expect("buffer is exactly 32 bytes long; qed");
+		affine.y().to_big_endian(&mut buf[32..64]).expect("buffer is exactly 32 bytes long; qed");
+	}
+	copy_to(&buf, output);
+}
+
+/// alt_bn128 point addition. Input is two points `(x, y)` on G1, each coordinate 32 bytes;
+/// missing input is taken as zero.
+fn bn128_add_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let mut padded = input.chain(::std::io::repeat(0));
+	let p1 = try!(read_g1(&mut padded));
+	let p2 = try!(read_g1(&mut padded));
+	write_g1(p1 + p2, output);
+	Ok(())
+}
+
+/// alt_bn128 scalar multiplication. Input is a point `(x, y)` on G1 followed by a 32 byte scalar;
+/// missing input is taken as zero.
+fn bn128_mul_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let mut padded = input.chain(::std::io::repeat(0));
+	let p = try!(read_g1(&mut padded));
+	let fr = try!(read_fr(&mut padded));
+	write_g1(p * fr, output);
+	Ok(())
+}
+
+/// alt_bn128 pairing check. Input is a sequence of 192 byte `(G1, G2)` pairs; output is
+/// 1 if the product of their pairings is one, 0 otherwise.
+fn bn128_pairing_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	if input.len() % 192 != 0 {
+		return Err(BuiltinError("input length must be a multiple of 192"));
+	}
+
+	let mut pairs = Vec::with_capacity(input.len() / 192);
+	for chunk in input.chunks(192) {
+		let mut reader = chunk;
+		let a = try!(read_g1(&mut reader));
+		let b = try!(read_g2(&mut reader));
+		pairs.push((a, b));
+	}
+
+	let product = pairs.into_iter().fold(bn::Gt::one(), |acc, (a, b)| acc * bn::pairing(a, b));
+	let result = if product == bn::Gt::one() { U256::one() } else { U256::zero() };
+	copy_to(&H256::from(result), output);
+	Ok(())
 }
 
 #[test]
 fn identity() {
-	let f = new_builtin_exec("identity");
+	let f = new_builtin_exec("identity").unwrap();
 	let i = [0u8, 1, 2, 3];
 
 	let mut o2 = [255u8; 2];
-	f(&i[..], &mut o2[..]);
+	f(&i[..], &mut o2[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(i[0..2], o2);
 
 	let mut o4 = [255u8; 4];
-	f(&i[..], &mut o4[..]);
+	f(&i[..], &mut o4[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(i, o4);
 
 	let mut o8 = [255u8; 8];
-	f(&i[..], &mut o8[..]);
+	f(&i[..], &mut o8[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(i, o8[..4]);
 	assert_eq!([255u8; 4], o8[4..]);
 }
@@ -187,38 +428,38 @@ fn identity() {
 #[test]
 fn sha256() {
 	use rustc_serialize::hex::FromHex;
-	let f = new_builtin_exec("sha256");
+	let f = new_builtin_exec("sha256").unwrap();
 	let i = [0u8; 0];
 
 	let mut o = [255u8; 32];
-	f(&i[..], &mut o[..]);
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").unwrap())[..]);
 
 	let mut o8 = [255u8; 8];
-	f(&i[..], &mut o8[..]);
+	f(&i[..], &mut o8[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o8[..], &(FromHex::from_hex("e3b0c44298fc1c14").unwrap())[..]);
 
 	let mut o34 = [255u8; 34];
-	f(&i[..], &mut o34[..]);
+	f(&i[..], &mut o34[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o34[..], &(FromHex::from_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855ffff").unwrap())[..]);
 }
 
 #[test]
 fn ripemd160() {
 	use rustc_serialize::hex::FromHex;
-	let f = new_builtin_exec("ripemd160");
+	let f = new_builtin_exec("ripemd160").unwrap();
 	let i = [0u8; 0];
 
 	let mut o = [255u8; 32];
-	f(&i[..], &mut o[..]);
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31").unwrap())[..]);
 
 	let mut o8 = [255u8; 8];
-	f(&i[..], &mut o8[..]);
+	f(&i[..], &mut o8[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o8[..], &(FromHex::from_hex("0000000000000000").unwrap())[..]);
 
 	let mut o34 = [255u8; 34];
-	f(&i[..], &mut o34[..]);
+	f(&i[..], &mut o34[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o34[..], &(FromHex::from_hex("0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31ffff").unwrap())[..]);
 }
 
@@ -233,90 +474,478 @@ fn ecrecover() {
 	let s = k.sign(&m).unwrap();
 	println!("Signed: {}", s);*/
 
-	let f = new_builtin_exec("ecrecover");
+	let f = new_builtin_exec("ecrecover").unwrap();
 	let i = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b650acf9d3f5f0a2c799776a1254355d5f4061762a237396a99a0e0e3fc2bcd6729514a0dacb2e623ac4abd157cb18163ff942280db4d5caad66ddf941ba12e03").unwrap();
 
 	let mut o = [255u8; 32];
-	f(&i[..], &mut o[..]);
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("000000000000000000000000c08b5542d177ac6686946920409741463a15dddb").unwrap())[..]);
 
 	let mut o8 = [255u8; 8];
-	f(&i[..], &mut o8[..]);
+	f(&i[..], &mut o8[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o8[..], &(FromHex::from_hex("0000000000000000").unwrap())[..]);
 
 	let mut o34 = [255u8; 34];
-	f(&i[..], &mut o34[..]);
+	f(&i[..], &mut o34[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o34[..], &(FromHex::from_hex("000000000000000000000000c08b5542d177ac6686946920409741463a15dddbffff").unwrap())[..]);
 
 	let i_bad = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001a650acf9d3f5f0a2c799776a1254355d5f4061762a237396a99a0e0e3fc2bcd6729514a0dacb2e623ac4abd157cb18163ff942280db4d5caad66ddf941ba12e03").unwrap();
 	let mut o = [255u8; 32];
-	f(&i_bad[..], &mut o[..]);
+	f(&i_bad[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())[..]);
 
 	let i_bad = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b000000000000000000000000000000000000000000000000000000000000001b0000000000000000000000000000000000000000000000000000000000000000").unwrap();
 	let mut o = [255u8; 32];
-	f(&i_bad[..], &mut o[..]);
+	f(&i_bad[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())[..]);
 
 	let i_bad = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001b").unwrap();
 	let mut o = [255u8; 32];
-	f(&i_bad[..], &mut o[..]);
+	f(&i_bad[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())[..]);
 
 	let i_bad = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001bffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff000000000000000000000000000000000000000000000000000000000000001b").unwrap();
 	let mut o = [255u8; 32];
-	f(&i_bad[..], &mut o[..]);
+	f(&i_bad[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())[..]);
 
 	let i_bad = FromHex::from_hex("47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b000000000000000000000000000000000000000000000000000000000000001bffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
 	let mut o = [255u8; 32];
-	f(&i_bad[..], &mut o[..]);
+	f(&i_bad[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())[..]);
 
 	// TODO: Should this (corrupted version of the above) fail rather than returning some address?
 /*	let i_bad = FromHex::from_hex("48173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad000000000000000000000000000000000000000000000000000000000000001b650acf9d3f5f0a2c799776a1254355d5f4061762a237396a99a0e0e3fc2bcd6729514a0dacb2e623ac4abd157cb18163ff942280db4d5caad66ddf941ba12e03").unwrap();
 	let mut o = [255u8; 32];
-	f(&i_bad[..], &mut o[..]);
+	f(&i_bad[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(&o[..], &(FromHex::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())[..]);*/
 }
 
 #[test]
-#[should_panic]
 fn from_unknown_linear() {
-	let _ = Builtin::from_named_linear("dw", 10, 20);
+	assert!(Builtin::from_named_linear("dw", 10, 20).is_none());
+}
+
+#[test]
+fn from_unknown_json() {
+	let b = Builtin::from_json(ethjson::spec::Builtin {
+		name: "dw".to_owned(),
+		pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
+			base: 10,
+			word: 20,
+		}),
+		activate_at: None,
+		deactivate_at: None,
+	});
+
+	match b {
+		Err(Error::UnknownBuiltinName(ref name)) if name == "dw" => {},
+		_ => panic!("Expected unknown builtin name error"),
+	}
+}
+
+#[test]
+fn register_custom() {
+	register_builtin("test_reverse", input_sized_output, |input: &[u8], output: &mut [u8], _: &mut BuiltinStorage| {
+		for (o, i) in output.iter_mut().zip(input.iter().rev()) {
+			*o = *i;
+		}
+		Ok(())
+	});
+	assert!(is_builtin_registered("test_reverse"));
+
+	let b = Builtin::from_named_linear("test_reverse", 10, 20).unwrap();
+	let i = [0u8, 1, 2, 3];
+	let mut o = [255u8; 4];
+	b.execute(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!([3u8, 2, 1, 0], o);
+	assert_eq!(b.output_size(&i[..]), 4);
 }
 
 #[test]
 fn from_named_linear() {
-	let b = Builtin::from_named_linear("identity", 10, 20);
-	assert_eq!((*b.cost)(0), U256::from(10));
-	assert_eq!((*b.cost)(1), U256::from(30));
-	assert_eq!((*b.cost)(32), U256::from(30));
-	assert_eq!((*b.cost)(33), U256::from(50));
+	let b = Builtin::from_named_linear("identity", 10, 20).unwrap();
+	assert_eq!(b.cost(&[]), U256::from(10));
+	assert_eq!(b.cost(&[0u8; 1]), U256::from(30));
+	assert_eq!(b.cost(&[0u8; 32]), U256::from(30));
+	assert_eq!(b.cost(&[0u8; 33]), U256::from(50));
 
 	let i = [0u8, 1, 2, 3];
 	let mut o = [255u8; 4];
-	(*b.execute)(&i[..], &mut o[..]);
+	(*b.execute)(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(i, o);
 }
 
 #[test]
 fn from_json() {
-	let b = Builtin::from(ethjson::spec::Builtin {
+	let b = Builtin::from_json(ethjson::spec::Builtin {
 		name: "identity".to_owned(),
 		pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
 			base: 10,
 			word: 20,
-		})
-	});
+		}),
+		activate_at: None,
+		deactivate_at: None,
+	}).unwrap();
 
-	assert_eq!((*b.cost)(0), U256::from(10));
-	assert_eq!((*b.cost)(1), U256::from(30));
-	assert_eq!((*b.cost)(32), U256::from(30));
-	assert_eq!((*b.cost)(33), U256::from(50));
+	assert_eq!(b.cost(&[]), U256::from(10));
+	assert_eq!(b.cost(&[0u8; 1]), U256::from(30));
+	assert_eq!(b.cost(&[0u8; 32]), U256::from(30));
+	assert_eq!(b.cost(&[0u8; 33]), U256::from(50));
 
 	let i = [0u8, 1, 2, 3];
 	let mut o = [255u8; 4];
-	(*b.execute)(&i[..], &mut o[..]);
+	(*b.execute)(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
 	assert_eq!(i, o);
 }
+
+#[test]
+fn zksnark_short_input() {
+	let f = new_builtin_exec("zkSNARK").unwrap();
+	let mut o = [255u8; 32];
+	assert_eq!(f(&[0u8, 1, 2][..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("input too short")));
+	assert_eq!([255u8; 32], o);
+}
+
+#[test]
+fn zksnark_invalid_abi() {
+	let f = new_builtin_exec("zkSNARK").unwrap();
+	let i = [0u8; 4];
+	let mut o = [255u8; 32];
+	assert_eq!(f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("invalid ABI encoding")));
+	assert_eq!([255u8; 32], o);
+}
+
+#[test]
+fn zksnark_pghr13() {
+	use rustc_serialize::hex::FromHex;
+	let f = new_builtin_exec("zkSNARK_pghr13").unwrap();
+	let vk: Bytes = include_str!("../res/zksnark/vk.hex").from_hex().unwrap();
+	let proof: Bytes = include_str!("../res/zksnark/proof.hex").from_hex().unwrap();
+	let primary_input: Bytes = include_str!("../res/zksnark/input.hex").from_hex().unwrap();
+
+	let mut o = [255u8; 32];
+	f(&zksnark::encode_verify_call(&vk, &proof, &primary_input), &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(H256::from(o), H256::from(U256::one()));
+
+	let mut o = [255u8; 32];
+	f(&zksnark::encode_verify_call(&vk, &proof, &H256::from(U256::from(3))), &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(o, [0u8; 32]);
+
+	let mut o = [255u8; 32];
+	assert_eq!(f(&zksnark::encode_verify_call(&vk[1..], &proof, &primary_input), &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("invalid verification key length")));
+	assert_eq!(o, [255u8; 32]);
+}
+
+#[test]
+fn bn128_add() {
+	use rustc_serialize::hex::FromHex;
+	let f = new_builtin_exec("alt_bn128_add").unwrap();
+
+	// zero + zero
+	let mut o = [255u8; 64];
+	f(&[][..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(&o[..], &[0u8; 64][..]);
+
+	// generator + generator
+	let i = FromHex::from_hex("\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000002\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+	let mut o = [255u8; 64];
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(&o[..], &(FromHex::from_hex("\
+		030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+		15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4").unwrap())[..]);
+
+	// point not on curve
+	let i = FromHex::from_hex("\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+	let mut o = [255u8; 64];
+	assert_eq!(f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("point not on curve")));
+	assert_eq!(&o[..], &[255u8; 64][..]);
+}
+
+#[test]
+fn bn128_mul() {
+	use rustc_serialize::hex::FromHex;
+	let f = new_builtin_exec("alt_bn128_mul").unwrap();
+
+	// generator * 2
+	let i = FromHex::from_hex("\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000002\
+		0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+	let mut o = [255u8; 64];
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(&o[..], &(FromHex::from_hex("\
+		030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+		15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4").unwrap())[..]);
+
+	// generator * 0
+	let i = FromHex::from_hex("\
+		0000000000000000000000000000000000000000000000000000000000000001\
+		0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+	let mut o = [255u8; 64];
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(&o[..], &[0u8; 64][..]);
+}
+
+#[test]
+fn bn128_pairing() {
+	let f = new_builtin_exec("alt_bn128_pairing").unwrap();
+
+	// empty product is one
+	let mut o = [255u8; 32];
+	f(&[][..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(&o[..], &*H256::from(U256::one()));
+
+	// pairing with the point at infinity is one
+	let i = [0u8; 192];
+	let mut o = [255u8; 32];
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(&o[..], &*H256::from(U256::one()));
+
+	let i = [0u8; 191];
+	let mut o = [255u8; 32];
+	assert_eq!(f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("input length must be a multiple of 192")));
+}
+
+#[test]
+fn from_json_activation() {
+	let b = Builtin::from_json(ethjson::spec::Builtin {
+		name: "identity".to_owned(),
+		pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
+			base: 10,
+			word: 20,
+		}),
+		activate_at: Some(ethjson::uint::Uint(U256::from(100))),
+		deactivate_at: Some(ethjson::uint::Uint(U256::from(200))),
+	}).unwrap();
+
+	assert!(!b.is_active(0));
+	assert!(!b.is_active(99));
+	assert!(b.is_active(100));
+	assert!(b.is_active(199));
+	assert!(!b.is_active(200));
+
+	assert!(Builtin::from_named_linear("identity", 10, 20).unwrap().is_active(0));
+}
+
+#[test]
+fn from_json_pairing_pricing() {
+	let b = Builtin::from_json(ethjson::spec::Builtin {
+		name: "alt_bn128_pairing".to_owned(),
+		pricing: ethjson::spec::Pricing::AltBn128Pairing(ethjson::spec::AltBn128Pairing {
+			base: 100000,
+			pair: 80000,
+		}),
+		activate_at: None,
+		deactivate_at: None,
+	}).unwrap();
+
+	assert_eq!(b.cost(&[]), U256::from(100000));
+	assert_eq!(b.cost(&[0u8; 191]), U256::from(100000));
+	assert_eq!(b.cost(&[0u8; 192]), U256::from(180000));
+	assert_eq!(b.cost(&[0u8; 384]), U256::from(260000));
+}
+
+#[test]
+fn from_json_zksnark_pricing() {
+	use rustc_serialize::hex::FromHex;
+
+	let b = Builtin::from_json(ethjson::spec::Builtin {
+		name: "zkSNARK".to_owned(),
+		pricing: ethjson::spec::Pricing::ZkSnark(ethjson::spec::ZkSnark {
+			base: 1000000,
+			vk_word: 1000,
+			input_word: 500,
+		}),
+		activate_at: None,
+		deactivate_at: None,
+	}).unwrap();
+
+	// selector, then heads of (bytes vk, bytes proof, bytes input) followed by
+	// a 33 byte vk, an empty proof and a 64 byte input
+	let i = FromHex::from_hex("\
+		00000000\
+		0000000000000000000000000000000000000000000000000000000000000060\
+		00000000000000000000000000000000000000000000000000000000000000c0\
+		00000000000000000000000000000000000000000000000000000000000000e0\
+		0000000000000000000000000000000000000000000000000000000000000021\
+		0000000000000000000000000000000000000000000000000000000000000000\
+		0000000000000000000000000000000000000000000000000000000000000000\
+		0000000000000000000000000000000000000000000000000000000000000000\
+		0000000000000000000000000000000000000000000000000000000000000040\
+		0000000000000000000000000000000000000000000000000000000000000000\
+		0000000000000000000000000000000000000000000000000000000000000000").unwrap();
+	assert_eq!(b.cost(&i), U256::from(1000000 + 2 * 1000 + 2 * 500));
+
+	// malformed input is charged the base price
+	assert_eq!(b.cost(&[0u8; 3]), U256::from(1000000));
+	assert_eq!(b.cost(&[0u8; 36]), U256::from(1000000));
+}
+
+#[test]
+fn zksnark_registry() {
+	use rustc_serialize::hex::FromHex;
+	let f = new_builtin_exec("zkSNARK_registry").unwrap();
+	let mut storage = HashMap::<H256, H256>::new();
+
+	let vk: Bytes = include_str!("../res/zksnark/vk.hex").from_hex().unwrap();
+	let proof: Bytes = include_str!("../res/zksnark/proof.hex").from_hex().unwrap();
+	let primary_input: Bytes = include_str!("../res/zksnark/input.hex").from_hex().unwrap();
+
+	let mut i = zksnark::selector(zksnark::REGISTER_VK).to_vec();
+	i.extend_from_slice(&H256::from(U256::from(0x20)));
+	i.extend_from_slice(&H256::from(U256::from(vk.len())));
+	i.extend_from_slice(&vk);
+	let mut o = [0u8; 32];
+	f(&i[..], &mut o[..], &mut storage).unwrap();
+	assert_eq!(H256::from(o), vk.sha3());
+
+	// verifying against the registered key
+	let mut i = zksnark::selector(zksnark::VERIFY_REGISTERED).to_vec();
+	i.extend_from_slice(&vk.sha3());
+	i.extend_from_slice(&H256::from(U256::from(0x60)));
+	i.extend_from_slice(&H256::from(U256::from(0x80 + proof.len())));
+	i.extend_from_slice(&H256::from(U256::from(proof.len())));
+	i.extend_from_slice(&proof);
+	i.extend_from_slice(&H256::from(U256::from(primary_input.len())));
+	i.extend_from_slice(&primary_input);
+	let mut o = [0u8; 32];
+	f(&i[..], &mut o[..], &mut storage).unwrap();
+	assert_eq!(H256::from(o), H256::from(U256::one()));
+
+	// keys which do not parse are not registered
+	let mut i = zksnark::selector(zksnark::REGISTER_VK).to_vec();
+	i.extend_from_slice(&H256::from(U256::from(0x20)));
+	i.extend_from_slice(&H256::from(U256::from(32)));
+	i.extend_from_slice(&[7u8; 32]);
+	let mut o = [255u8; 32];
+	assert_eq!(f(&i[..], &mut o[..], &mut storage), Err(BuiltinError("invalid verification key length")));
+
+	// verifying against an unregistered key fails
+	let mut i = zksnark::selector(zksnark::VERIFY_REGISTERED).to_vec();
+	i.extend_from_slice(&b"unknown".sha3());
+	i.extend_from_slice(&H256::from(U256::from(0x60)));
+	i.extend_from_slice(&H256::from(U256::from(0x80)));
+	i.extend_from_slice(&H256::new());
+	i.extend_from_slice(&H256::new());
+	assert_eq!(f(&i[..], &mut o[..], &mut storage), Err(BuiltinError("unknown verification key")));
+	assert_eq!(o, [255u8; 32]);
+}
+
+#[test]
+fn from_json_zksnark_registry_pricing() {
+	let b = Builtin::from_json(ethjson::spec::Builtin {
+		name: "zkSNARK_registry".to_owned(),
+		pricing: ethjson::spec::Pricing::ZkSnarkRegistry(ethjson::spec::ZkSnark {
+			base: 1000000,
+			vk_word: 1000,
+			input_word: 500,
+		}),
+		activate_at: None,
+		deactivate_at: None,
+	}).unwrap();
+
+	// a 40 byte key takes two words, written with its length to three slots
+	let mut i = zksnark::selector(zksnark::REGISTER_VK).to_vec();
+	i.extend_from_slice(&H256::from(U256::from(0x20)));
+	i.extend_from_slice(&H256::from(U256::from(40)));
+	assert_eq!(b.cost(&i), U256::from(1000000 + 2 * 1000 + 3 * zksnark::REGISTER_SLOT_GAS));
+
+	// verification writes nothing
+	let mut i = zksnark::selector(zksnark::VERIFY_REGISTERED).to_vec();
+	i.extend_from_slice(&H256::new());
+	i.extend_from_slice(&H256::from(U256::from(0x60)));
+	i.extend_from_slice(&H256::from(U256::from(0x80)));
+	i.extend_from_slice(&H256::new());
+	i.extend_from_slice(&H256::from(U256::from(64)));
+	assert_eq!(b.cost(&i), U256::from(1000000 + 2 * 500));
+}
+
+#[test]
+fn zksnark_batch_empty() {
+	let f = new_builtin_exec("zkSNARK_batch").unwrap();
+
+	// nothing to verify: no bit set
+	let vk: Bytes = include_str!("../res/zksnark/vk.hex").from_hex().unwrap();
+	let i = zksnark::encode_batch_call(&vk, &[]);
+	let mut o = [255u8; 32];
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(o, [0u8; 32]);
+}
+
+#[test]
+fn zksnark_batch() {
+	let f = new_builtin_exec("zkSNARK_batch").unwrap();
+
+	let vk: Bytes = include_str!("../res/zksnark/vk.hex").from_hex().unwrap();
+	let proof: Bytes = include_str!("../res/zksnark/proof.hex").from_hex().unwrap();
+	let primary_input: Bytes = include_str!("../res/zksnark/input.hex").from_hex().unwrap();
+	let wrong_input = H256::from(U256::from(3)).to_vec();
+
+	// only the first and third proofs are for their inputs
+	let pairs = vec![(proof.clone(), primary_input.clone()), (proof.clone(), wrong_input), (proof, primary_input)];
+	let i = zksnark::encode_batch_call(&vk, &pairs);
+	let mut o = [255u8; 32];
+	f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()).unwrap();
+	assert_eq!(o, H256::from(U256::from(0b101)).0);
+
+	// the shared key is checked before any proof
+	let i = zksnark::encode_batch_call(&[1u8; 40], &pairs);
+	assert_eq!(f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("invalid verification key length")));
+}
+
+#[test]
+fn zksnark_batch_invalid_input() {
+	use ethabi;
+	let f = new_builtin_exec("zkSNARK_batch").unwrap();
+	let mut o = [255u8; 32];
+
+	assert_eq!(f(&[0u8, 1, 2][..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("input too short")));
+	assert_eq!(f(&[0u8; 4][..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("invalid ABI encoding")));
+
+	// two proofs but a single input
+	let mut i = zksnark::selector(zksnark::VERIFY_BATCH).to_vec();
+	i.extend(ethabi::Encoder::encode(vec![
+		ethabi::Token::Bytes(vec![1u8; 40]),
+		ethabi::Token::Array(vec![ethabi::Token::Bytes(vec![2u8; 8]), ethabi::Token::Bytes(vec![3u8; 8])]),
+		ethabi::Token::Array(vec![ethabi::Token::Bytes(vec![4u8; 8])]),
+	]));
+	assert_eq!(f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("proof and input counts differ")));
+
+	// one more proof than there are bits in the result
+	let pairs = vec![(vec![], vec![]); zksnark::MAX_BATCH + 1];
+	let i = zksnark::encode_batch_call(&[1u8; 40], &pairs);
+	assert_eq!(f(&i[..], &mut o[..], &mut HashMap::<H256, H256>::new()), Err(BuiltinError("too many proofs")));
+
+	assert_eq!(o, [255u8; 32]);
+}
+
+#[test]
+fn from_json_zksnark_batch_pricing() {
+	let b = Builtin::from_json(ethjson::spec::Builtin {
+		name: "zkSNARK_batch".to_owned(),
+		pricing: ethjson::spec::Pricing::ZkSnarkBatch(ethjson::spec::ZkSnarkBatch {
+			base: 1000000,
+			vk_word: 1000,
+			proof: 200000,
+			input_word: 500,
+		}),
+		activate_at: None,
+		deactivate_at: None,
+	}).unwrap();
+
+	// the 33 byte vk is charged once for all three proofs
+	let pairs = vec![(vec![2u8; 8], vec![3u8; 64]), (vec![2u8; 8], vec![3u8; 1]), (vec![2u8; 8], vec![])];
+	let i = zksnark::encode_batch_call(&[1u8; 33], &pairs);
+	assert_eq!(b.cost(&i), U256::from(1000000 + 2 * 1000 + 3 * 200000 + 3 * 500));
+
+	// malformed input is charged the base price
+	assert_eq!(b.cost(&[0u8; 3]), U256::from(1000000));
+	assert_eq!(b.cost(&[0u8; 36]), U256::from(1000000));
+}
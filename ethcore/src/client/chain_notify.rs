@@ -42,6 +42,11 @@ pub trait ChainNotify : Send + Sync {
 	fn stop(&self) {
 		// does nothing by default
 	}
+
+	/// fires when the engine has a consensus message for the network
+	fn broadcast(&self, _message: Vec<u8>) {
+		// does nothing by default
+	}
 }
 
 impl IpcConfig for ChainNotify { }
@@ -19,12 +19,13 @@ use std::ops::Deref;
 use std::sync::{Arc, Weak};
 use std::path::{Path, PathBuf};
 use std::fmt;
+use std::mem;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
 use std::time::{Instant};
 use time::precise_time_ns;
 
 // util
-use util::{journaldb, rlp, Bytes, Stream, View, PerfTimer, Itertools, Mutex, RwLock};
+use util::{journaldb, rlp, Bytes, Stream, View, PerfTimer, Itertools, Mutex, RwLock, HeapSizeOf, Mismatch};
 use util::journaldb::JournalDB;
 use util::rlp::{RlpStream, Rlp, UntrustedRlp};
 use util::numbers::*;
@@ -35,8 +36,8 @@ use util::kvdb::*;
 
 // other
 use views::BlockView;
-use error::{ImportError, ExecutionError, BlockError, ImportResult};
-use header::BlockNumber;
+use error::{ImportError, ExecutionError, BlockError, EngineError, ImportResult, Error};
+use header::{BlockNumber, Header};
 use state::State;
 use spec::Spec;
 use basic_types::Seal;
@@ -45,24 +46,26 @@ use views::HeaderView;
 use service::ClientIoMessage;
 use env_info::LastHashes;
 use verification;
+use zksnark;
 use verification::{PreverifiedBlock, Verifier};
 use block::*;
-use transaction::{LocalizedTransaction, SignedTransaction, Action};
+use transaction::{LocalizedTransaction, Transaction, SignedTransaction, Action};
 use blockchain::extras::TransactionAddress;
 use types::filter::Filter;
 use log_entry::LocalizedLogEntry;
 use block_queue::{BlockQueue, BlockQueueInfo};
 use blockchain::{BlockChain, BlockProvider, TreeRoute, ImportRoute};
 use client::{BlockID, TransactionID, UncleID, TraceId, ClientConfig,
-	DatabaseCompactionProfile, BlockChainClient, MiningBlockChainClient,
-	TraceFilter, CallAnalytics, BlockImportError, Mode, ChainNotify};
+	DatabaseCompactionProfile, BlockChainClient, MiningBlockChainClient, DebuggingBlockChainClient,
+	TraceFilter, CallAnalytics, BlockImportError, Mode, ChainNotify, SnarkVerification, DebugSession, DebugFrame, StructLogger};
 use client::Error as ClientError;
 use env_info::EnvInfo;
 use executive::{Executive, Executed, TransactOptions, contract_address};
+use types::executed::ReplayError;
 use receipt::LocalizedReceipt;
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace;
-use evm::Factory as EvmFactory;
+use evm::{Factory as EvmFactory, Schedule};
 use miner::{Miner, MinerService};
 use util::TrieFactory;
 
@@ -91,6 +94,8 @@ pub struct ClientReport {
 	pub gas_processed: U256,
 	/// Memory used by state DB
 	pub state_db_mem: usize,
+	/// Memory used by the code analysis cache of the EVM
+	pub evm_cache_mem: usize,
 }
 
 impl ClientReport {
@@ -223,7 +228,10 @@ impl Client {
 			import_lock: Mutex::new(()),
 			panic_handler: panic_handler,
 			verifier: verification::new(config.verifier_type),
-			vm_factory: Arc::new(EvmFactory::new(config.vm_type)),
+			vm_factory: Arc::new(match config.jump_table_size {
+				Some(size) => EvmFactory::with_cache_size(config.vm_type, size),
+				None => EvmFactory::new(config.vm_type),
+			}),
 			trie_factory: TrieFactory::new(config.trie_spec),
 			miner: miner,
 			io_channel: message_channel,
@@ -231,7 +239,9 @@ impl Client {
 			queue_transactions: AtomicUsize::new(0),
 			last_hashes: RwLock::new(VecDeque::new()),
 		};
-		Ok(Arc::new(client))
+		let client = Arc::new(client);
+		client.engine.register_client(Arc::downgrade(&client));
+		Ok(client)
 	}
 
 	/// Adds an actor to be notified on certain events
@@ -280,6 +290,77 @@ impl Client {
 		last_hashes
 	}
 
+	/// State and environment to execute a call in: the latest state, with the sender
+	/// given enough balance to pay for the call.
+	fn call_state(&self, t: &SignedTransaction) -> Result<(State, EnvInfo), ExecutionError> {
+		let header = self.block_header(BlockID::Latest).unwrap();
+		let view = HeaderView::new(&header);
+		let last_hashes = self.build_last_hashes(view.hash());
+		let env_info = EnvInfo {
+			number: view.number(),
+			author: view.author(),
+			timestamp: view.timestamp(),
+			difficulty: view.difficulty(),
+			last_hashes: last_hashes,
+			gas_used: U256::zero(),
+			gas_limit: U256::max_value(),
+		};
+		// that's just a copy of the state.
+		let mut state = self.state();
+		let sender = try!(t.sender().map_err(|e| {
+			let message = format!("Transaction malformed: {:?}", e);
+			ExecutionError::TransactionMalformed(message)
+		}));
+		let balance = state.balance(&sender);
+		let needed_balance = t.value + t.gas * t.gas_price;
+		if balance < needed_balance {
+			// give the sender a sufficient balance
+			state.add_balance(&sender, &(needed_balance - balance));
+		}
+		Ok((state, env_info))
+	}
+
+	/// Transaction `id`, along with the state and environment it was executed in:
+	/// the state of the parent block with the transactions before it in the block applied.
+	fn replay_state(&self, id: TransactionID) -> Result<(State, EnvInfo, SignedTransaction), ReplayError> {
+		let address = try!(self.transaction_address(id).ok_or(ReplayError::TransactionNotFound));
+		let block_data = try!(self.block(BlockID::Hash(address.block_hash)).ok_or(ReplayError::TransactionNotFound));
+		let block = BlockView::new(&block_data);
+		let header = block.header_view();
+		let mut transactions = block.transactions();
+		if address.index >= transactions.len() {
+			return Err(ReplayError::TransactionNotFound);
+		}
+		let mut state = try!(self.state_at(BlockID::Hash(header.parent_hash())).ok_or(ReplayError::StatePruned));
+
+		let mut env_info = EnvInfo {
+			number: header.number(),
+			author: header.author(),
+			timestamp: header.timestamp(),
+			difficulty: header.difficulty(),
+			last_hashes: self.build_last_hashes(header.parent_hash()),
+			gas_used: U256::zero(),
+			gas_limit: header.gas_limit(),
+		};
+		let engine = self.engine.deref().deref();
+		for t in &transactions[..address.index] {
+			let options = TransactOptions { tracing: false, vm_tracing: false, profiling: false, check_nonce: true };
+			let executed = try!(Executive::new(&mut state, &env_info, engine, &self.vm_factory).transact(t, options).map_err(ReplayError::Execution));
+			env_info.gas_used = env_info.gas_used + executed.gas_used;
+		}
+		Ok((state, env_info, transactions.swap_remove(address.index)))
+	}
+
+	/// Debugging session executing `t` on `state`.
+	fn debug_session(&self, mut state: State, env_info: EnvInfo, t: SignedTransaction, check_nonce: bool) -> DebugSession {
+		let engine = self.engine.clone();
+		let vm_factory = self.vm_factory.clone();
+		DebugSession::new(move |vm_tracer| {
+			Executive::new(&mut state, &env_info, engine.deref().deref(), &vm_factory)
+				.transact_with_tracer(&t, check_nonce, trace::NoopTracer, vm_tracer)
+		})
+	}
+
 	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, ()> {
 		let engine = self.engine.deref().deref();
 		let header = &block.header;
@@ -515,6 +596,71 @@ impl Client {
 		})
 	}
 
+	/// Makes a non-persistent call of `data` to `address` on top of the state at block `id`,
+	/// returning its output.
+	pub fn call_contract(&self, id: BlockID, address: Address, data: Bytes) -> Result<Bytes, String> {
+		let header = try!(self.block_header(id.clone()).ok_or_else(|| "Unknown block".to_owned()));
+		let mut state = try!(self.state_at(id).ok_or_else(|| "State not available".to_owned()));
+		let view = HeaderView::new(&header);
+		let env_info = EnvInfo {
+			number: view.number(),
+			author: view.author(),
+			timestamp: view.timestamp(),
+			difficulty: view.difficulty(),
+			last_hashes: self.build_last_hashes(view.hash()),
+			gas_used: U256::zero(),
+			gas_limit: U256::max_value(),
+		};
+		let transaction = Transaction {
+			nonce: state.nonce(&Address::default()),
+			action: Action::Call(address),
+			gas: U256::from(50_000_000),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: data,
+		}.fake_sign(Address::default());
+		let options = TransactOptions { tracing: false, vm_tracing: false, profiling: false, check_nonce: false };
+		Executive::new(&mut state, &env_info, self.engine.deref().deref(), &self.vm_factory)
+			.transact(&transaction, options)
+			.map(|executed| executed.output)
+			.map_err(|e| format!("{:?}", e))
+	}
+
+	/// The consensus engine of the chain.
+	pub fn engine(&self) -> &Engine {
+		self.engine.deref().deref()
+	}
+
+	/// RLP of the block the miner is preparing for sealing, without its seal, preparing one if needed.
+	pub fn sealing_block(&self) -> Option<Bytes> {
+		self.miner.map_sealing_work(self, |b| b.base().rlp_bytes(Seal::Without))
+	}
+
+	/// Check a block proposed to the consensus engine before it is sealed: it must build on a known
+	/// block, and executing its transactions and uncles there must give the header it claims.
+	pub fn verify_proposal(&self, block_bytes: &[u8]) -> Result<(), Error> {
+		let block: Block = try!(UntrustedRlp::new(block_bytes).as_val());
+		let header = &block.header;
+		let parent = try!(self.chain.block_header(header.parent_hash()).ok_or_else(|| BlockError::UnknownParent(header.parent_hash().clone())));
+		if header.number() != parent.number() + 1 {
+			return Err(From::from(BlockError::InvalidNumber(Mismatch { expected: parent.number() + 1, found: header.number() })));
+		}
+		let last_hashes = self.build_last_hashes(header.parent_hash().clone());
+		let db = self.state_db.lock().boxed_clone();
+		let locked_block = try!(enact(header, &block.transactions, &block.uncles, self.engine(), false, db, &parent, last_hashes, &self.vm_factory, self.trie_factory.clone()));
+		self.verifier.verify_block_final(header, locked_block.block().header())
+	}
+
+	/// Let the miner reconsider sealing its pending block, e.g. when the engine's turn to seal has come.
+	pub fn update_sealing(&self) {
+		self.miner.update_sealing(self)
+	}
+
+	/// Broadcast a consensus message of the engine to the network.
+	pub fn broadcast_consensus_message(&self, message: Bytes) {
+		self.notify(|notify| notify.broadcast(message.clone()));
+	}
+
 	/// Get a copy of the best block's state.
 	pub fn state(&self) -> State {
 		State::from_existing(
@@ -534,6 +680,7 @@ impl Client {
 	pub fn report(&self) -> ClientReport {
 		let mut report = self.report.read().clone();
 		report.state_db_mem = self.state_db.lock().mem_used();
+		report.evm_cache_mem = self.vm_factory.heap_size_of_children();
 		report
 	}
 
@@ -635,31 +782,8 @@ impl Client {
 
 impl BlockChainClient for Client {
 	fn call(&self, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, ExecutionError> {
-		let header = self.block_header(BlockID::Latest).unwrap();
-		let view = HeaderView::new(&header);
-		let last_hashes = self.build_last_hashes(view.hash());
-		let env_info = EnvInfo {
-			number: view.number(),
-			author: view.author(),
-			timestamp: view.timestamp(),
-			difficulty: view.difficulty(),
-			last_hashes: last_hashes,
-			gas_used: U256::zero(),
-			gas_limit: U256::max_value(),
-		};
-		// that's just a copy of the state.
-		let mut state = self.state();
-		let sender = try!(t.sender().map_err(|e| {
-			let message = format!("Transaction malformed: {:?}", e);
-			ExecutionError::TransactionMalformed(message)
-		}));
-		let balance = state.balance(&sender);
-		let needed_balance = t.value + t.gas * t.gas_price;
-		if balance < needed_balance {
-			// give the sender a sufficient balance
-			state.add_balance(&sender, &(needed_balance - balance));
-		}
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let (mut state, env_info) = try!(self.call_state(t));
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, profiling: analytics.profiling, check_nonce: false };
 		let mut ret = Executive::new(&mut state, &env_info, self.engine.deref().deref(), &self.vm_factory).transact(t, options);
 
 		// TODO gav move this into Executive.
@@ -671,6 +795,19 @@ impl BlockChainClient for Client {
 		ret
 	}
 
+	fn replay(&self, id: TransactionID, analytics: CallAnalytics) -> Result<Executed, ReplayError> {
+		let (mut state, env_info, t) = try!(self.replay_state(id));
+		let orig = state.clone();
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, profiling: analytics.profiling, check_nonce: true };
+		let mut ret = Executive::new(&mut state, &env_info, self.engine.deref().deref(), &self.vm_factory).transact(&t, options);
+		if analytics.state_diffing {
+			if let Ok(ref mut x) = ret {
+				x.state_diff = Some(state.diff_from(orig));
+			}
+		}
+		ret.map_err(ReplayError::Execution)
+	}
+
 	fn keep_alive(&self) {
 		if self.mode != Mode::Active {
 			self.wake_up();
@@ -682,6 +819,10 @@ impl BlockChainClient for Client {
 		Self::block_hash(&self.chain, id).and_then(|hash| self.chain.block(&hash).map(|bytes| BlockView::new(&bytes).rlp().at(0).as_raw().to_vec()))
 	}
 
+	fn block_signer(&self, id: BlockID) -> Option<Address> {
+		self.block_header(id).and_then(|header| self.engine.signer(&rlp::decode(&header)))
+	}
+
 	fn block_body(&self, id: BlockID) -> Option<Bytes> {
 		Self::block_hash(&self.chain, id).and_then(|hash| {
 			self.chain.block(&hash).map(|bytes| {
@@ -734,6 +875,10 @@ impl BlockChainClient for Client {
 		self.state().code(address)
 	}
 
+	fn code_at(&self, address: &Address, id: BlockID) -> Option<Bytes> {
+		self.state_at(id).map(|s| s.code(address).unwrap_or_else(Vec::new))
+	}
+
 	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
 		self.state_at(id).map(|s| s.balance(address))
 	}
@@ -894,6 +1039,11 @@ impl BlockChainClient for Client {
 			.collect()
 	}
 
+	fn verify_snark(&self, vk: Bytes, proof: Bytes, primary_input: Bytes) -> SnarkVerification {
+		// a transaction sent now would run in the next block
+		zksnark::verify_offline(self.engine.builtins(), self.chain.best_block_number() + 1, &vk, &proof, &primary_input)
+	}
+
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		let start = self.block_number(filter.range.start);
 		let end = self.block_number(filter.range.end);
@@ -957,6 +1107,47 @@ impl BlockChainClient for Client {
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		self.miner.pending_transactions()
 	}
+
+	fn handle_consensus_message(&self, message: Bytes) -> Result<(), EngineError> {
+		self.engine.handle_message(&message).map_err(|e| match e {
+			Error::Engine(e) => e,
+			e => {
+				trace!(target: "client", "Malformed consensus message: {}", e);
+				EngineError::MalformedMessage
+			},
+		})
+	}
+
+	fn signing_chain_id(&self) -> Option<u64> {
+		let params = self.engine.params();
+		match self.chain.best_block_number() + 1 >= params.eip155_transition {
+			true => Some(params.chain_id),
+			false => None,
+		}
+	}
+}
+
+impl DebuggingBlockChainClient for Client {
+	fn debug_transaction(&self, id: TransactionID) -> Result<DebugSession, ReplayError> {
+		let (state, env_info, t) = try!(self.replay_state(id));
+		Ok(self.debug_session(state, env_info, t, true))
+	}
+
+	fn debug_call(&self, t: &SignedTransaction) -> Result<DebugSession, ExecutionError> {
+		let (state, env_info) = try!(self.call_state(t));
+		Ok(self.debug_session(state, env_info, t.clone(), false))
+	}
+
+	fn replay_frames(&self, id: TransactionID, memory: bool, stack: bool) -> Result<(Executed, Vec<DebugFrame>), ReplayError> {
+		let (mut state, env_info, t) = try!(self.replay_state(id));
+		let frames = Arc::new(Mutex::new(Vec::new()));
+		let vm_tracer = StructLogger::new(frames.clone(), memory, stack);
+		let executed = try!(Executive::new(&mut state, &env_info, self.engine.deref().deref(), &self.vm_factory)
+			.transact_with_tracer(&t, true, trace::NoopTracer, vm_tracer)
+			.map_err(ReplayError::Execution));
+		let frames = mem::replace(&mut *frames.lock(), Vec::new());
+		Ok((executed, frames))
+	}
 }
 
 impl MiningBlockChainClient for Client {
@@ -994,6 +1185,17 @@ impl MiningBlockChainClient for Client {
 		&self.vm_factory
 	}
 
+	fn schedule_at(&self, id: BlockID) -> Option<Schedule> {
+		let number = match id {
+			BlockID::Pending => Some(self.chain.best_block_number() + 1),
+			id => self.block_number(id),
+		};
+		number.map(|number| self.engine.schedule(&EnvInfo {
+			number: number,
+			..Default::default()
+		}))
+	}
+
 	fn import_sealed_block(&self, block: SealedBlock) -> ImportResult {
 		let _import_lock = self.import_lock.lock();
 		let _timer = PerfTimer::new("import_sealed_block");
@@ -64,6 +64,8 @@ pub struct ClientConfig {
 	pub tracing: TraceConfig,
 	/// VM type.
 	pub vm_type: VMType,
+	/// EVM code analysis cache size if not default
+	pub jump_table_size: Option<usize>,
 	/// Trie type.
 	pub trie_spec: TrieSpec,
 	/// The JournalDB ("pruning") algorithm to use.
@@ -0,0 +1,441 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Interactive debugging of transactions, one instruction at a time.
+
+use std::thread;
+use std::cell::RefCell;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use util::*;
+use evm::instructions;
+use executive::Executed;
+use trace::{VMTracer, VMTrace};
+use types::executed::ExecutionError;
+
+/// Command resuming a paused debugging session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugCommand {
+	/// Execute one instruction, pausing inside the call it makes, if any.
+	Step,
+	/// Execute one instruction, running the call it makes, if any, to completion.
+	StepOver,
+	/// Run until the code being executed reaches the given program counter.
+	ContinueTo(usize),
+	/// Run until a breakpoint is hit.
+	Continue,
+}
+
+/// State of the VM before it executes an instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugFrame {
+	/// Hash of the code being executed.
+	pub code_hash: H256,
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction about to be executed.
+	pub instruction: u8,
+	/// Gas left.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, starting at 1.
+	pub depth: usize,
+	/// Stack, bottom first.
+	pub stack: Vec<U256>,
+	/// Memory.
+	pub memory: Bytes,
+	/// Storage slots read or written so far by this call.
+	pub storage: BTreeMap<U256, U256>,
+}
+
+/// Where a debugging session is at.
+#[derive(Debug)]
+pub enum DebugStatus {
+	/// Paused before executing an instruction.
+	Paused(DebugFrame),
+	/// Done executing.
+	Finished(Result<Executed, ExecutionError>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+	Step,
+	StepOver(usize),
+	ContinueTo(H256, usize),
+	Continue,
+	/// The session is gone, run to completion.
+	Detached,
+}
+
+/// State shared by the tracers of all the calls of a debugged transaction.
+struct Control {
+	mode: Mutex<Mode>,
+	breakpoints: Arc<RwLock<HashSet<(H256, usize)>>>,
+}
+
+/// Storage slots read or written by one call, as seen from its instructions.
+#[derive(Default)]
+struct SeenStorage {
+	sload: Option<U256>,
+	slots: BTreeMap<U256, U256>,
+}
+
+impl SeenStorage {
+	/// Note the instruction about to be executed, with the stack it finds.
+	fn before(&mut self, instruction: u8, stack: &[U256]) {
+		self.sload = match instruction {
+			instructions::SLOAD => stack.last().cloned(),
+			_ => None,
+		};
+	}
+
+	/// Note what the instruction noted by `before` did.
+	fn after(&mut self, stack_push: &[U256], store_diff: Option<(U256, U256)>) {
+		if let (Some(key), Some(value)) = (self.sload.take(), stack_push.first()) {
+			self.slots.insert(key, value.clone());
+		}
+		if let Some((key, value)) = store_diff {
+			self.slots.insert(key, value);
+		}
+	}
+}
+
+/// VM tracer pausing execution whenever the debugging session it reports to asks for it.
+pub struct DebugVMTracer {
+	control: Arc<Control>,
+	events: Sender<DebugStatus>,
+	// owned by the tracer of the call being executed: handed to the tracer of a subcall
+	// and back once the subcall is done
+	commands: RefCell<Option<Receiver<DebugCommand>>>,
+	code_hash: H256,
+	depth: usize,
+	pc: usize,
+	instruction: u8,
+	gas_cost: U256,
+	storage: SeenStorage,
+}
+
+impl DebugVMTracer {
+	fn new(control: Arc<Control>, events: Sender<DebugStatus>, commands: Option<Receiver<DebugCommand>>, code_hash: H256, depth: usize) -> Self {
+		DebugVMTracer {
+			control: control,
+			events: events,
+			commands: RefCell::new(commands),
+			code_hash: code_hash,
+			depth: depth,
+			pc: 0,
+			instruction: 0,
+			gas_cost: U256::zero(),
+			storage: SeenStorage::default(),
+		}
+	}
+
+	fn should_pause(&self) -> bool {
+		let breakpoint = self.control.breakpoints.read().contains(&(self.code_hash.clone(), self.pc));
+		match *self.control.mode.lock() {
+			Mode::Step => true,
+			Mode::StepOver(depth) => breakpoint || self.depth <= depth,
+			Mode::ContinueTo(ref code_hash, pc) => breakpoint || (*code_hash == self.code_hash && pc == self.pc),
+			Mode::Continue => breakpoint,
+			Mode::Detached => false,
+		}
+	}
+}
+
+impl VMTracer for DebugVMTracer {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		self.pc = pc;
+		self.instruction = instruction;
+		self.gas_cost = gas_cost.clone();
+		true
+	}
+
+	fn trace_state(&mut self, gas: &U256, stack: &[U256], mem: &[u8]) {
+		self.storage.before(self.instruction, stack);
+		if !self.should_pause() {
+			return;
+		}
+
+		let frame = DebugFrame {
+			code_hash: self.code_hash.clone(),
+			pc: self.pc,
+			instruction: self.instruction,
+			gas: gas.clone(),
+			gas_cost: self.gas_cost.clone(),
+			depth: self.depth,
+			stack: stack.to_vec(),
+			memory: mem.to_vec(),
+			storage: self.storage.slots.clone(),
+		};
+		// a session which went away can't send any more commands
+		let command = match self.events.send(DebugStatus::Paused(frame)) {
+			Ok(()) => self.commands.borrow().as_ref().and_then(|commands| commands.recv().ok()),
+			Err(_) => None,
+		};
+		*self.control.mode.lock() = match command {
+			Some(DebugCommand::Step) => Mode::Step,
+			Some(DebugCommand::StepOver) => Mode::StepOver(self.depth),
+			Some(DebugCommand::ContinueTo(pc)) => Mode::ContinueTo(self.code_hash.clone(), pc),
+			Some(DebugCommand::Continue) => Mode::Continue,
+			None => Mode::Detached,
+		};
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], _mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		self.storage.after(stack_push, store_diff);
+	}
+
+	fn prepare_subtrace(&self, code: &[u8]) -> Self {
+		let commands = self.commands.borrow_mut().take();
+		DebugVMTracer::new(self.control.clone(), self.events.clone(), commands, code.sha3(), self.depth + 1)
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		*self.commands.borrow_mut() = sub.commands.into_inner();
+	}
+
+	fn drain(self) -> Option<VMTrace> { None }
+}
+
+/// VM tracer recording the state of the VM before every executed instruction, in order of
+/// execution. Memory and stack are left empty unless asked for.
+pub struct StructLogger {
+	frames: Arc<Mutex<Vec<DebugFrame>>>,
+	memory: bool,
+	stack: bool,
+	code_hash: H256,
+	depth: usize,
+	pc: usize,
+	instruction: u8,
+	gas_cost: U256,
+	storage: SeenStorage,
+}
+
+impl StructLogger {
+	/// Create a new tracer appending to `frames`.
+	pub fn new(frames: Arc<Mutex<Vec<DebugFrame>>>, memory: bool, stack: bool) -> Self {
+		StructLogger {
+			frames: frames,
+			memory: memory,
+			stack: stack,
+			code_hash: H256::new(),
+			depth: 0,
+			pc: 0,
+			instruction: 0,
+			gas_cost: U256::zero(),
+			storage: SeenStorage::default(),
+		}
+	}
+}
+
+impl VMTracer for StructLogger {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		self.pc = pc;
+		self.instruction = instruction;
+		self.gas_cost = gas_cost.clone();
+		true
+	}
+
+	fn trace_state(&mut self, gas: &U256, stack: &[U256], mem: &[u8]) {
+		self.storage.before(self.instruction, stack);
+		self.frames.lock().push(DebugFrame {
+			code_hash: self.code_hash.clone(),
+			pc: self.pc,
+			instruction: self.instruction,
+			gas: gas.clone(),
+			gas_cost: self.gas_cost.clone(),
+			depth: self.depth,
+			stack: if self.stack { stack.to_vec() } else { Vec::new() },
+			memory: if self.memory { mem.to_vec() } else { Vec::new() },
+			storage: self.storage.slots.clone(),
+		});
+	}
+
+	fn trace_executed(&mut self, _gas_used: U256, stack_push: &[U256], _mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		self.storage.after(stack_push, store_diff);
+	}
+
+	fn prepare_subtrace(&self, code: &[u8]) -> Self {
+		StructLogger {
+			code_hash: code.sha3(),
+			depth: self.depth + 1,
+			..StructLogger::new(self.frames.clone(), self.memory, self.stack)
+		}
+	}
+
+	fn done_subtrace(&mut self, _sub: Self) {}
+
+	fn drain(self) -> Option<VMTrace> { None }
+}
+
+/// Transaction executing on a background thread, which pauses between instructions
+/// as it is told to.
+pub struct DebugSession {
+	breakpoints: Arc<RwLock<HashSet<(H256, usize)>>>,
+	commands: Sender<DebugCommand>,
+	events: Receiver<DebugStatus>,
+	status: DebugStatus,
+}
+
+impl DebugSession {
+	/// Start `run` on a background thread, and wait until it is paused before its first
+	/// instruction. `run` is expected to execute a transaction with the VM tracer it is given.
+	///
+	/// Dropping the session lets the transaction run to completion.
+	pub fn new<F>(run: F) -> Self where F: FnOnce(DebugVMTracer) -> Result<Executed, ExecutionError> + Send + 'static {
+		let (commands_tx, commands_rx) = channel();
+		let (events_tx, events_rx) = channel();
+		let breakpoints = Arc::new(RwLock::new(HashSet::new()));
+		let control = Arc::new(Control {
+			mode: Mutex::new(Mode::Step),
+			breakpoints: breakpoints.clone(),
+		});
+
+		let tracer = DebugVMTracer::new(control, events_tx.clone(), Some(commands_rx), H256::new(), 0);
+		thread::spawn(move || {
+			let result = run(tracer);
+			let _ = events_tx.send(DebugStatus::Finished(result));
+		});
+
+		let status = Self::next_status(&events_rx);
+		DebugSession {
+			breakpoints: breakpoints,
+			commands: commands_tx,
+			events: events_rx,
+			status: status,
+		}
+	}
+
+	fn next_status(events: &Receiver<DebugStatus>) -> DebugStatus {
+		// the executing thread only hangs up without reporting back if it panicked
+		events.recv().unwrap_or(DebugStatus::Finished(Err(ExecutionError::Internal)))
+	}
+
+	/// Where the session is at.
+	pub fn status(&self) -> &DebugStatus {
+		&self.status
+	}
+
+	/// Resume a paused session and wait until it pauses again or finishes.
+	pub fn resume(&mut self, command: DebugCommand) -> &DebugStatus {
+		if let DebugStatus::Paused(_) = self.status {
+			self.status = match self.commands.send(command) {
+				Ok(()) => Self::next_status(&self.events),
+				Err(_) => DebugStatus::Finished(Err(ExecutionError::Internal)),
+			};
+		}
+		&self.status
+	}
+
+	/// Pause whenever the code with hash `code_hash` is about to execute the instruction at `pc`.
+	/// Returns `false` if there already was such a breakpoint.
+	pub fn set_breakpoint(&self, code_hash: H256, pc: usize) -> bool {
+		self.breakpoints.write().insert((code_hash, pc))
+	}
+
+	/// Remove a breakpoint. Returns `false` if there was no such breakpoint.
+	pub fn remove_breakpoint(&self, code_hash: H256, pc: usize) -> bool {
+		self.breakpoints.write().remove(&(code_hash, pc))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use ethereum;
+	use env_info::EnvInfo;
+	use pod_account::PodAccount;
+	use pod_state::PodState;
+	use transaction::{Transaction, Action};
+	use super::super::EvmTestClient;
+	use super::{DebugSession, DebugStatus, DebugCommand, DebugFrame};
+
+	fn start(code: &str, callee_code: &str) -> (DebugSession, H256, H256) {
+		let keypair = KeyPair::from_secret("".sha3()).unwrap();
+		let code = code.from_hex().unwrap();
+		let callee_code = callee_code.from_hex().unwrap();
+		let hashes = (code.sha3(), callee_code.sha3());
+		let pre = PodState::from(map![
+			keypair.address() => PodAccount::new(1_000_000.into(), 0.into(), vec![], map![]),
+			Address::from(10u64) => PodAccount::new(0.into(), 0.into(), code, map![]),
+			Address::from(11u64) => PodAccount::new(0.into(), 0.into(), callee_code, map![])
+		]);
+		let t = Transaction {
+			action: Action::Call(Address::from(10u64)),
+			value: 0.into(),
+			data: vec![],
+			gas: 100_000.into(),
+			gas_price: 1.into(),
+			nonce: 0.into(),
+		}.sign(keypair.secret(), None);
+		let mut env_info = EnvInfo::default();
+		env_info.gas_limit = 1_000_000.into();
+
+		let session = DebugSession::new(move |vm_tracer| {
+			EvmTestClient::new(ethereum::new_frontier_test()).transact(&env_info, pre, &t, vm_tracer).outcome
+		});
+		(session, hashes.0, hashes.1)
+	}
+
+	fn paused(status: &DebugStatus) -> DebugFrame {
+		match *status {
+			DebugStatus::Paused(ref frame) => frame.clone(),
+			DebugStatus::Finished(ref result) => panic!("session finished with {:?}", result),
+		}
+	}
+
+	#[test]
+	fn should_step_and_stop_at_breakpoints() {
+		// store 1 at 0, then load it
+		let (mut session, code_hash, _) = start("600160005560005400", "");
+
+		let frame = paused(session.status());
+		assert_eq!((frame.pc, frame.depth, frame.gas), (0, 1, 79_000.into()));
+		assert_eq!(frame.code_hash, code_hash);
+
+		let frame = paused(session.resume(DebugCommand::Step));
+		assert_eq!(frame.pc, 2);
+		assert_eq!(frame.stack, vec![1.into()]);
+
+		assert!(session.set_breakpoint(code_hash.clone(), 7));
+		let frame = paused(session.resume(DebugCommand::Continue));
+		assert_eq!(frame.pc, 7);
+		assert_eq!(frame.storage, map![0.into() => 1.into()]);
+
+		assert!(session.remove_breakpoint(code_hash, 7));
+		match *session.resume(DebugCommand::Continue) {
+			DebugStatus::Finished(Ok(ref executed)) => assert_eq!(executed.gas_used, 41_059.into()),
+			ref other => panic!("unexpected status {:?}", other),
+		}
+	}
+
+	#[test]
+	fn should_step_into_or_over_calls() {
+		// call 0x0b with 10000 gas, then stop
+		let code = "60006000600060006000600b612710f100";
+		let (mut session, _, callee_hash) = start(code, "600100");
+		let frame = paused(session.resume(DebugCommand::ContinueTo(15)));
+		assert_eq!(frame.instruction, 0xf1);
+		let frame = paused(session.resume(DebugCommand::Step));
+		assert_eq!((frame.pc, frame.depth), (0, 2));
+		assert_eq!(frame.code_hash, callee_hash);
+
+		let (mut session, _, _) = start(code, "600100");
+		session.resume(DebugCommand::ContinueTo(15));
+		let frame = paused(session.resume(DebugCommand::StepOver));
+		assert_eq!((frame.pc, frame.depth), (16, 1));
+	}
+}
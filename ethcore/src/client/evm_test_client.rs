@@ -0,0 +1,211 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Standalone transaction executor for tools such as `evmbin`.
+
+use std::time::{Instant, Duration};
+use util::*;
+use spec::Spec;
+use state::State;
+use pod_state::PodState;
+use env_info::EnvInfo;
+use evm;
+use action_params::ActionParams;
+use executive::{Executive, Executed};
+use substate::Substate;
+use trace::{NoopTracer, VMTracer};
+use transaction::SignedTransaction;
+use types::executed::ExecutionError;
+use types::log_entry::LogEntry;
+use types::state_diff::StateDiff;
+use types::profile::Profile;
+
+/// Outcome of a transaction executed by `EvmTestClient`.
+pub struct EvmTestResult {
+	/// Root of the state after the transaction.
+	pub state_root: H256,
+	/// Execution receipt, or the reason the transaction could not be applied.
+	pub outcome: Result<Executed, ExecutionError>,
+}
+
+/// Outcome of a message call executed by `EvmTestClient`.
+pub struct EvmCallResult {
+	/// Gas left after the call, or the error it failed with.
+	pub gas_left: evm::Result<U256>,
+	/// Data returned by the call.
+	pub output: Bytes,
+	/// Logs emitted by the call.
+	pub logs: Vec<LogEntry>,
+	/// Gas the storage clears and suicides of the call would refund, before the cap
+	/// applied when finalizing a transaction.
+	pub refund: U256,
+	/// Changes the call made to the pre-state, storage writes included.
+	pub state_diff: StateDiff,
+	/// Execution profile, if `vm_tracer` profiles.
+	pub profile: Option<Profile>,
+	/// Time spent executing the call, setting up the pre-state excluded.
+	pub time: Duration,
+}
+
+/// State built from a `PodState`, ready to run calls on.
+pub struct EvmTestState(State);
+
+/// Executes single transactions on top of a given pre-state, by the rules of a chain spec
+/// and without a blockchain behind it.
+pub struct EvmTestClient {
+	spec: Spec,
+}
+
+impl EvmTestClient {
+	/// Create a new executor following the rules of `spec`.
+	pub fn new(spec: Spec) -> Self {
+		EvmTestClient {
+			spec: spec,
+		}
+	}
+
+	/// Execute `transaction` in the environment `env_info` on a fresh state populated with `pre`,
+	/// passing every executed instruction to `vm_tracer`.
+	pub fn transact<V: VMTracer>(&self, env_info: &EnvInfo, pre: PodState, transaction: &SignedTransaction, vm_tracer: V) -> EvmTestResult {
+		let mut state = self.state(pre);
+
+		let vm_factory = Default::default();
+		let outcome = Executive::new(&mut state, env_info, &*self.spec.engine, &vm_factory)
+			.transact_with_tracer(transaction, true, NoopTracer, vm_tracer);
+		state.commit();
+
+		EvmTestResult {
+			state_root: state.root().clone(),
+			outcome: outcome,
+		}
+	}
+
+	/// Execute the message call `params` in the environment `env_info` on a fresh state populated
+	/// with `pre`, passing every executed instruction to `vm_tracer`. Unless `params` carries code,
+	/// the code of the called account is run.
+	pub fn call<V: VMTracer>(&self, env_info: &EnvInfo, pre: PodState, params: ActionParams, vm_tracer: V) -> EvmCallResult {
+		let pre = self.prepare(pre);
+		self.call_on(env_info, &pre, params, vm_tracer)
+	}
+
+	/// Build the state `pre` once, so that it can be the pre-state of many calls.
+	pub fn prepare(&self, pre: PodState) -> EvmTestState {
+		EvmTestState(self.state(pre))
+	}
+
+	/// Execute the message call `params` like `call`, on a copy of the prepared state `pre`.
+	pub fn call_on<V: VMTracer>(&self, env_info: &EnvInfo, pre: &EvmTestState, mut params: ActionParams, mut vm_tracer: V) -> EvmCallResult {
+		let orig = pre.0.clone();
+		let mut state = pre.0.clone();
+
+		if params.code.is_none() {
+			params.code = state.code(&params.code_address);
+			params.code_hash = state.code_hash(&params.code_address);
+		}
+
+		let mut substate = Substate::new();
+		let mut output = Vec::new();
+		let vm_factory = Default::default();
+		let start = Instant::now();
+		let gas_left = Executive::new(&mut state, env_info, &*self.spec.engine, &vm_factory)
+			.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut NoopTracer, &mut vm_tracer);
+		let time = start.elapsed();
+
+		let schedule = self.spec.engine.schedule(env_info);
+		let refund = U256::from(schedule.sstore_refund_gas) * substate.sstore_clears_count
+			+ U256::from(schedule.suicide_refund_gas) * U256::from(substate.suicides.len());
+
+		EvmCallResult {
+			gas_left: gas_left,
+			output: output,
+			logs: substate.logs,
+			refund: refund,
+			state_diff: state.diff_from(orig),
+			profile: vm_tracer.take_profile(),
+			time: time,
+		}
+	}
+
+	fn state(&self, pre: PodState) -> State {
+		let db = journaldb::new_in_memory();
+		let mut state = State::new(db, self.spec.engine.account_start_nonce(), Default::default());
+		state.populate_from(pre);
+		state.commit();
+		state
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use ethereum;
+	use env_info::EnvInfo;
+	use pod_account::PodAccount;
+	use pod_state::PodState;
+	use action_params::ActionParams;
+	use types::account_diff::Diff;
+	use trace::NoopVMTracer;
+	use transaction::{Transaction, Action};
+	use super::EvmTestClient;
+
+	#[test]
+	fn executes_transaction_on_pre_state() {
+		let keypair = KeyPair::from_secret("".sha3()).unwrap();
+		let receiver = Address::from(10u64);
+		let pre = PodState::from(map![keypair.address() => PodAccount::new(100_000.into(), 0.into(), vec![], map![])]);
+		let t = Transaction {
+			action: Action::Call(receiver.clone()),
+			value: 5.into(),
+			data: vec![],
+			gas: 21_000.into(),
+			gas_price: 1.into(),
+			nonce: 0.into(),
+		}.sign(keypair.secret(), None);
+		let mut env_info = EnvInfo::default();
+		env_info.gas_limit = 1_000_000.into();
+
+		let client = EvmTestClient::new(ethereum::new_frontier_test());
+		let result = client.transact(&env_info, pre, &t, NoopVMTracer);
+		assert_eq!(result.outcome.unwrap().gas_used, 21_000.into());
+
+		let post = PodState::from(map![
+			keypair.address() => PodAccount::new(78_995.into(), 1.into(), vec![], map![]),
+			receiver => PodAccount::new(5.into(), 0.into(), vec![], map![]),
+			Address::new() => PodAccount::new(21_000.into(), 0.into(), vec![], map![])
+		]);
+		assert_eq!(result.state_root, post.root());
+	}
+
+	#[test]
+	fn reports_output_logs_and_storage_writes_of_call() {
+		let address = Address::from(10u64);
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.gas = 100_000.into();
+		// store 1 at 0, emit an empty log and return 0xff
+		params.code = Some("600160005560006000a060ff60005360016000f3".from_hex().unwrap());
+
+		let client = EvmTestClient::new(ethereum::new_frontier_test());
+		let result = client.call(&EnvInfo::default(), PodState::default(), params, NoopVMTracer);
+
+		assert!(result.gas_left.is_ok());
+		assert_eq!(result.output, vec![0xff]);
+		assert_eq!(result.logs.len(), 1);
+		assert_eq!(result.refund, 0.into());
+		assert_eq!(result.state_diff.raw[&address].storage[&H256::new()], Diff::Born(H256::from(&U256::from(1))));
+	}
+}
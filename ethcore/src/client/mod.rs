@@ -21,22 +21,28 @@ mod error;
 mod test_client;
 mod trace;
 mod client;
+mod evm_test_client;
+mod debugger;
 
 pub use self::client::*;
+pub use self::evm_test_client::{EvmTestClient, EvmTestResult, EvmCallResult, EvmTestState};
+pub use self::debugger::{DebugSession, DebugStatus, DebugCommand, DebugFrame, DebugVMTracer, StructLogger};
 pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockQueueConfig, BlockChainConfig, Switch, VMType};
 pub use self::error::Error;
 pub use types::ids::*;
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
 pub use types::trace_filter::Filter as TraceFilter;
 pub use executive::{Executed, Executive, TransactOptions};
+pub use types::executed::ReplayError;
 pub use env_info::{LastHashes, EnvInfo};
 pub use self::chain_notify::{ChainNotify, ChainNotifyClient};
 
 pub use types::call_analytics::CallAnalytics;
+pub use types::snark_verification::{SnarkVerification, SnarkVerificationResult};
 pub use block_import_error::BlockImportError;
 pub use transaction_import::TransactionImportResult;
 pub use transaction_import::TransactionImportError;
-pub use self::traits::{BlockChainClient, MiningBlockChainClient, RemoteClient};
+pub use self::traits::{BlockChainClient, MiningBlockChainClient, DebuggingBlockChainClient, RemoteClient};
 
 mod traits {
 	#![allow(dead_code, unused_assignments, unused_variables, missing_docs)] // codegen issues
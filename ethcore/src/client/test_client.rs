@@ -21,23 +21,27 @@ use util::*;
 use devtools::*;
 use transaction::{Transaction, LocalizedTransaction, SignedTransaction, Action};
 use blockchain::TreeRoute;
-use client::{BlockChainClient, MiningBlockChainClient, BlockChainInfo, BlockStatus, BlockID,
+use client::{BlockChainClient, MiningBlockChainClient, DebuggingBlockChainClient, BlockChainInfo, BlockStatus, BlockID,
 	TransactionID, UncleID, TraceId, TraceFilter, LastHashes, CallAnalytics,
-	BlockImportError};
+	BlockImportError, SnarkVerification, DebugSession, DebugFrame, EvmTestClient, EnvInfo};
 use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt};
 use blockchain::extras::BlockReceipts;
-use error::{ImportResult};
-use evm::{Factory as EvmFactory, VMType};
+use error::{ImportResult, Error, EngineError};
+use evm::{Factory as EvmFactory, VMType, Schedule};
 use miner::{Miner, MinerService};
 use spec::Spec;
+use pod_account::PodAccount;
+use pod_state::PodState;
+use zksnark;
 
 use block_queue::BlockQueueInfo;
 use block::{OpenBlock, SealedBlock};
 use executive::Executed;
 use error::ExecutionError;
+use types::executed::ReplayError;
 use trace::LocalizedTrace;
 
 /// Test client.
@@ -62,6 +66,8 @@ pub struct TestBlockChainClient {
 	pub code: RwLock<HashMap<Address, Bytes>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Executed>>,
+	/// VM states replayed along with the execution result.
+	pub debug_frames: RwLock<Vec<DebugFrame>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionID, LocalizedReceipt>>,
 	/// Block queue size.
@@ -108,6 +114,7 @@ impl TestBlockChainClient {
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			debug_frames: RwLock::new(Vec::new()),
 			receipts: RwLock::new(HashMap::new()),
 			queue_size: AtomicUsize::new(0),
 			miner: Arc::new(Miner::with_spec(Spec::new_test())),
@@ -129,6 +136,11 @@ impl TestBlockChainClient {
 		*self.execution_result.write() = Some(result);
 	}
 
+	/// Set the VM states replayed along with the execution result.
+	pub fn set_debug_frames(&self, frames: Vec<DebugFrame>) {
+		*self.debug_frames.write() = frames;
+	}
+
 	/// Set the balance of account `address` to `balance`.
 	pub fn set_balance(&self, address: Address, balance: U256) {
 		self.balances.write().insert(address, balance);
@@ -190,7 +202,7 @@ impl TestBlockChainClient {
 						gas_price: U256::one(),
 						nonce: U256::zero()
 					};
-					let signed_tx = tx.sign(&keypair.secret());
+					let signed_tx = tx.sign(&keypair.secret(), None);
 					txs.append(&signed_tx);
 					txs.out()
 				},
@@ -255,6 +267,51 @@ pub fn get_temp_journal_db() -> GuardedTempResult<Box<JournalDB>> {
 	}
 }
 
+impl DebuggingBlockChainClient for TestBlockChainClient {
+	fn debug_transaction(&self, _id: TransactionID) -> Result<DebugSession, ReplayError> {
+		Err(ReplayError::StatePruned)
+	}
+
+	fn debug_call(&self, t: &SignedTransaction) -> Result<DebugSession, ExecutionError> {
+		fn account<'a>(accounts: &'a mut BTreeMap<Address, PodAccount>, address: &Address) -> &'a mut PodAccount {
+			accounts.entry(address.clone()).or_insert_with(|| PodAccount::new(U256::zero(), U256::zero(), vec![], BTreeMap::new()))
+		}
+
+		// the call runs on a state made of the accounts set up on this client
+		let mut accounts = BTreeMap::new();
+		for (address, balance) in self.balances.read().iter() {
+			account(&mut accounts, address).balance = balance.clone();
+		}
+		for (address, nonce) in self.nonces.read().iter() {
+			account(&mut accounts, address).nonce = nonce.clone();
+		}
+		for (address, code) in self.code.read().iter() {
+			account(&mut accounts, address).code = code.clone();
+		}
+		for (&(ref address, ref position), value) in self.storage.read().iter() {
+			account(&mut accounts, address).storage.insert(position.clone(), value.clone());
+		}
+		// nonces of this client default to zero, not to the account start nonce
+		if let Ok(sender) = t.sender() {
+			account(&mut accounts, &sender);
+		}
+		let pre = PodState::from(accounts);
+		let env_info = EnvInfo {
+			gas_limit: U256::max_value(),
+			..Default::default()
+		};
+		let t = t.clone();
+		Ok(DebugSession::new(move |vm_tracer| {
+			EvmTestClient::new(Spec::new_test()).transact(&env_info, pre, &t, vm_tracer).outcome
+		}))
+	}
+
+	fn replay_frames(&self, _id: TransactionID, _memory: bool, _stack: bool) -> Result<(Executed, Vec<DebugFrame>), ReplayError> {
+		let executed = try!(self.execution_result.read().clone().ok_or(ReplayError::TransactionNotFound));
+		Ok((executed, self.debug_frames.read().clone()))
+	}
+}
+
 impl MiningBlockChainClient for TestBlockChainClient {
 	fn prepare_open_block(&self, _author: Address, _gas_range_target: (U256, U256), _extra_data: Bytes) -> OpenBlock {
 		let engine = &self.spec.engine;
@@ -282,6 +339,19 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		&self.vm_factory
 	}
 
+	fn schedule_at(&self, id: BlockID) -> Option<Schedule> {
+		let number = match id {
+			BlockID::Number(number) => number,
+			BlockID::Earliest => 0,
+			BlockID::Pending => self.chain_info().best_block_number + 1,
+			BlockID::Latest | BlockID::Hash(_) => self.chain_info().best_block_number,
+		};
+		Some(self.spec.engine.schedule(&EnvInfo {
+			number: number,
+			..Default::default()
+		}))
+	}
+
 	fn import_sealed_block(&self, _block: SealedBlock) -> ImportResult {
 		Ok(H256::default())
 	}
@@ -292,6 +362,10 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(self.execution_result.read().clone().unwrap())
 	}
 
+	fn replay(&self, _t: TransactionID, _analytics: CallAnalytics) -> Result<Executed, ReplayError> {
+		self.execution_result.read().clone().ok_or(ReplayError::TransactionNotFound)
+	}
+
 	fn block_total_difficulty(&self, _id: BlockID) -> Option<U256> {
 		Some(U256::zero())
 	}
@@ -315,6 +389,14 @@ impl BlockChainClient for TestBlockChainClient {
 		self.code.read().get(address).cloned()
 	}
 
+	fn code_at(&self, address: &Address, id: BlockID) -> Option<Bytes> {
+		if let BlockID::Latest = id {
+			Some(self.code.read().get(address).cloned().unwrap_or_else(Vec::new))
+		} else {
+			None
+		}
+	}
+
 	fn balance(&self, address: &Address, id: BlockID) -> Option<U256> {
 		if let BlockID::Latest = id {
 			Some(self.balances.read().get(address).cloned().unwrap_or_else(U256::zero))
@@ -363,6 +445,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).map(|r| Rlp::new(r).at(0).as_raw().to_vec()))
 	}
 
+	fn block_signer(&self, id: BlockID) -> Option<Address> {
+		self.block_header(id).and_then(|header| self.spec.engine.signer(&decode(&header)))
+	}
+
 	fn block_body(&self, id: BlockID) -> Option<Bytes> {
 		self.block_hash(id).and_then(|hash| self.blocks.read().get(&hash).map(|r| {
 			let mut stream = RlpStream::new_list(2);
@@ -512,6 +598,10 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn verify_snark(&self, vk: Bytes, proof: Bytes, primary_input: Bytes) -> SnarkVerification {
+		zksnark::verify_offline(self.spec.engine.builtins(), self.chain_info().best_block_number + 1, &vk, &proof, &primary_input)
+	}
+
 	fn filter_traces(&self, _filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		unimplemented!();
 	}
@@ -534,6 +624,21 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.import_external_transactions(self, txs);
 	}
 
+	fn handle_consensus_message(&self, message: Bytes) -> Result<(), EngineError> {
+		self.spec.engine.handle_message(&message).map_err(|e| match e {
+			Error::Engine(e) => e,
+			_ => EngineError::MalformedMessage,
+		})
+	}
+
+	fn signing_chain_id(&self) -> Option<u64> {
+		let params = self.spec.engine.params();
+		match self.chain_info().best_block_number + 1 >= params.eip155_transition {
+			true => Some(params.chain_id),
+			false => None,
+		}
+	}
+
 	fn pending_transactions(&self) -> Vec<SignedTransaction> {
 		self.miner.pending_transactions()
 	}
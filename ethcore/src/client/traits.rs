@@ -26,13 +26,16 @@ use transaction::{LocalizedTransaction, SignedTransaction};
 use log_entry::LocalizedLogEntry;
 use filter::Filter;
 use views::{BlockView};
-use error::{ImportResult, ExecutionError};
+use error::{ImportResult, ExecutionError, EngineError};
 use receipt::LocalizedReceipt;
 use trace::LocalizedTrace;
 use evm::Factory as EvmFactory;
+use evm::Schedule;
 use types::ids::*;
 use types::trace_filter::Filter as TraceFilter;
 use executive::Executed;
+use types::executed::ReplayError;
+use client::{DebugSession, DebugFrame};
 use env_info::LastHashes;
 use types::call_analytics::CallAnalytics;
 use block_import_error::BlockImportError;
@@ -41,6 +44,7 @@ use std::collections::VecDeque;
 use ipc::{IpcConfig, BinaryConvertError};
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
+use types::snark_verification::SnarkVerification;
 
 #[derive(Ipc)]
 #[ipc(client_ident="RemoteClient")]
@@ -54,6 +58,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get raw block header data by block id.
 	fn block_header(&self, id: BlockID) -> Option<Bytes>;
 
+	/// Get the account which sealed the block, if the engine seals with a signature.
+	fn block_signer(&self, id: BlockID) -> Option<Address>;
+
 	/// Get raw block body data by block id.
 	/// Block body is an RLP list of two items: uncles and transactions.
 	fn block_body(&self, id: BlockID) -> Option<Bytes>;
@@ -84,6 +91,10 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get address code.
 	fn code(&self, address: &Address) -> Option<Bytes>;
 
+	/// Get address code at the given block's state. Empty for accounts without code,
+	/// `None` if the state is not available.
+	fn code_at(&self, address: &Address, id: BlockID) -> Option<Bytes>;
+
 	/// Get address balance at the given block's state.
 	///
 	/// May not return None if given BlockID::Latest.
@@ -160,6 +171,12 @@ pub trait BlockChainClient : Sync + Send {
 	// TODO: should be able to accept blockchain location for call.
 	fn call(&self, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, ExecutionError>;
 
+	/// Re-executes a transaction of the chain on the state it was originally executed on.
+	fn replay(&self, t: TransactionID, analytics: CallAnalytics) -> Result<Executed, ReplayError>;
+
+	/// Verifies a zkSNARK proof with the chain's zkSNARK builtin without making a transaction.
+	fn verify_snark(&self, vk: Bytes, proof: Bytes, primary_input: Bytes) -> SnarkVerification;
+
 	/// Returns traces matching given filter.
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>>;
 
@@ -178,6 +195,13 @@ pub trait BlockChainClient : Sync + Send {
 	/// Queue transactions for importing.
 	fn queue_transactions(&self, transactions: Vec<Bytes>);
 
+	/// Pass a consensus message received from the network to the engine.
+	/// Fails if the message is invalid, so that its sender can be penalised.
+	fn handle_consensus_message(&self, message: Bytes) -> Result<(), EngineError>;
+
+	/// Chain id new transactions should be signed for, if replay protection is enabled from the next block on.
+	fn signing_chain_id(&self) -> Option<u64>;
+
 	/// list all transactions
 	fn pending_transactions(&self) -> Vec<SignedTransaction>;
 
@@ -217,8 +241,28 @@ pub trait MiningBlockChainClient : BlockChainClient {
 	/// Returns EvmFactory.
 	fn vm_factory(&self) -> &EvmFactory;
 
+	/// Returns the EVM schedule the transactions of the given block are executed with.
+	/// `None` if the block is unknown.
+	fn schedule_at(&self, id: BlockID) -> Option<Schedule>;
+
 	/// Import sealed block. Skips all verifications.
 	fn import_sealed_block(&self, block: SealedBlock) -> ImportResult;
 }
 
+/// Extended client interface used for debugging transactions interactively
+pub trait DebuggingBlockChainClient : BlockChainClient {
+	/// Start debugging a transaction of the chain on the state it was executed on,
+	/// paused before its first instruction.
+	fn debug_transaction(&self, id: TransactionID) -> Result<DebugSession, ReplayError>;
+
+	/// Start debugging a call on the latest state, as `call` would make it,
+	/// paused before its first instruction.
+	fn debug_call(&self, t: &SignedTransaction) -> Result<DebugSession, ExecutionError>;
+
+	/// Re-executes a transaction of the chain on the state it was originally executed on,
+	/// recording the state of the VM before each instruction it runs. Memory and stack are
+	/// only recorded if asked for.
+	fn replay_frames(&self, id: TransactionID, memory: bool, stack: bool) -> Result<(Executed, Vec<DebugFrame>), ReplayError>;
+}
+
 impl IpcConfig for BlockChainClient { }
@@ -21,6 +21,8 @@ use account_provider::AccountProvider;
 use block::ExecutedBlock;
 use spec::CommonParams;
 use evm::Schedule;
+use client::Client;
+use std::sync::Weak;
 
 /// A consensus mechanism for the chain. Generally either proof-of-work or proof-of-stake-based.
 /// Provides hooks into each of the major parts of block import.
@@ -36,6 +38,9 @@ pub trait Engine : Sync + Send {
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> HashMap<String, String> { HashMap::new() }
 
+	/// The account which sealed `header`, for engines whose seal is a signature.
+	fn signer(&self, _header: &Header) -> Option<Address> { None }
+
 	/// Get the general parameters of the chain.
 	fn params(&self) -> &CommonParams;
 
@@ -68,6 +73,10 @@ pub trait Engine : Sync + Send {
 	/// be returned.
 	fn generate_seal(&self, _block: &ExecutedBlock, _accounts: Option<&AccountProvider>) -> Option<Vec<Bytes>> { None }
 
+	/// Whether `generate_seal` seals any block with transactions straight away, so that
+	/// the miner should seal as soon as it gets a transaction.
+	fn seals_internally(&self) -> bool { false }
+
 	/// Phase 1 quick block verification. Only does checks that are cheap. `block` (the header's full block)
 	/// may be provided for additional checks. Returns either a null `Ok` or a general error detailing the problem with import.
 	fn verify_block_basic(&self, _header: &Header,  _block: Option<&[u8]>) -> Result<(), Error> { Ok(()) }
@@ -83,10 +92,20 @@ pub trait Engine : Sync + Send {
 	/// Additional verification for transactions in blocks.
 	// TODO: Add flags for which bits of the transaction to check.
 	// TODO: consider including State in the params.
-	fn verify_transaction_basic(&self, _t: &SignedTransaction, _header: &Header) -> Result<(), Error> { Ok(()) }
+	fn verify_transaction_basic(&self, t: &SignedTransaction, header: &Header) -> Result<(), Error> { self.verify_transaction_chain_id(t, header) }
 	/// Verify a particular transaction is valid.
 	fn verify_transaction(&self, _t: &SignedTransaction, _header: &Header) -> Result<(), Error> { Ok(()) }
 
+	/// Verify a replay-protected transaction is for this chain and that such transactions are
+	/// allowed in the block of `header` (EIP-155).
+	fn verify_transaction_chain_id(&self, t: &SignedTransaction, header: &Header) -> Result<(), Error> {
+		match t.chain_id() {
+			Some(chain_id) if chain_id != self.params().chain_id || header.number() < self.params().eip155_transition =>
+				Err(TransactionError::InvalidChainId(chain_id).into()),
+			_ => Ok(()),
+		}
+	}
+
 	/// Verify the seal of a block. This is an auxilliary method that actually just calls other `verify_` methods
 	/// to get the job done. By default it must pass `verify_basic` and `verify_block_unordered`. If more or fewer
 	/// methods are needed for an Engine, this may be overridden.
@@ -104,14 +123,40 @@ pub trait Engine : Sync + Send {
 
 	// TODO: builtin contract routing - to do this properly, it will require removing the built-in configuration-reading logic
 	// from Spec into here and removing the Spec::builtins field.
-	/// Determine whether a particular address is a builtin contract.
-	fn is_builtin(&self, a: &Address) -> bool { self.builtins().contains_key(a) }
-	/// Determine the code execution cost of the builtin contract with address `a`.
-	/// Panics if `is_builtin(a)` is not true.
-	fn cost_of_builtin(&self, a: &Address, input: &[u8]) -> U256 { self.builtins().get(a).unwrap().cost(input.len()) }
-	/// Execution the builtin contract `a` on `input` and return `output`.
-	/// Panics if `is_builtin(a)` is not true.
-	fn execute_builtin(&self, a: &Address, input: &[u8], output: &mut [u8]) { self.builtins().get(a).unwrap().execute(input, output); }
+	/// The builtin contract with address `a`, if it is active at block number `at`.
+	fn active_builtin(&self, a: &Address, at: BlockNumber) -> Option<&Builtin> {
+		self.builtins().get(a).and_then(|b| if b.is_active(at) { Some(b) } else { None })
+	}
+	/// Determine whether a particular address is a builtin contract active at block number `at`.
+	fn is_builtin(&self, a: &Address, at: BlockNumber) -> bool { self.active_builtin(a, at).is_some() }
+	/// Determine the code execution cost of the builtin contract with address `a` at block number `at`.
+	/// Panics if `is_builtin(a, at)` is not true.
+	fn cost_of_builtin(&self, a: &Address, at: BlockNumber, input: &[u8]) -> U256 {
+		self.active_builtin(a, at).expect("builtin is active; qed").cost(input)
+	}
+	/// Determine the size of the output of the builtin contract with address `a` at block number `at`.
+	/// Panics if `is_builtin(a, at)` is not true.
+	fn output_size_of_builtin(&self, a: &Address, at: BlockNumber, input: &[u8]) -> usize {
+		self.active_builtin(a, at).expect("builtin is active; qed").output_size(input)
+	}
+	/// Execution the builtin contract `a` at block number `at` on `input` and return `output`, with `storage`
+	/// being the storage of the builtin's account. Returns an error if the builtin could not process `input`.
+	/// Panics if `is_builtin(a, at)` is not true.
+	fn execute_builtin(&self, a: &Address, at: BlockNumber, input: &[u8], output: &mut [u8], storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+		self.active_builtin(a, at).expect("builtin is active; qed").execute(input, output, storage)
+	}
+
+	/// Gives the engine access to the chain it runs on, e.g. to read state during verification.
+	fn register_client(&self, _client: Weak<Client>) {}
+
+	/// Sets the account the engine signs its consensus messages with.
+	fn set_signer(&self, _accounts: Arc<AccountProvider>, _address: Address) {}
+
+	/// Handle a consensus message received from another node of the network.
+	fn handle_message(&self, _message: &[u8]) -> Result<(), Error> { Err(EngineError::UnexpectedMessage.into()) }
+
+	/// Called periodically by the client so that the engine can act on its timeouts.
+	fn tick(&self) {}
 
 	// TODO: sealing stuff - though might want to leave this for later.
 }
@@ -59,6 +59,8 @@ pub enum TransactionError {
 	},
 	/// Transaction's gas limit (aka gas) is invalid.
 	InvalidGasLimit(OutOfBounds<U256>),
+	/// Transaction is replay-protected for another chain, or before replay protection is enabled.
+	InvalidChainId(u64),
 }
 
 impl fmt::Display for TransactionError {
@@ -77,6 +79,7 @@ impl fmt::Display for TransactionError {
 			GasLimitExceeded { limit, got } =>
 				format!("Gas limit exceeded. Limit={}, Given={}", limit, got),
 			InvalidGasLimit(ref err) => format!("Invalid gas limit. {}", err),
+			InvalidChainId(chain_id) => format!("Invalid chain id {}", chain_id),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
@@ -129,6 +132,8 @@ pub enum BlockError {
 	InvalidReceiptsRoot(Mismatch<H256>),
 	/// Timestamp header field is invalid.
 	InvalidTimestamp(OutOfBounds<u64>),
+	/// Step recorded in the seal is not after the parent's or is in the future.
+	InvalidStep(OutOfBounds<u64>),
 	/// Log bloom header field is invalid.
 	InvalidLogBloom(Mismatch<LogBloom>),
 	/// Parent hash field of header is invalid; this is an invalid error indicating a logic flaw in the codebase.
@@ -169,6 +174,7 @@ impl fmt::Display for BlockError {
 			InvalidGasLimit(ref oob) => format!("Invalid gas limit: {}", oob),
 			InvalidReceiptsRoot(ref mis) => format!("Invalid receipts trie root in header: {}", mis),
 			InvalidTimestamp(ref oob) => format!("Invalid timestamp in header: {}", oob),
+			InvalidStep(ref oob) => format!("Invalid step in seal: {}", oob),
 			InvalidLogBloom(ref oob) => format!("Invalid log bloom in header: {}", oob),
 			InvalidParentHash(ref mis) => format!("Invalid parent hash: {}", mis),
 			InvalidNumber(ref mis) => format!("Invalid number in header: {}", mis),
@@ -181,6 +187,43 @@ impl fmt::Display for BlockError {
 	}
 }
 
+#[derive(Debug, PartialEq, Clone)]
+/// Errors concerning consensus messages and seals of the engines which exchange them.
+pub enum EngineError {
+	/// The engine does not take consensus messages.
+	UnexpectedMessage,
+	/// Consensus message which does not decode or whose signer cannot be recovered.
+	MalformedMessage,
+	/// Message or signature by an account which is not a validator.
+	NotAuthorized(Address),
+	/// Proposal signed by an account which is not the proposer of its round.
+	NotProposer(Mismatch<Address>),
+	/// Message is for a height other than the one currently being decided.
+	WrongHeight(Mismatch<BlockNumber>),
+	/// Seal does not carry precommits from enough validators.
+	InsufficientPrecommits(OutOfBounds<usize>),
+	/// Proposal carries a block other than the one it proposes.
+	ProposalMismatch(Mismatch<H256>),
+}
+
+impl fmt::Display for EngineError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		use self::EngineError::*;
+
+		let msg = match *self {
+			UnexpectedMessage => "This engine does not take consensus messages.".into(),
+			MalformedMessage => "Consensus message is malformed.".into(),
+			NotAuthorized(ref address) => format!("Signer {} is not a validator.", address),
+			NotProposer(ref mis) => format!("Proposal not signed by the proposer of its round: {}", mis),
+			WrongHeight(ref mis) => format!("Message for another height: {}", mis),
+			InsufficientPrecommits(ref oob) => format!("Too few precommits in seal: {}", oob),
+			ProposalMismatch(ref mis) => format!("Proposal carries another block: {}", mis),
+		};
+
+		f.write_fmt(format_args!("Engine error ({})", msg))
+	}
+}
+
 #[derive(Debug, PartialEq)]
 /// Import to the block queue result
 pub enum ImportError {
@@ -215,12 +258,16 @@ pub enum Error {
 	Block(BlockError),
 	/// Unknown engine given.
 	UnknownEngineName(String),
+	/// Unknown builtin name given.
+	UnknownBuiltinName(String),
 	/// Error concerning EVM code execution.
 	Execution(ExecutionError),
 	/// Error concerning transaction processing.
 	Transaction(TransactionError),
 	/// Error concerning block import.
 	Import(ImportError),
+	/// Error concerning consensus messages or seals.
+	Engine(EngineError),
 	/// PoW hash is invalid or out of date.
 	PowHashInvalid,
 	/// The value of the nonce or mishash is invalid.
@@ -242,8 +289,11 @@ impl fmt::Display for Error {
 			Error::Execution(ref err) => f.write_fmt(format_args!("{}", err)),
 			Error::Transaction(ref err) => f.write_fmt(format_args!("{}", err)),
 			Error::Import(ref err) => f.write_fmt(format_args!("{}", err)),
+			Error::Engine(ref err) => f.write_fmt(format_args!("{}", err)),
 			Error::UnknownEngineName(ref name) =>
 				f.write_fmt(format_args!("Unknown engine name ({})", name)),
+			Error::UnknownBuiltinName(ref name) =>
+				f.write_fmt(format_args!("Unknown builtin name ({})", name)),
 			Error::PowHashInvalid => f.write_str("Invalid or out of date PoW hash."),
 			Error::PowInvalid => f.write_str("Invalid nonce or mishash"),
 			Error::Trie(ref err) => f.write_fmt(format_args!("{}", err)),
@@ -274,6 +324,12 @@ impl From<ImportError> for Error {
 	}
 }
 
+impl From<EngineError> for Error {
+	fn from(err: EngineError) -> Error {
+		Error::Engine(err)
+	}
+}
+
 impl From<BlockError> for Error {
 	fn from(err: BlockError) -> Error {
 		Error::Block(err)
@@ -341,6 +397,7 @@ impl From<BlockImportError> for Error {
 binary_fixed_size!(BlockError);
 binary_fixed_size!(ImportError);
 binary_fixed_size!(TransactionError);
+binary_fixed_size!(EngineError);
 
 // TODO: uncomment below once https://github.com/rust-lang/rust/issues/27336 sorted.
 /*#![feature(concat_idents)]
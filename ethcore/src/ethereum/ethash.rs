@@ -105,11 +105,13 @@ impl Engine for Ethash {
 	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
 		trace!(target: "client", "Creating schedule. fCML={}", self.ethash_params.frontier_compatibility_mode_limit);
 
-		if env_info.number < self.ethash_params.frontier_compatibility_mode_limit {
+		let mut schedule = if env_info.number < self.ethash_params.frontier_compatibility_mode_limit {
 			Schedule::new_frontier()
 		} else {
 			Schedule::new_homestead()
-		}
+		};
+		self.params.update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
@@ -246,7 +248,7 @@ impl Engine for Ethash {
 		if header.number() >= self.ethash_params.frontier_compatibility_mode_limit {
 			try!(t.check_low_s());
 		}
-		Ok(())
+		self.verify_transaction_chain_id(t, header)
 	}
 
 	fn verify_transaction(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
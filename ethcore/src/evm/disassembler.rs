@@ -0,0 +1,270 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EVM bytecode disassembler.
+
+use std::fmt;
+use std::collections::BTreeSet;
+use rustc_serialize::hex::ToHex;
+use util::{Bytes, U256, Uint};
+use evm::instructions::{self, Instruction, GasPriceTier};
+use evm::Schedule;
+
+/// Instruction of disassembled code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+	/// Position of the instruction in code.
+	pub pc: usize,
+	/// The instruction.
+	pub instruction: Instruction,
+	/// Data pushed by a `PUSH*` instruction. Shorter than the instruction says if code ends first.
+	pub push_data: Option<Bytes>,
+}
+
+impl Operation {
+	/// Mnemonic of the instruction, empty for undefined instructions.
+	pub fn name(&self) -> &'static str {
+		instructions::INSTRUCTIONS[self.instruction as usize].name
+	}
+
+	fn is_valid(&self) -> bool {
+		instructions::INSTRUCTIONS[self.instruction as usize].tier != GasPriceTier::Invalid
+	}
+
+	fn ends_block(&self) -> bool {
+		match self.instruction {
+			instructions::STOP | instructions::JUMP | instructions::JUMPI | instructions::RETURN | instructions::REVERT | instructions::SUICIDE => true,
+			_ => !self.is_valid(),
+		}
+	}
+
+	fn falls_through(&self) -> bool {
+		match self.instruction {
+			instructions::JUMPI => true,
+			_ => !self.ends_block(),
+		}
+	}
+}
+
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		try!(write!(f, "{:04x} ", self.pc));
+		match self.is_valid() {
+			true => try!(write!(f, "{}", self.name())),
+			false => try!(write!(f, "INVALID 0x{:02x}", self.instruction)),
+		}
+		if let Some(ref data) = self.push_data {
+			try!(write!(f, " 0x{}", data.to_hex()));
+		}
+		Ok(())
+	}
+}
+
+/// Gas `instruction` costs at least: costs which depend on the stack, memory or state,
+/// such as memory expansion, copying or value transfers, are left out.
+pub fn static_gas(instruction: Instruction, schedule: &Schedule) -> usize {
+	match instruction {
+		instructions::JUMPDEST => schedule.jumpdest_gas,
+		instructions::SLOAD => schedule.sload_gas,
+		instructions::SSTORE => schedule.sstore_reset_gas,
+		instructions::SHA3 => schedule.sha3_gas,
+		instructions::EXP => schedule.exp_gas,
+		instructions::BALANCE => schedule.balance_gas,
+		instructions::EXTCODESIZE => schedule.extcodesize_gas,
+		instructions::EXTCODECOPY => schedule.extcodecopy_base_gas,
+		instructions::LOG0...instructions::LOG4 => schedule.log_gas + schedule.log_topic_gas * instructions::get_log_topics(instruction),
+		instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL => schedule.call_gas,
+		instructions::CREATE => schedule.create_gas,
+		instructions::SUICIDE => schedule.suicide_gas,
+		_ => match instructions::INSTRUCTIONS[instruction as usize].tier {
+			GasPriceTier::Invalid => 0,
+			tier => schedule.tier_step_gas[instructions::get_tier_idx(tier)],
+		},
+	}
+}
+
+/// Run of code executed straight through: it is only entered at its first instruction
+/// and only left after its last one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+	/// Position of the first instruction.
+	pub start: usize,
+	/// Instructions of the block.
+	pub operations: Vec<Operation>,
+	/// Gas the instructions of the block cost at least. See `static_gas`.
+	pub static_gas: usize,
+	/// Destination of the jump ending the block, if it is pushed right before the jump.
+	pub jump: Option<usize>,
+	/// Whether execution may go on with the next block.
+	pub falls_through: bool,
+}
+
+/// Code split into basic blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disassembly {
+	/// Basic blocks, in code order.
+	pub blocks: Vec<BasicBlock>,
+	/// Positions of the `JUMPDEST` instructions, which are the only valid jump destinations.
+	pub jump_destinations: BTreeSet<usize>,
+}
+
+impl Disassembly {
+	/// Disassemble `code`, with gas costs following `schedule`.
+	pub fn new(code: &[u8], schedule: &Schedule) -> Self {
+		let mut blocks: Vec<BasicBlock> = Vec::new();
+		let mut jump_destinations = BTreeSet::new();
+		let mut operations: Vec<Operation> = Vec::new();
+		let mut pc = 0;
+
+		while pc < code.len() {
+			let instruction = code[pc];
+			if instruction == instructions::JUMPDEST {
+				jump_destinations.insert(pc);
+				if !operations.is_empty() {
+					blocks.push(Self::block(operations, schedule));
+					operations = Vec::new();
+				}
+			}
+
+			let push_data = match instructions::is_push(instruction) {
+				true => {
+					let end = ::std::cmp::min(pc + 1 + instructions::get_push_bytes(instruction), code.len());
+					Some(code[pc + 1..end].to_vec())
+				},
+				false => None,
+			};
+			let next = pc + 1 + push_data.as_ref().map_or(0, Vec::len);
+			let operation = Operation {
+				pc: pc,
+				instruction: instruction,
+				push_data: push_data,
+			};
+			let ends_block = operation.ends_block();
+			operations.push(operation);
+			if ends_block {
+				blocks.push(Self::block(operations, schedule));
+				operations = Vec::new();
+			}
+			pc = next;
+		}
+		if !operations.is_empty() {
+			blocks.push(Self::block(operations, schedule));
+		}
+
+		// running off the end of code stops execution
+		if let Some(last) = blocks.last_mut() {
+			last.falls_through = false;
+		}
+
+		Disassembly {
+			blocks: blocks,
+			jump_destinations: jump_destinations,
+		}
+	}
+
+	fn block(operations: Vec<Operation>, schedule: &Schedule) -> BasicBlock {
+		let jump = match operations.len() {
+			n if n >= 2 => match (operations[n - 1].instruction, &operations[n - 2].push_data) {
+				(instructions::JUMP, &Some(ref data)) | (instructions::JUMPI, &Some(ref data)) => {
+					let target = U256::from(data.as_slice());
+					match target.bits() <= 64 {
+						true => Some(target.low_u64() as usize),
+						false => None,
+					}
+				},
+				_ => None,
+			},
+			_ => None,
+		};
+
+		BasicBlock {
+			start: operations[0].pc,
+			static_gas: operations.iter().map(|o| static_gas(o.instruction, schedule)).fold(0, |a, b| a + b),
+			jump: jump,
+			falls_through: operations[operations.len() - 1].falls_through(),
+			operations: operations,
+		}
+	}
+
+	/// Jumps known to fail, as their destination is not a `JUMPDEST`, as (position, destination) pairs.
+	pub fn invalid_jumps(&self) -> Vec<(usize, usize)> {
+		self.blocks.iter()
+			.filter_map(|b| b.jump.map(|dest| (b.operations[b.operations.len() - 1].pc, dest)))
+			.filter(|&(_, dest)| !self.jump_destinations.contains(&dest))
+			.collect()
+	}
+}
+
+impl fmt::Display for Disassembly {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for block in &self.blocks {
+			try!(write!(f, "; block 0x{:04x}, static gas {}", block.start, block.static_gas));
+			if let Some(dest) = block.jump {
+				let valid = match self.jump_destinations.contains(&dest) {
+					true => "",
+					false => " (invalid)",
+				};
+				try!(write!(f, ", jumps to 0x{:04x}{}", dest, valid));
+			}
+			if block.falls_through {
+				try!(write!(f, ", falls through"));
+			}
+			try!(writeln!(f, ""));
+			for operation in &block.operations {
+				try!(writeln!(f, "{}", operation));
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_serialize::hex::FromHex;
+	use evm::Schedule;
+	use evm::instructions;
+	use super::Disassembly;
+
+	#[test]
+	fn should_split_code_into_basic_blocks() {
+		// PUSH1 1, PUSH1 8, JUMPI, PUSH1 0xff, JUMP, JUMPDEST, STOP, 0xef (undefined)
+		let code = "600160085760ff565b00ef".from_hex().unwrap();
+		let disassembly = Disassembly::new(&code, &Schedule::new_homestead());
+
+		assert_eq!(disassembly.jump_destinations.iter().cloned().collect::<Vec<_>>(), vec![8]);
+		assert_eq!(disassembly.blocks.iter().map(|b| b.start).collect::<Vec<_>>(), vec![0, 5, 8, 10]);
+
+		let jumpi = &disassembly.blocks[0];
+		assert_eq!(jumpi.static_gas, 3 + 3 + 10);
+		assert_eq!(jumpi.jump, Some(8));
+		assert!(jumpi.falls_through);
+
+		let jump = &disassembly.blocks[1];
+		assert_eq!(jump.jump, Some(0xff));
+		assert!(!jump.falls_through);
+
+		assert_eq!(disassembly.blocks[2].operations.iter().map(|o| o.instruction).collect::<Vec<_>>(), vec![instructions::JUMPDEST, instructions::STOP]);
+		assert_eq!(disassembly.invalid_jumps(), vec![(7, 0xff)]);
+	}
+
+	#[test]
+	fn should_print_assembly() {
+		// PUSH2 with a truncated argument
+		let code = "60105b61ab".from_hex().unwrap();
+		let disassembly = Disassembly::new(&code, &Schedule::new_homestead());
+		assert_eq!(format!("{}", disassembly), "; block 0x0000, static gas 3, falls through\n0000 PUSH1 0x10\n; block 0x0002, static gas 4\n0002 JUMPDEST\n0003 PUSH2 0xab\n");
+	}
+}
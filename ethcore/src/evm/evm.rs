@@ -57,6 +57,23 @@ pub enum Error {
 		/// What was the stack limit
 		limit: usize
 	},
+	/// `BuiltIn` is returned when a built-in contract failed on given input.
+	/// As with `OutOfGas`, all gas given to the call is consumed.
+	BuiltIn(&'static str),
+	/// `MutableCallInStaticContext` is returned when code run by a `STATICCALL`
+	/// tries to modify the state.
+	MutableCallInStaticContext,
+	/// `ReturnDataOutOfBounds` is returned when `RETURNDATACOPY` reads past
+	/// the end of the data returned by the last call.
+	ReturnDataOutOfBounds,
+	/// `Reverted` is returned when execution stops on `REVERT`. The state is
+	/// reverted as for other errors, but the gas left is not consumed.
+	Reverted {
+		/// Gas left.
+		gas_left: U256,
+		/// Data given to `REVERT`.
+		data: Bytes,
+	},
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	#[allow(dead_code)] // created only by jit
@@ -74,6 +91,8 @@ pub enum GasLeft<'a> {
 	Known(U256),
 	/// Return instruction must be processed.
 	NeedsReturn(U256, &'a [u8]),
+	/// Execution stopped on `REVERT`, with the gas left and the data to return.
+	NeedsRevert(U256, &'a [u8]),
 }
 
 /// Types that can be "finalized" using an EVM.
@@ -90,6 +109,7 @@ impl<'a> Finalize for Result<GasLeft<'a>> {
 		match self {
 			Ok(GasLeft::Known(gas)) => Ok(gas),
 			Ok(GasLeft::NeedsReturn(gas, ret_code)) => ext.ret(&gas, ret_code),
+			Ok(GasLeft::NeedsRevert(gas, data)) => Err(Error::Reverted { gas_left: gas, data: data.to_vec() }),
 			Err(err) => Err(err),
 		}
 	}
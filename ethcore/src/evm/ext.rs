@@ -27,17 +27,23 @@ pub enum ContractCreateResult {
 	Created(Address, U256),
 	/// Returned when contract creation failed.
 	/// VM doesn't have to know the reason.
-	Failed
+	Failed,
+	/// Returned when the init code hit `REVERT`.
+	/// Contains gas left and the data given to `REVERT`.
+	Reverted(U256, Bytes),
 }
 
 /// Result of externalities call function.
 pub enum MessageCallResult {
 	/// Returned when message call was successfull.
-	/// Contains gas left.
-	Success(U256),
+	/// Contains gas left and the data returned.
+	Success(U256, Bytes),
 	/// Returned when message call failed.
 	/// VM doesn't have to know the reason.
-	Failed
+	Failed,
+	/// Returned when the called code hit `REVERT`.
+	/// Contains gas left and the data given to `REVERT`.
+	Reverted(U256, Bytes),
 }
 
 /// Externalities interface for EVMs
@@ -67,6 +73,7 @@ pub trait Ext {
 	/// Returns Err, if we run out of gas.
 	/// Otherwise returns call_result which contains gas left
 	/// and true if subcall was successfull.
+	/// With `is_static`, the called code may not modify the state.
 	#[cfg_attr(feature="dev", allow(too_many_arguments))]
 	fn call(&mut self,
 			gas: &U256,
@@ -75,7 +82,8 @@ pub trait Ext {
 			value: Option<U256>,
 			data: &[u8],
 			code_address: &Address,
-			output: &mut [u8]) -> MessageCallResult;
+			output: &mut [u8],
+			is_static: bool) -> MessageCallResult;
 
 	/// Returns code at given address
 	fn extcode(&self, address: &Address) -> Bytes;
@@ -109,6 +117,9 @@ pub trait Ext {
 	/// Prepare to trace an operation. Passthrough for the VM trace.
 	fn trace_prepare_execute(&mut self, _pc: usize, _instruction: u8, _gas_cost: &U256) -> bool { false }
 
+	/// Trace the machine state before executing the prepared operation. Passthrough for the VM trace.
+	fn trace_state(&mut self, _gas: &U256, _stack: &[U256], _mem: &[u8]) {}
+
 	/// Trace the finalised execution of a single instruction.
 	fn trace_executed(&mut self, _gas_used: U256, _stack_push: &[U256], _mem_diff: Option<(usize, &[u8])>, _store_diff: Option<(U256, U256)>) {}
 }
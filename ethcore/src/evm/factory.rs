@@ -18,8 +18,10 @@
 //!
 //! TODO: consider spliting it into two separate files.
 use std::fmt;
+use std::sync::Arc;
 use evm::Evm;
-use util::{U256, Uint};
+use util::{U256, Uint, HeapSizeOf};
+use super::interpreter::{Interpreter, SharedCache};
 
 #[derive(Debug, Clone)]
 /// Type of EVM to use.
@@ -81,7 +83,8 @@ impl VMType {
 
 /// Evm factory. Creates appropriate Evm.
 pub struct Factory {
-	evm: VMType
+	evm: VMType,
+	evm_cache: Arc<SharedCache>,
 }
 
 impl Factory {
@@ -94,9 +97,9 @@ impl Factory {
 				Box::new(super::jit::JitEvm::default())
 			},
 			VMType::Interpreter => if Self::can_fit_in_usize(gas) {
-				Box::new(super::interpreter::Interpreter::<usize>::default())
+				Box::new(Interpreter::<usize>::new(self.evm_cache.clone()))
 			} else {
-				Box::new(super::interpreter::Interpreter::<U256>::default())
+				Box::new(Interpreter::<U256>::new(self.evm_cache.clone()))
 			}
 		}
 	}
@@ -107,9 +110,9 @@ impl Factory {
 	pub fn create(&self, gas: U256) -> Box<Evm> {
 		match self.evm {
 			VMType::Interpreter => if Self::can_fit_in_usize(gas) {
-				Box::new(super::interpreter::Interpreter::<usize>::default())
+				Box::new(Interpreter::<usize>::new(self.evm_cache.clone()))
 			} else {
-				Box::new(super::interpreter::Interpreter::<U256>::default())
+				Box::new(Interpreter::<U256>::new(self.evm_cache.clone()))
 			}
 		}
 	}
@@ -117,7 +120,17 @@ impl Factory {
 	/// Create new instance of specific `VMType` factory
 	pub fn new(evm: VMType) -> Self {
 		Factory {
-			evm: evm
+			evm: evm,
+			evm_cache: Arc::new(SharedCache::default()),
+		}
+	}
+
+	/// Create new instance of specific `VMType` factory, whose interpreters share
+	/// a code analysis cache of at most `cache_size` bytes.
+	pub fn with_cache_size(evm: VMType, cache_size: usize) -> Self {
+		Factory {
+			evm: evm,
+			evm_cache: Arc::new(SharedCache::new(cache_size)),
 		}
 	}
 
@@ -130,17 +143,19 @@ impl Default for Factory {
 	/// Returns jitvm factory
 	#[cfg(feature = "jit")]
 	fn default() -> Factory {
-		Factory {
-			evm: VMType::Jit
-		}
+		Factory::new(VMType::Jit)
 	}
 
 	/// Returns native rust evm factory
 	#[cfg(not(feature = "jit"))]
 	fn default() -> Factory {
-		Factory {
-			evm: VMType::Interpreter
-		}
+		Factory::new(VMType::Interpreter)
+	}
+}
+
+impl HeapSizeOf for Factory {
+	fn heap_size_of_children(&self) -> usize {
+		self.evm_cache.heap_size_of_children()
 	}
 }
 
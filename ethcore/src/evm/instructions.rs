@@ -16,6 +16,7 @@
 
 //! VM Instructions list and utility functions
 
+/// Virtual machine bytecode instruction.
 pub type Instruction = u8;
 
 /// Returns true if given instruction is `PUSHN` instruction.
@@ -79,6 +80,7 @@ fn test_get_log_topics() {
 	assert_eq!(get_log_topics(LOG4), 4);
 }
 
+/// Gas price tier of an instruction.
 #[derive(PartialEq, Clone, Copy)]
 pub enum GasPriceTier {
 	/// 0 Zero
@@ -122,17 +124,25 @@ pub fn get_tier_idx (tier: GasPriceTier) -> usize {
 	}
 }
 
+/// Static information about an instruction.
 #[derive(Copy, Clone, Default)]
 pub struct InstructionInfo {
+	/// Mnemonic of the instruction.
 	pub name: &'static str,
+	/// Number of immediate bytes following the instruction in code.
 	pub additional: usize,
+	/// Number of stack items taken.
 	pub args: usize,
+	/// Number of stack items pushed.
 	pub ret: usize,
+	/// Whether the instruction has effects beyond the stack.
 	pub side_effects: bool,
+	/// Gas price tier.
 	pub tier: GasPriceTier
 }
 
 impl InstructionInfo {
+	/// Create a new instruction info.
 	pub fn new(name: &'static str, additional: usize, args: usize, ret: usize, side_effects: bool, tier: GasPriceTier) -> Self {
 		InstructionInfo {
 			name: name,
@@ -146,6 +156,7 @@ impl InstructionInfo {
 }
 
 lazy_static! {
+	/// Information about every instruction, indexed by opcode.
 	pub static ref INSTRUCTIONS: [InstructionInfo; 0x100] = {
 		let mut arr = [InstructionInfo::default(); 0x100];
 		arr[STOP as usize] =			InstructionInfo::new("STOP",			0, 0, 0, true, GasPriceTier::Zero);
@@ -185,6 +196,8 @@ lazy_static! {
 		arr[GASPRICE as usize] =		InstructionInfo::new("GASPRICE",		0, 0, 1, false, GasPriceTier::Base);
 		arr[EXTCODESIZE as usize] = 	InstructionInfo::new("EXTCODESIZE",		0, 1, 1, false, GasPriceTier::Ext);
 		arr[EXTCODECOPY as usize] = 	InstructionInfo::new("EXTCODECOPY",		0, 4, 0, true, GasPriceTier::Ext);
+		arr[RETURNDATASIZE as usize] =	InstructionInfo::new("RETURNDATASIZE",	0, 0, 1, false, GasPriceTier::Base);
+		arr[RETURNDATACOPY as usize] =	InstructionInfo::new("RETURNDATACOPY",	0, 3, 0, true, GasPriceTier::VeryLow);
 		arr[BLOCKHASH as usize] =		InstructionInfo::new("BLOCKHASH",		0, 1, 1, false, GasPriceTier::Ext);
 		arr[COINBASE as usize] =		InstructionInfo::new("COINBASE",		0, 0, 1, false, GasPriceTier::Base);
 		arr[TIMESTAMP as usize] =		InstructionInfo::new("TIMESTAMP",		0, 0, 1, false, GasPriceTier::Base);
@@ -277,6 +290,8 @@ lazy_static! {
 		arr[CALLCODE as usize] =		InstructionInfo::new("CALLCODE",		0, 7, 1, true, GasPriceTier::Special);
 		arr[RETURN as usize] =			InstructionInfo::new("RETURN",			0, 2, 0, true, GasPriceTier::Zero);
 		arr[DELEGATECALL as usize] =	InstructionInfo::new("DELEGATECALL",	0, 6, 1, true, GasPriceTier::Special);
+		arr[STATICCALL as usize] =		InstructionInfo::new("STATICCALL",		0, 6, 1, true, GasPriceTier::Special);
+		arr[REVERT as usize] =			InstructionInfo::new("REVERT",			0, 2, 0, true, GasPriceTier::Zero);
 		arr[SUICIDE as usize] = 		InstructionInfo::new("SUICIDE",			0, 1, 0, true, GasPriceTier::Zero);
 		arr
 	};
@@ -360,6 +375,10 @@ pub const GASPRICE: Instruction = 0x3a;
 pub const EXTCODESIZE: Instruction = 0x3b;
 /// copy external code (from another contract)
 pub const EXTCODECOPY: Instruction = 0x3c;
+/// get size of the data returned by the last call
+pub const RETURNDATASIZE: Instruction = 0x3d;
+/// copy the data returned by the last call to memory
+pub const RETURNDATACOPY: Instruction = 0x3e;
 
 /// get hash of most recent complete block
 pub const BLOCKHASH: Instruction = 0x40;
@@ -553,6 +572,10 @@ pub const CALLCODE: Instruction = 0xf2;
 pub const RETURN: Instruction = 0xf3;
 /// like CALLCODE but keeps caller's value and sender
 pub const DELEGATECALL: Instruction = 0xf4;
+/// like CALL but disallows state modifications
+pub const STATICCALL: Instruction = 0xfa;
+/// halt execution, reverting state changes but returning data and remaining gas
+pub const REVERT: Instruction = 0xfd;
 /// halt execution and register account for later deletion
 pub const SUICIDE: Instruction = 0xff;
 
@@ -32,7 +32,9 @@ macro_rules! overflowing {
 enum InstructionCost<Cost: CostType> {
 	Gas(Cost),
 	GasMem(Cost, Cost),
-	GasMemCopy(Cost, Cost, Cost)
+	GasMemCopy(Cost, Cost, Cost),
+	/// Gas, memory and the gas requested for a callee; all remaining gas if `None`.
+	GasMemProvide(Cost, Cost, Option<U256>),
 }
 
 pub struct Gasometer<Gas: CostType> {
@@ -54,6 +56,8 @@ impl<Gas: CostType> Gasometer<Gas> {
 		}
 	}
 
+	/// The cost of `instruction`, the memory size it needs and, for `*CALL*` and `CREATE`,
+	/// the gas provided to the callee, which is included in the cost.
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
 	pub fn get_gas_cost_mem(
 		&mut self,
@@ -62,7 +66,7 @@ impl<Gas: CostType> Gasometer<Gas> {
 		info: &InstructionInfo,
 		stack: &Stack<U256>,
 		current_mem_size: usize,
-	) -> evm::Result<(Gas, usize)> {
+	) -> evm::Result<(Gas, usize, Option<Gas>)> {
 		let schedule = ext.schedule();
 		let tier = instructions::get_tier_idx(info.tier);
 		let default_gas = Gas::from(schedule.tier_step_gas[tier]);
@@ -94,7 +98,7 @@ impl<Gas: CostType> Gasometer<Gas> {
 			instructions::MSTORE8 => {
 				InstructionCost::GasMem(default_gas, try!(self.mem_needed_const(stack.peek(0), 1)))
 			},
-			instructions::RETURN => {
+			instructions::RETURN | instructions::REVERT => {
 				InstructionCost::GasMem(default_gas, try!(self.mem_needed(stack.peek(0), stack.peek(1))))
 			},
 			instructions::SHA3 => {
@@ -103,11 +107,24 @@ impl<Gas: CostType> Gasometer<Gas> {
 				let gas = Gas::from(schedule.sha3_gas) + (Gas::from(schedule.sha3_word_gas) * words);
 				InstructionCost::GasMem(gas, try!(self.mem_needed(stack.peek(0), stack.peek(1))))
 			},
-			instructions::CALLDATACOPY | instructions::CODECOPY => {
+			instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY => {
 				InstructionCost::GasMemCopy(default_gas, try!(self.mem_needed(stack.peek(0), stack.peek(2))), try!(Gas::from_u256(*stack.peek(2))))
 			},
+			instructions::BALANCE => {
+				InstructionCost::Gas(Gas::from(schedule.balance_gas))
+			},
+			instructions::EXTCODESIZE => {
+				InstructionCost::Gas(Gas::from(schedule.extcodesize_gas))
+			},
+			instructions::SUICIDE => {
+				let mut gas = Gas::from(schedule.suicide_gas);
+				if !ext.exists(&u256_to_address(stack.peek(0))) {
+					gas = overflowing!(gas.overflow_add(Gas::from(schedule.suicide_to_new_account_cost)));
+				}
+				InstructionCost::Gas(gas)
+			},
 			instructions::EXTCODECOPY => {
-				InstructionCost::GasMemCopy(default_gas, try!(self.mem_needed(stack.peek(1), stack.peek(3))), try!(Gas::from_u256(*stack.peek(3))))
+				InstructionCost::GasMemCopy(Gas::from(schedule.extcodecopy_base_gas), try!(self.mem_needed(stack.peek(1), stack.peek(3))), try!(Gas::from_u256(*stack.peek(3))))
 			},
 			instructions::LOG0...instructions::LOG4 => {
 				let no_of_topics = instructions::get_log_topics(instruction);
@@ -118,7 +135,7 @@ impl<Gas: CostType> Gasometer<Gas> {
 				InstructionCost::GasMem(gas, try!(self.mem_needed(stack.peek(0), stack.peek(1))))
 			},
 			instructions::CALL | instructions::CALLCODE => {
-				let mut gas  = overflowing!(add_gas_usize(try!(Gas::from_u256(*stack.peek(0))), schedule.call_gas));
+				let mut gas = Gas::from(schedule.call_gas);
 				let mem = cmp::max(
 					try!(self.mem_needed(stack.peek(5), stack.peek(6))),
 					try!(self.mem_needed(stack.peek(3), stack.peek(4)))
@@ -134,20 +151,20 @@ impl<Gas: CostType> Gasometer<Gas> {
 					gas = overflowing!(gas.overflow_add(Gas::from(schedule.call_value_transfer_gas)));
 				};
 
-				InstructionCost::GasMem(gas,mem)
+				InstructionCost::GasMemProvide(gas, mem, Some(*stack.peek(0)))
 			},
-			instructions::DELEGATECALL => {
-				let gas = overflowing!(add_gas_usize(try!(Gas::from_u256(*stack.peek(0))), schedule.call_gas));
+			instructions::DELEGATECALL | instructions::STATICCALL => {
+				let gas = Gas::from(schedule.call_gas);
 				let mem = cmp::max(
 					try!(self.mem_needed(stack.peek(4), stack.peek(5))),
 					try!(self.mem_needed(stack.peek(2), stack.peek(3)))
 				);
-				InstructionCost::GasMem(gas, mem)
+				InstructionCost::GasMemProvide(gas, mem, Some(*stack.peek(0)))
 			},
 			instructions::CREATE => {
 				let gas = Gas::from(schedule.create_gas);
 				let mem = try!(self.mem_needed(stack.peek(1), stack.peek(2)));
-				InstructionCost::GasMem(gas, mem)
+				InstructionCost::GasMemProvide(gas, mem, None)
 			},
 			instructions::EXP => {
 				let expon = stack.peek(1);
@@ -160,12 +177,20 @@ impl<Gas: CostType> Gasometer<Gas> {
 
 		match cost {
 			InstructionCost::Gas(gas) => {
-				Ok((gas, 0))
+				Ok((gas, 0, None))
 			},
 			InstructionCost::GasMem(gas, mem_size) => {
 				let (mem_gas, new_mem_size) = try!(self.mem_gas_cost(schedule, current_mem_size, &mem_size));
 				let gas = overflowing!(gas.overflow_add(mem_gas));
-				Ok((gas, new_mem_size))
+				Ok((gas, new_mem_size, None))
+			},
+			InstructionCost::GasMemProvide(gas, mem_size, requested) => {
+				let (mem_gas, new_mem_size) = try!(self.mem_gas_cost(schedule, current_mem_size, &mem_size));
+				let gas = overflowing!(gas.overflow_add(mem_gas));
+				match try!(self.gas_provided(schedule, gas, requested)) {
+					Some(provided) => Ok((overflowing!(gas.overflow_add(provided)), new_mem_size, Some(provided))),
+					None => Ok((gas, new_mem_size, None)),
+				}
 			},
 			InstructionCost::GasMemCopy(gas, mem_size, copy) => {
 				let (mem_gas, new_mem_size) = try!(self.mem_gas_cost(schedule, current_mem_size, &mem_size));
@@ -173,11 +198,31 @@ impl<Gas: CostType> Gasometer<Gas> {
 				let copy_gas = Gas::from(schedule.copy_gas) * (copy / Gas::from(32 as usize));
 				let gas = overflowing!(gas.overflow_add(copy_gas));
 				let gas = overflowing!(gas.overflow_add(mem_gas));
-				Ok((gas, new_mem_size))
+				Ok((gas, new_mem_size, None))
 			}
 		}
 	}
 
+	/// The gas passed to a callee when `needed` is charged for the call itself. With a
+	/// `sub_gas_cap_divisor` the callee gets at most all but a fraction of the gas remaining,
+	/// otherwise exactly what was `requested`, or all remaining gas if nothing was.
+	fn gas_provided(&self, schedule: &evm::Schedule, needed: Gas, requested: Option<U256>) -> evm::Result<Option<Gas>> {
+		match schedule.sub_gas_cap_divisor {
+			Some(divisor) if self.current_gas >= needed => {
+				let remaining = self.current_gas - needed;
+				let cap = remaining - remaining / Gas::from(divisor);
+				match requested {
+					Some(requested) if requested < cap.as_u256() => Gas::from_u256(requested).map(Some),
+					_ => Ok(Some(cap)),
+				}
+			},
+			_ => match requested {
+				Some(requested) => Gas::from_u256(requested).map(Some),
+				None => Ok(None),
+			},
+		}
+	}
+
 	fn is_zero(&self, val: &Gas) -> bool {
 		&Gas::from(0) == val
 	}
@@ -31,10 +31,12 @@ macro_rules! evm_debug {
 mod gasometer;
 mod stack;
 mod memory;
+mod shared_cache;
 
 use self::gasometer::Gasometer;
 use self::stack::{Stack, VecStack};
 use self::memory::Memory;
+pub use self::shared_cache::{SharedCache, JumpDestinations};
 
 use std::marker::PhantomData;
 use common::*;
@@ -48,7 +50,6 @@ fn color(instruction: Instruction, name: &'static str) -> String {
 	format!("\x1B[1;{}m{}\x1B[0m", colors[c], name)
 }
 
-type CodePosition = usize;
 type ProgramCounter = usize;
 
 /// Abstraction over raw vector of Bytes. Easier state management of PC.
@@ -80,23 +81,27 @@ enum InstructionResult<Gas> {
 	JumpToPosition(U256),
 	// gas left, init_orf, init_size
 	StopExecutionNeedsReturn(Gas, U256, U256),
+	// gas left, offset, size
+	StopExecutionNeedsRevert(Gas, U256, U256),
 	StopExecution,
 }
 
 
 /// Intepreter EVM implementation
-#[derive(Default)]
 pub struct Interpreter<Cost: CostType> {
 	mem: Vec<u8>,
+	cache: Arc<SharedCache>,
+	return_data: Bytes,
 	_type: PhantomData<Cost>,
 }
 
 impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 	fn exec(&mut self, params: ActionParams, ext: &mut evm::Ext) -> evm::Result<GasLeft> {
 		self.mem.clear();
+		self.return_data.clear();
 
 		let code = &params.code.as_ref().unwrap();
-		let valid_jump_destinations = self.find_jump_destinations(&code);
+		let valid_jump_destinations = self.cache.jump_destinations(&params.code_hash, &code);
 
 		let mut gasometer = Gasometer::<Cost>::new(try!(Cost::from_u256(params.gas)));
 		let mut stack = VecStack::with_capacity(ext.schedule().stack_limit, U256::zero());
@@ -111,12 +116,16 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 			reader.position += 1;
 
 			let info = infos[instruction as usize];
-			try!(self.verify_instruction(ext, instruction, &info, &stack));
+			try!(self.verify_instruction(ext, instruction, &info, &stack, params.is_static));
 
 			// Calculate gas cost
-			let (gas_cost, mem_size) = try!(gasometer.get_gas_cost_mem(ext, instruction, &info, &stack, self.mem.size()));
+			let (gas_cost, mem_size, provided_gas) = try!(gasometer.get_gas_cost_mem(ext, instruction, &info, &stack, self.mem.size()));
 			// TODO: make compile-time removable if too much of a performance hit.
 			let trace_executed = ext.trace_prepare_execute(reader.position - 1, instruction, &gas_cost.as_u256());
+			if trace_executed {
+				let stack_size = stack.size();
+				ext.trace_state(&gasometer.current_gas.as_u256(), stack.peek_top(stack_size), &self.mem);
+			}
 
 			try!(gasometer.verify_gas(&gas_cost));
 			self.mem.expand(mem_size);
@@ -139,7 +148,7 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 
 			// Execute instruction
 			let result = try!(self.exec_instruction(
-				gasometer.current_gas, &params, ext, instruction, &mut reader, &mut stack
+				gasometer.current_gas, &params, ext, instruction, provided_gas, &mut reader, &mut stack
 			));
 
 			if trace_executed {
@@ -165,6 +174,9 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 				InstructionResult::StopExecutionNeedsReturn(gas, off, size) => {
 					return Ok(GasLeft::NeedsReturn(gas.as_u256(), self.mem.read_slice(off, size)));
 				},
+				InstructionResult::StopExecutionNeedsRevert(gas, off, size) => {
+					return Ok(GasLeft::NeedsRevert(gas.as_u256(), self.mem.read_slice(off, size)));
+				},
 				InstructionResult::StopExecution => break,
 			}
 		}
@@ -174,11 +186,27 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 }
 
 impl<Cost: CostType> Interpreter<Cost> {
+	/// Create a new interpreter looking up code analysis in `cache`.
+	pub fn new(cache: Arc<SharedCache>) -> Interpreter<Cost> {
+		Interpreter {
+			mem: Vec::new(),
+			cache: cache,
+			return_data: Vec::new(),
+			_type: PhantomData::default(),
+		}
+	}
 
-	fn verify_instruction(&self, ext: &evm::Ext, instruction: Instruction, info: &InstructionInfo, stack: &Stack<U256>) -> evm::Result<()> {
+	fn verify_instruction(&self, ext: &evm::Ext, instruction: Instruction, info: &InstructionInfo, stack: &Stack<U256>, is_static: bool) -> evm::Result<()> {
 		let schedule = ext.schedule();
 
-		if !schedule.have_delegate_call && instruction == instructions::DELEGATECALL {
+		let enabled = match instruction {
+			instructions::DELEGATECALL => schedule.have_delegate_call,
+			instructions::REVERT => schedule.have_revert,
+			instructions::RETURNDATASIZE | instructions::RETURNDATACOPY => schedule.have_return_data,
+			instructions::STATICCALL => schedule.have_static_call,
+			_ => true,
+		};
+		if !enabled {
 			return Err(evm::Error::BadInstruction {
 				instruction: instruction
 			});
@@ -202,11 +230,21 @@ impl<Cost: CostType> Interpreter<Cost> {
 				wanted: info.ret - info.args,
 				limit: schedule.stack_limit
 			})
+		} else if is_static && Self::modifies_state(instruction, stack) {
+			Err(evm::Error::MutableCallInStaticContext)
 		} else {
 			Ok(())
 		}
 	}
 
+	fn modifies_state(instruction: Instruction, stack: &Stack<U256>) -> bool {
+		match instruction {
+			instructions::SSTORE | instructions::CREATE | instructions::SUICIDE | instructions::LOG0...instructions::LOG4 => true,
+			instructions::CALL => !stack.peek(2).is_zero(),
+			_ => false,
+		}
+	}
+
 	fn mem_written(
 		instruction: Instruction,
 		stack: &Stack<U256>
@@ -214,10 +252,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 		match instruction {
 			instructions::MSTORE | instructions::MLOAD => Some((stack.peek(0).low_u64() as usize, 32)),
 			instructions::MSTORE8 => Some((stack.peek(0).low_u64() as usize, 1)),
-			instructions::CALLDATACOPY | instructions::CODECOPY => Some((stack.peek(0).low_u64() as usize, stack.peek(2).low_u64() as usize)),
+			instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY => Some((stack.peek(0).low_u64() as usize, stack.peek(2).low_u64() as usize)),
 			instructions::EXTCODECOPY => Some((stack.peek(1).low_u64() as usize, stack.peek(3).low_u64() as usize)),
 			instructions::CALL | instructions::CALLCODE => Some((stack.peek(5).low_u64() as usize, stack.peek(6).low_u64() as usize)),
-			instructions::DELEGATECALL => Some((stack.peek(4).low_u64() as usize, stack.peek(5).low_u64() as usize)),
+			instructions::DELEGATECALL | instructions::STATICCALL => Some((stack.peek(4).low_u64() as usize, stack.peek(5).low_u64() as usize)),
 			_ => None,
 		}
 	}
@@ -239,6 +277,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 		params: &ActionParams,
 		ext: &mut evm::Ext,
 		instruction: Instruction,
+		provided_gas: Option<Cost>,
 		code: &mut CodeReader,
 		stack: &mut Stack<U256>
 	) -> evm::Result<InstructionResult<Cost>> {
@@ -269,9 +308,30 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let contract_code = self.mem.read_slice(init_off, init_size);
 				let can_create = ext.balance(&params.address) >= endowment && ext.depth() < ext.schedule().max_depth;
 
+				self.return_data.clear();
+
 				if !can_create {
 					stack.push(U256::zero());
-					return Ok(InstructionResult::Ok);
+					return Ok(provided_gas.map_or(InstructionResult::Ok, InstructionResult::UnusedGas));
+				}
+
+				// with a gas cap, part of the gas is kept from the callee and was charged as provided.
+				if let Some(provided_gas) = provided_gas {
+					return match ext.create(&provided_gas.as_u256(), &endowment, &contract_code) {
+						ContractCreateResult::Created(address, gas_left) => {
+							stack.push(address_to_u256(address));
+							Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
+						},
+						ContractCreateResult::Reverted(gas_left, data) => {
+							stack.push(U256::zero());
+							self.return_data = data;
+							Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
+						},
+						ContractCreateResult::Failed => {
+							stack.push(U256::zero());
+							Ok(InstructionResult::Ok)
+						}
+					};
 				}
 
 				let create_result = ext.create(&gas.as_u256(), &endowment, &contract_code);
@@ -280,6 +340,11 @@ impl<Cost: CostType> Interpreter<Cost> {
 						stack.push(address_to_u256(address));
 						Ok(InstructionResult::GasLeft(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
 					},
+					ContractCreateResult::Reverted(gas_left, data) => {
+						stack.push(U256::zero());
+						self.return_data = data;
+						Ok(InstructionResult::GasLeft(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
+					},
 					ContractCreateResult::Failed => {
 						stack.push(U256::zero());
 						// TODO [todr] Should we just StopExecution here?
@@ -287,16 +352,18 @@ impl<Cost: CostType> Interpreter<Cost> {
 					}
 				};
 			},
-			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL => {
+			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL => {
 				assert!(ext.schedule().call_value_transfer_gas > ext.schedule().call_stipend, "overflow possible");
-				let call_gas = Cost::from_u256(stack.pop_back()).expect("Gas is already validated.");
+				stack.pop_back();
+				let call_gas = provided_gas.expect("gas provided to calls is always known; qed");
 				let code_address = stack.pop_back();
 				let code_address = u256_to_address(&code_address);
 
-				let value = if instruction == instructions::DELEGATECALL {
-					None
-				} else {
-					Some(stack.pop_back())
+				// a static call transfers nothing, so the callee sees no value
+				let value = match instruction {
+					instructions::DELEGATECALL => None,
+					instructions::STATICCALL => Some(U256::zero()),
+					_ => Some(stack.pop_back()),
 				};
 
 				let in_off = stack.pop_back();
@@ -321,9 +388,12 @@ impl<Cost: CostType> Interpreter<Cost> {
 						(&params.address, &params.address, has_balance)
 					},
 					instructions::DELEGATECALL => (&params.sender, &params.address, true),
+					instructions::STATICCALL => (&params.address, &code_address, true),
 					_ => panic!(format!("Unexpected instruction {} in CALL branch.", instruction))
 				};
 
+				self.return_data.clear();
+
 				let can_call = has_balance && ext.depth() < ext.schedule().max_depth;
 				if !can_call {
 					stack.push(U256::zero());
@@ -335,12 +405,18 @@ impl<Cost: CostType> Interpreter<Cost> {
 					// and we don't want to copy
 					let input = unsafe { ::std::mem::transmute(self.mem.read_slice(in_off, in_size)) };
 					let output = self.mem.writeable_slice(out_off, out_size);
-					ext.call(&call_gas.as_u256(), sender_address, receive_address, value, input, &code_address, output)
+					ext.call(&call_gas.as_u256(), sender_address, receive_address, value, input, &code_address, output, instruction == instructions::STATICCALL)
 				};
 
 				return match call_result {
-					MessageCallResult::Success(gas_left) => {
+					MessageCallResult::Success(gas_left, data) => {
 						stack.push(U256::one());
+						self.return_data = data;
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
+					},
+					MessageCallResult::Reverted(gas_left, data) => {
+						stack.push(U256::zero());
+						self.return_data = data;
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
 					},
 					MessageCallResult::Failed  => {
@@ -355,6 +431,12 @@ impl<Cost: CostType> Interpreter<Cost> {
 
 				return Ok(InstructionResult::StopExecutionNeedsReturn(gas, init_off, init_size))
 			},
+			instructions::REVERT => {
+				let offset = stack.pop_back();
+				let size = stack.pop_back();
+
+				return Ok(InstructionResult::StopExecutionNeedsRevert(gas, offset, size))
+			},
 			instructions::STOP => {
 				return Ok(InstructionResult::StopExecution);
 			},
@@ -482,6 +564,17 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let code = ext.extcode(&address);
 				self.copy_data_to_memory(stack, &code);
 			},
+			instructions::RETURNDATASIZE => {
+				stack.push(U256::from(self.return_data.len()));
+			},
+			instructions::RETURNDATACOPY => {
+				let (end, overflow) = stack.peek(1).overflowing_add(*stack.peek(2));
+				if overflow || end > U256::from(self.return_data.len()) {
+					return Err(evm::Error::ReturnDataOutOfBounds);
+				}
+				let return_data = self.return_data.clone();
+				self.copy_data_to_memory(stack, &return_data);
+			},
 			instructions::GASPRICE => {
 				stack.push(params.gas_price.clone());
 			},
@@ -539,10 +632,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 		}
 	}
 
-	fn verify_jump(&self, jump_u: U256, valid_jump_destinations: &HashSet<usize>) -> evm::Result<usize> {
+	fn verify_jump(&self, jump_u: U256, valid_jump_destinations: &JumpDestinations) -> evm::Result<usize> {
 		let jump = jump_u.low_u64() as usize;
 
-		if valid_jump_destinations.contains(&jump) && jump_u < U256::from(!0 as usize) {
+		if valid_jump_destinations.contains(jump) && jump_u < U256::from(!0 as usize) {
 			Ok(jump)
 		} else {
 			Err(evm::Error::BadJumpDestination {
@@ -763,24 +856,6 @@ impl<Cost: CostType> Interpreter<Cost> {
 		}
 		Ok(())
 	}
-
-	fn find_jump_destinations(&self, code: &[u8]) -> HashSet<CodePosition> {
-		let mut jump_dests = HashSet::new();
-		let mut position = 0;
-
-		while position < code.len() {
-			let instruction = code[position];
-
-			if instruction == instructions::JUMPDEST {
-				jump_dests.insert(position);
-			} else if instructions::is_push(instruction) {
-				position += instructions::get_push_bytes(instruction);
-			}
-			position += 1;
-		}
-
-		jump_dests
-	}
 }
 
 fn get_and_reset_sign(value: U256) -> (U256, bool) {
@@ -810,12 +885,11 @@ fn address_to_u256(value: Address) -> U256 {
 #[test]
 fn test_find_jump_destinations() {
 	// given
-	let interpreter = Interpreter::<U256>::default();
 	let code = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff5b01600055".from_hex().unwrap();
 
 	// when
-	let valid_jump_destinations = interpreter.find_jump_destinations(&code);
+	let valid_jump_destinations = JumpDestinations::new(&code);
 
 	// then
-	assert!(valid_jump_destinations.contains(&66));
+	assert!(valid_jump_destinations.contains(66));
 }
@@ -0,0 +1,237 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Code analysis shared between interpreters.
+
+use common::*;
+use evm::instructions;
+
+/// Default size of the cache, in bytes.
+pub const DEFAULT_CACHE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Positions of the `JUMPDEST` instructions of some code, push data excluded.
+#[derive(Debug, PartialEq)]
+pub struct JumpDestinations {
+	bits: Vec<u64>,
+}
+
+impl JumpDestinations {
+	/// Find the jump destinations of `code`.
+	pub fn new(code: &[u8]) -> Self {
+		let mut bits = vec![0u64; (code.len() + 63) / 64];
+		let mut position = 0;
+
+		while position < code.len() {
+			let instruction = code[position];
+
+			if instruction == instructions::JUMPDEST {
+				bits[position / 64] |= 1u64 << (position % 64);
+			} else if instructions::is_push(instruction) {
+				position += instructions::get_push_bytes(instruction);
+			}
+			position += 1;
+		}
+
+		JumpDestinations {
+			bits: bits,
+		}
+	}
+
+	/// Whether `position` is a valid jump destination.
+	pub fn contains(&self, position: usize) -> bool {
+		self.bits.get(position / 64).map_or(false, |word| word & (1u64 << (position % 64)) != 0)
+	}
+}
+
+impl HeapSizeOf for JumpDestinations {
+	fn heap_size_of_children(&self) -> usize {
+		self.bits.heap_size_of_children()
+	}
+}
+
+struct Entry {
+	jump_destinations: Arc<JumpDestinations>,
+	last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+	by_hash: HashMap<H256, Entry>,
+	/// Cached code hashes by the time they were last used, least recently used first.
+	by_use: BTreeMap<u64, H256>,
+	/// Ticks on every use of the cache.
+	clock: u64,
+	size: usize,
+}
+
+impl Entries {
+	fn tick(&mut self) -> u64 {
+		self.clock += 1;
+		self.clock
+	}
+}
+
+fn entry_size(jump_destinations: &JumpDestinations) -> usize {
+	// the bitmap, plus the key and the time of last use stored in both maps
+	jump_destinations.heap_size_of_children() + mem::size_of::<JumpDestinations>() + 2 * (mem::size_of::<H256>() + mem::size_of::<u64>())
+}
+
+/// Jump destinations of the code run by the interpreters of a `Factory`, keyed by code hash.
+/// Once `max_size` bytes are used, the least recently used entries are evicted first.
+pub struct SharedCache {
+	entries: Mutex<Entries>,
+	max_size: usize,
+}
+
+impl SharedCache {
+	/// Create a cache holding at most `max_size` bytes.
+	pub fn new(max_size: usize) -> Self {
+		SharedCache {
+			entries: Mutex::new(Entries::default()),
+			max_size: max_size,
+		}
+	}
+
+	/// Get the jump destinations of `code`, analysing it unless it has been seen before.
+	/// Code whose hash is not known, i.e. `SHA3_EMPTY`, is analysed every time.
+	pub fn jump_destinations(&self, code_hash: &H256, code: &[u8]) -> Arc<JumpDestinations> {
+		if code_hash == &SHA3_EMPTY {
+			return Arc::new(JumpDestinations::new(code));
+		}
+
+		{
+			let mut entries = self.entries.lock();
+			let now = entries.tick();
+			let last_used = entries.by_hash.get_mut(code_hash).map(|entry| mem::replace(&mut entry.last_used, now));
+			if let Some(last_used) = last_used {
+				entries.by_use.remove(&last_used);
+				entries.by_use.insert(now, code_hash.clone());
+				return entries.by_hash[code_hash].jump_destinations.clone();
+			}
+		}
+
+		let jump_destinations = Arc::new(JumpDestinations::new(code));
+		let size = entry_size(&jump_destinations);
+		if size > self.max_size {
+			return jump_destinations;
+		}
+
+		let mut entries = self.entries.lock();
+		if entries.by_hash.contains_key(code_hash) {
+			// analysed concurrently by another interpreter
+			return jump_destinations;
+		}
+
+		while entries.size + size > self.max_size {
+			let least_used = entries.by_use.keys().next().cloned().expect("size is non-zero only with entries in the maps; qed");
+			let evicted = entries.by_use.remove(&least_used).expect("key taken from the map above; qed");
+			let evicted = entries.by_hash.remove(&evicted).expect("both maps hold the same code hashes; qed");
+			entries.size -= entry_size(&evicted.jump_destinations);
+		}
+
+		let now = entries.tick();
+		entries.by_hash.insert(code_hash.clone(), Entry {
+			jump_destinations: jump_destinations.clone(),
+			last_used: now,
+		});
+		entries.by_use.insert(now, code_hash.clone());
+		entries.size += size;
+		jump_destinations
+	}
+
+	/// Number of code hashes cached.
+	pub fn len(&self) -> usize {
+		self.entries.lock().by_hash.len()
+	}
+
+	/// Whether the cache is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl Default for SharedCache {
+	fn default() -> Self {
+		SharedCache::new(DEFAULT_CACHE_SIZE)
+	}
+}
+
+impl HeapSizeOf for SharedCache {
+	fn heap_size_of_children(&self) -> usize {
+		self.entries.lock().size
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use super::{SharedCache, JumpDestinations};
+
+	fn same(a: &Arc<JumpDestinations>, b: &Arc<JumpDestinations>) -> bool {
+		&**a as *const JumpDestinations == &**b as *const JumpDestinations
+	}
+
+	#[test]
+	fn should_find_jump_destinations_skipping_push_data() {
+		// PUSH32 (with a JUMPDEST byte in its data), JUMPDEST, STOP
+		let code = "7f5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b5b00".from_hex().unwrap();
+		let jump_destinations = JumpDestinations::new(&code);
+
+		assert!(jump_destinations.contains(33));
+		assert!(!(0..33).any(|position| jump_destinations.contains(position)));
+		assert!(!jump_destinations.contains(34));
+		assert!(!jump_destinations.contains(1000));
+	}
+
+	#[test]
+	fn should_reuse_and_evict_entries() {
+		let code_a = vec![0x5b; 64];
+		let code_b = vec![0x5b; 128];
+		let (hash_a, hash_b) = (code_a.sha3(), code_b.sha3());
+		let cache = SharedCache::new(super::entry_size(&JumpDestinations::new(&code_b)));
+
+		let first = cache.jump_destinations(&hash_a, &code_a);
+		assert!(same(&first, &cache.jump_destinations(&hash_a, &code_a)));
+		assert_eq!(cache.len(), 1);
+
+		// the second entry only fits once the first is evicted
+		cache.jump_destinations(&hash_b, &code_b);
+		assert_eq!(cache.len(), 1);
+		assert_eq!(cache.heap_size_of_children(), super::entry_size(&JumpDestinations::new(&code_b)));
+		assert!(!same(&first, &cache.jump_destinations(&hash_a, &code_a)));
+
+		// code of unknown hash is never cached
+		cache.jump_destinations(&SHA3_EMPTY, &code_a);
+		assert_eq!(cache.len(), 1);
+	}
+
+	#[test]
+	fn should_evict_least_recently_used_entries() {
+		let codes = [vec![0x5b; 64], vec![0x00; 64], vec![0x01; 64]];
+		let hashes: Vec<_> = codes.iter().map(|code| code.sha3()).collect();
+		let cache = SharedCache::new(2 * super::entry_size(&JumpDestinations::new(&codes[0])));
+
+		let first = cache.jump_destinations(&hashes[0], &codes[0]);
+		let second = cache.jump_destinations(&hashes[1], &codes[1]);
+		// using the first entry again makes the second the least recently used
+		cache.jump_destinations(&hashes[0], &codes[0]);
+		cache.jump_destinations(&hashes[2], &codes[2]);
+
+		assert_eq!(cache.len(), 2);
+		assert!(same(&first, &cache.jump_destinations(&hashes[0], &codes[0])));
+		assert!(!same(&second, &cache.jump_destinations(&hashes[1], &codes[1])));
+	}
+}
@@ -177,6 +177,10 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 					*address = new_address.into_jit();
 					*io_gas = gas_left.low_u64();
 				},
+				evm::ContractCreateResult::Reverted(gas_left, _) => unsafe {
+					*address = Address::new().into_jit();
+					*io_gas = gas_left.low_u64();
+				},
 				evm::ContractCreateResult::Failed => unsafe {
 					*address = Address::new().into_jit();
 					*io_gas = 0;
@@ -246,11 +250,17 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 					  value,
 					  unsafe { slice::from_raw_parts(in_beg, in_size as usize) },
 					  &code_address,
-					  unsafe { slice::from_raw_parts_mut(out_beg, out_size as usize) }) {
-			evm::MessageCallResult::Success(gas_left) => unsafe {
+					  unsafe { slice::from_raw_parts_mut(out_beg, out_size as usize) },
+					  // never static: the jit refuses to run code in a static context
+					  false) {
+			evm::MessageCallResult::Success(gas_left, _) => unsafe {
 				*io_gas = (gas + gas_left).low_u64();
 				true
 			},
+			evm::MessageCallResult::Reverted(gas_left, _) => unsafe {
+				*io_gas = (gas + gas_left).low_u64();
+				false
+			},
 			evm::MessageCallResult::Failed => unsafe {
 				*io_gas = gas.low_u64();
 				false
@@ -307,6 +317,15 @@ pub struct JitEvm {
 
 impl evm::Evm for JitEvm {
 	fn exec(&mut self, params: ActionParams, ext: &mut evm::Ext) -> evm::Result<GasLeft> {
+		// evmjit knows neither `REVERT`, `RETURNDATASIZE`/`RETURNDATACOPY` nor `STATICCALL`, and cannot keep
+		// code called by `STATICCALL` from modifying the state; it never runs where any of them may occur.
+		{
+			let schedule = ext.schedule();
+			if params.is_static || schedule.have_revert || schedule.have_return_data || schedule.have_static_call {
+				return Err(evm::Error::Internal);
+			}
+		}
+
 		// Dirty hack. This is unsafe, but we interact with ffi, so it's justified.
 		let ext_adapter: ExtAdapter<'static> = unsafe { ::std::mem::transmute(ExtAdapter::new(ext, params.address.clone())) };
 		let mut ext_handle = evmjit::ExtHandle::new(ext_adapter);
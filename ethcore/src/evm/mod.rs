@@ -22,7 +22,8 @@ pub mod interpreter;
 #[macro_use]
 pub mod factory;
 pub mod schedule;
-mod instructions;
+pub mod instructions;
+pub mod disassembler;
 #[cfg(feature = "jit" )]
 mod jit;
 
@@ -34,4 +35,4 @@ mod benches;
 pub use self::evm::{Evm, Error, Finalize, GasLeft, Result, CostType};
 pub use self::ext::{Ext, ContractCreateResult, MessageCallResult};
 pub use self::factory::{Factory, VMType};
-pub use self::schedule::Schedule;
+pub use self::schedule::{Schedule, ScheduleOverrides};
@@ -16,12 +16,20 @@
 
 //! Cost schedule and other parameterisations for the EVM.
 
+use ethjson;
+
 /// Definition of the cost schedule and other parameterisations for the EVM.
 pub struct Schedule {
 	/// Does it support exceptional failed code deposit
 	pub exceptional_failed_code_deposit: bool,
 	/// Does it have a delegate cal
 	pub have_delegate_call: bool,
+	/// Does it have a `REVERT` instruction (EIP-140)
+	pub have_revert: bool,
+	/// Does it have `RETURNDATASIZE` and `RETURNDATACOPY` instructions (EIP-211)
+	pub have_return_data: bool,
+	/// Does it have a `STATICCALL` instruction (EIP-214)
+	pub have_static_call: bool,
 	/// VM stack limit
 	pub stack_limit: usize,
 	/// Max number of nested calls/creates
@@ -56,12 +64,24 @@ pub struct Schedule {
 	pub create_gas: usize,
 	/// Gas price for `*CALL*` opcodes
 	pub call_gas: usize,
+	/// If `Some(d)`, `*CALL*` and `CREATE` can pass at most all but `1/d` of the remaining gas to the callee
+	pub sub_gas_cap_divisor: Option<usize>,
 	/// Stipend for transfer for `CALL|CALLCODE` opcode when `value>0`
 	pub call_stipend: usize,
 	/// Additional gas required for value transfer (`CALL|CALLCODE`)
 	pub call_value_transfer_gas: usize,
 	/// Additional gas for creating new account (`CALL|CALLCODE`)
 	pub call_new_account_gas: usize,
+	/// Gas price for `BALANCE` opcode
+	pub balance_gas: usize,
+	/// Gas price for `EXTCODESIZE` opcode
+	pub extcodesize_gas: usize,
+	/// Base gas price for `EXTCODECOPY` opcode
+	pub extcodecopy_base_gas: usize,
+	/// Gas price for `SUICIDE` opcode
+	pub suicide_gas: usize,
+	/// Additional gas for `SUICIDE` when the refund address does not exist
+	pub suicide_to_new_account_cost: usize,
 	/// Refund for SUICIDE
 	pub suicide_refund_gas: usize,
 	/// Gas for used memory
@@ -80,6 +100,8 @@ pub struct Schedule {
 	pub tx_data_non_zero_gas: usize,
 	/// Gas price for copying memory
 	pub copy_gas: usize,
+	/// Remove empty accounts touched during a transaction at its end (EIP-161)
+	pub kill_empty: bool,
 }
 
 impl Schedule {
@@ -97,6 +119,9 @@ impl Schedule {
 		Schedule{
 			exceptional_failed_code_deposit: efcd,
 			have_delegate_call: hdc,
+			have_revert: false,
+			have_return_data: false,
+			have_static_call: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -114,9 +139,15 @@ impl Schedule {
 			log_topic_gas: 375,
 			create_gas: 32000,
 			call_gas: 40,
+			sub_gas_cap_divisor: None,
 			call_stipend: 2300,
 			call_value_transfer_gas: 9000,
 			call_new_account_gas: 25000,
+			balance_gas: 20,
+			extcodesize_gas: 20,
+			extcodecopy_base_gas: 20,
+			suicide_gas: 0,
+			suicide_to_new_account_cost: 0,
 			suicide_refund_gas: 24000,
 			memory_gas: 3,
 			quad_coeff_div: 512,
@@ -126,6 +157,118 @@ impl Schedule {
 			tx_data_zero_gas: 4,
 			tx_data_non_zero_gas: 68,
 			copy_gas: 3,	
+			kill_empty: false,
 		}
 	}
 }
+
+/// Values of `Schedule` fields which a chain spec replaces from some block on.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ScheduleOverrides {
+	/// See `Schedule::sload_gas`.
+	pub sload_gas: Option<usize>,
+	/// See `Schedule::sstore_set_gas`.
+	pub sstore_set_gas: Option<usize>,
+	/// See `Schedule::sstore_reset_gas`.
+	pub sstore_reset_gas: Option<usize>,
+	/// See `Schedule::sstore_refund_gas`.
+	pub sstore_refund_gas: Option<usize>,
+	/// See `Schedule::exp_byte_gas`.
+	pub exp_byte_gas: Option<usize>,
+	/// See `Schedule::create_gas`.
+	pub create_gas: Option<usize>,
+	/// See `Schedule::call_gas`.
+	pub call_gas: Option<usize>,
+	/// See `Schedule::sub_gas_cap_divisor`.
+	pub sub_gas_cap_divisor: Option<usize>,
+	/// See `Schedule::call_value_transfer_gas`.
+	pub call_value_transfer_gas: Option<usize>,
+	/// See `Schedule::call_new_account_gas`.
+	pub call_new_account_gas: Option<usize>,
+	/// See `Schedule::balance_gas`.
+	pub balance_gas: Option<usize>,
+	/// See `Schedule::extcodesize_gas`.
+	pub extcodesize_gas: Option<usize>,
+	/// See `Schedule::extcodecopy_base_gas`.
+	pub extcodecopy_base_gas: Option<usize>,
+	/// See `Schedule::suicide_gas`.
+	pub suicide_gas: Option<usize>,
+	/// See `Schedule::suicide_to_new_account_cost`.
+	pub suicide_to_new_account_cost: Option<usize>,
+	/// See `Schedule::suicide_refund_gas`.
+	pub suicide_refund_gas: Option<usize>,
+	/// See `Schedule::tx_data_zero_gas`.
+	pub tx_data_zero_gas: Option<usize>,
+	/// See `Schedule::tx_data_non_zero_gas`.
+	pub tx_data_non_zero_gas: Option<usize>,
+}
+
+impl ScheduleOverrides {
+	/// Replace the fields of `schedule` which are overridden.
+	pub fn apply(&self, schedule: &mut Schedule) {
+		fn set(field: &mut usize, value: Option<usize>) {
+			if let Some(value) = value {
+				*field = value;
+			}
+		}
+
+		set(&mut schedule.sload_gas, self.sload_gas);
+		set(&mut schedule.sstore_set_gas, self.sstore_set_gas);
+		set(&mut schedule.sstore_reset_gas, self.sstore_reset_gas);
+		set(&mut schedule.sstore_refund_gas, self.sstore_refund_gas);
+		set(&mut schedule.exp_byte_gas, self.exp_byte_gas);
+		set(&mut schedule.create_gas, self.create_gas);
+		set(&mut schedule.call_gas, self.call_gas);
+		set(&mut schedule.call_value_transfer_gas, self.call_value_transfer_gas);
+		set(&mut schedule.call_new_account_gas, self.call_new_account_gas);
+		set(&mut schedule.balance_gas, self.balance_gas);
+		set(&mut schedule.extcodesize_gas, self.extcodesize_gas);
+		set(&mut schedule.extcodecopy_base_gas, self.extcodecopy_base_gas);
+		set(&mut schedule.suicide_gas, self.suicide_gas);
+		set(&mut schedule.suicide_to_new_account_cost, self.suicide_to_new_account_cost);
+		set(&mut schedule.suicide_refund_gas, self.suicide_refund_gas);
+		set(&mut schedule.tx_data_zero_gas, self.tx_data_zero_gas);
+		set(&mut schedule.tx_data_non_zero_gas, self.tx_data_non_zero_gas);
+		if self.sub_gas_cap_divisor.is_some() {
+			schedule.sub_gas_cap_divisor = self.sub_gas_cap_divisor;
+		}
+	}
+}
+
+impl From<ethjson::spec::ScheduleOverrides> for ScheduleOverrides {
+	fn from(o: ethjson::spec::ScheduleOverrides) -> Self {
+		ScheduleOverrides {
+			sload_gas: o.sload_gas.map(Into::into),
+			sstore_set_gas: o.sstore_set_gas.map(Into::into),
+			sstore_reset_gas: o.sstore_reset_gas.map(Into::into),
+			sstore_refund_gas: o.sstore_refund_gas.map(Into::into),
+			exp_byte_gas: o.exp_byte_gas.map(Into::into),
+			create_gas: o.create_gas.map(Into::into),
+			call_gas: o.call_gas.map(Into::into),
+			sub_gas_cap_divisor: o.sub_gas_cap_divisor.map(Into::into),
+			call_value_transfer_gas: o.call_value_transfer_gas.map(Into::into),
+			call_new_account_gas: o.call_new_account_gas.map(Into::into),
+			balance_gas: o.balance_gas.map(Into::into),
+			extcodesize_gas: o.extcodesize_gas.map(Into::into),
+			extcodecopy_base_gas: o.extcodecopy_base_gas.map(Into::into),
+			suicide_gas: o.suicide_gas.map(Into::into),
+			suicide_to_new_account_cost: o.suicide_to_new_account_cost.map(Into::into),
+			suicide_refund_gas: o.suicide_refund_gas.map(Into::into),
+			tx_data_zero_gas: o.tx_data_zero_gas.map(Into::into),
+			tx_data_non_zero_gas: o.tx_data_non_zero_gas.map(Into::into),
+		}
+	}
+}
+
+#[test]
+fn overrides_replace_only_given_fields() {
+	let mut schedule = Schedule::new_homestead();
+	ScheduleOverrides {
+		sload_gas: Some(200),
+		sub_gas_cap_divisor: Some(64),
+		..Default::default()
+	}.apply(&mut schedule);
+	assert_eq!(schedule.sload_gas, 200);
+	assert_eq!(schedule.sub_gas_cap_divisor, Some(64));
+	assert_eq!(schedule.call_gas, 40);
+}
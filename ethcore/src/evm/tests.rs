@@ -62,6 +62,7 @@ fn test_finalize(res: Result<GasLeft, evm::Error>) -> Result<U256, evm::Error> {
 	match res {
 		Ok(GasLeft::Known(gas)) => Ok(gas),
 		Ok(GasLeft::NeedsReturn(_, _)) => unimplemented!(), // since ret is unimplemented.
+		Ok(GasLeft::NeedsRevert(gas, data)) => Err(evm::Error::Reverted { gas_left: gas, data: data.to_vec() }),
 		Err(e) => Err(e),
 	}
 }
@@ -119,7 +120,8 @@ impl Ext for FakeExt {
 			value: Option<U256>,
 			data: &[u8],
 			code_address: &Address,
-			_output: &mut [u8]) -> MessageCallResult {
+			_output: &mut [u8],
+			_is_static: bool) -> MessageCallResult {
 
 		self.calls.insert(FakeCall {
 			call_type: FakeCallType::Call,
@@ -130,7 +132,7 @@ impl Ext for FakeExt {
 			data: data.to_vec(),
 			code_address: Some(code_address.clone())
 		});
-		MessageCallResult::Success(*gas)
+		MessageCallResult::Success(*gas, vec![])
 	}
 
 	fn extcode(&self, address: &Address) -> Bytes {
@@ -181,7 +183,7 @@ fn test_stack_underflow() {
 	let mut ext = FakeExt::new();
 
 	let err = {
-		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::default());
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(Default::default())));
 		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
 	};
 
@@ -937,6 +939,130 @@ fn test_calls(factory: super::Factory) {
 	assert_eq!(ext.calls.len(), 2);
 }
 
+#[test]
+fn test_call_gas_cap() {
+	// CALL asking for 0xffffffff gas
+	let code = "60006000600060006000600063fffffffff100".from_hex().unwrap();
+
+	let address = Address::from(0x155);
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(code);
+	params.address = address.clone();
+	let mut ext = FakeExt::new();
+	ext.schedule = Schedule::new_homestead();
+	ext.schedule.call_gas = 700;
+	ext.schedule.sub_gas_cap_divisor = Some(64);
+	ext.balances.insert(address.clone(), U256::zero());
+	ext.balances.insert(Address::new(), U256::zero());
+
+	let gas_left = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(Default::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap()
+	};
+
+	// 99_279 gas remains after the call itself, all but a 64th of it is passed on
+	assert_set_contains(&ext.calls, &FakeCall {
+		call_type: FakeCallType::Call,
+		gas: U256::from(97_728),
+		sender_address: Some(address.clone()),
+		receive_address: Some(Address::new()),
+		value: Some(U256::zero()),
+		data: vec!(),
+		code_address: Some(Address::new())
+	});
+	assert_eq!(gas_left, U256::from(99_279));
+}
+
+#[test]
+fn test_revert_needs_schedule() {
+	let code = "60006000fd".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(code);
+	let mut ext = FakeExt::new();
+
+	let err = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(Default::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		evm::Error::BadInstruction { instruction } => assert_eq!(instruction, 0xfd),
+		_ => assert!(false, "Expected BadInstruction"),
+	}
+}
+
+#[test]
+fn test_revert() {
+	// store 0xff at 0 and revert with it
+	let code = "60ff60005360016000fd".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(code);
+	let mut ext = FakeExt::new();
+	ext.schedule.have_revert = true;
+
+	let err = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(Default::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		evm::Error::Reverted { gas_left, data } => {
+			assert_eq!(gas_left, U256::from(99_982));
+			assert_eq!(data, vec![0xff]);
+		}
+		_ => assert!(false, "Expected Reverted"),
+	}
+}
+
+#[test]
+fn test_return_data_copy_out_of_bounds() {
+	// copy a byte of the (empty) return data to memory
+	let code = "6001600060003e".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(code);
+	let mut ext = FakeExt::new();
+	ext.schedule.have_return_data = true;
+
+	let err = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(Default::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		evm::Error::ReturnDataOutOfBounds => {},
+		_ => assert!(false, "Expected ReturnDataOutOfBounds"),
+	}
+}
+
+#[test]
+fn test_sstore_in_static_context() {
+	let code = "6001600055".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(code);
+	params.is_static = true;
+	let mut ext = FakeExt::new();
+
+	let err = {
+		let mut vm : Box<evm::Evm> = Box::new(super::interpreter::Interpreter::<usize>::new(Arc::new(Default::default())));
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	match err {
+		evm::Error::MutableCallInStaticContext => {},
+		_ => assert!(false, "Expected MutableCallInStaticContext"),
+	}
+	assert!(ext.store.is_empty());
+}
+
 fn assert_set_contains<T : Debug + Eq + PartialEq + Hash>(set: &HashSet<T>, val: &T) {
 	let contains = set.contains(val);
 	if !contains {
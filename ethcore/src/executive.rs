@@ -21,15 +21,38 @@ use engine::*;
 use evm::{self, Ext, Factory, Finalize};
 use externalities::*;
 use substate::*;
-use trace::{Trace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer};
+use trace::{Trace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer, ProfilingVMTracer};
+use types::profile::Profile;
 use crossbeam;
-pub use types::executed::{Executed, ExecutionResult};
+pub use types::executed::{Executed, ExecutionResult, ExecutionException};
 
 /// Max depth to avoid stack overflow (when it's reached we start a new thread with VM)
 /// TODO [todr] We probably need some more sophisticated calculations here (limit on my machine 132)
 /// Maybe something like here: `https://github.com/ethereum/libethereum/blob/4db169b8504f2b87f7d5a481819cfb959fc65f6c/libethereum/ExtVM.cpp`
 const MAX_VM_DEPTH_FOR_THREAD: usize = 64;
 
+/// Storage of a builtin's account in the state.
+struct BuiltinAccount<'a> {
+	state: &'a mut State,
+	address: &'a Address,
+	/// Whether the builtin runs in a static call, which may not change the state.
+	is_static: bool,
+}
+
+impl<'a> BuiltinStorage for BuiltinAccount<'a> {
+	fn storage_at(&self, key: &H256) -> H256 {
+		self.state.storage_at(self.address, key)
+	}
+
+	fn set_storage(&mut self, key: H256, value: H256) -> Result<(), BuiltinError> {
+		if self.is_static {
+			return Err(BuiltinError("storage changed in a static call"));
+		}
+		self.state.set_storage(self.address, key, value);
+		Ok(())
+	}
+}
+
 /// Returns new address created from address and given nonce.
 pub fn contract_address(address: &Address, nonce: &U256) -> Address {
 	let mut stream = RlpStream::new_list(2);
@@ -44,6 +67,8 @@ pub struct TransactOptions {
 	pub tracing: bool,
 	/// Enable VM tracing.
 	pub vm_tracing: bool,
+	/// Enable per-instruction profiling.
+	pub profiling: bool,
 	/// Check transaction nonce before execution.
 	pub check_nonce: bool,
 }
@@ -95,15 +120,15 @@ impl<'a> Executive<'a> {
 	/// This function should be used to execute transaction.
 	pub fn transact(&'a mut self, t: &SignedTransaction, options: TransactOptions) -> Result<Executed, ExecutionError> {
 		let check = options.check_nonce;
-		match options.tracing {
-			true => match options.vm_tracing {
-				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ExecutiveVMTracer::default()),
-				false => self.transact_with_tracer(t, check, ExecutiveTracer::default(), NoopVMTracer),
-			},
-			false => match options.vm_tracing {
-				true => self.transact_with_tracer(t, check, NoopTracer, ExecutiveVMTracer::default()),
-				false => self.transact_with_tracer(t, check, NoopTracer, NoopVMTracer),
-			},
+		match (options.tracing, options.vm_tracing, options.profiling) {
+			(true, true, true) => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ProfilingVMTracer::new(ExecutiveVMTracer::default())),
+			(true, true, false) => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ExecutiveVMTracer::default()),
+			(true, false, true) => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ProfilingVMTracer::new(NoopVMTracer)),
+			(true, false, false) => self.transact_with_tracer(t, check, ExecutiveTracer::default(), NoopVMTracer),
+			(false, true, true) => self.transact_with_tracer(t, check, NoopTracer, ProfilingVMTracer::new(ExecutiveVMTracer::default())),
+			(false, true, false) => self.transact_with_tracer(t, check, NoopTracer, ExecutiveVMTracer::default()),
+			(false, false, true) => self.transact_with_tracer(t, check, NoopTracer, ProfilingVMTracer::new(NoopVMTracer)),
+			(false, false, false) => self.transact_with_tracer(t, check, NoopTracer, NoopVMTracer),
 		}
 	}
 
@@ -172,7 +197,9 @@ impl<'a> Executive<'a> {
 					gas_price: t.gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: Some(t.data.clone()),
+					code_hash: t.data.sha3(),
 					data: None,
+					is_static: false,
 				};
 				(self.create(params, &mut substate, &mut tracer, &mut vm_tracer), vec![])
 			},
@@ -186,7 +213,9 @@ impl<'a> Executive<'a> {
 					gas_price: t.gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: self.state.code(address),
+					code_hash: self.state.code_hash(address),
 					data: Some(t.data.clone()),
+					is_static: false,
 				};
 				// TODO: move output upstream
 				let mut out = vec![];
@@ -195,7 +224,8 @@ impl<'a> Executive<'a> {
 		};
 
 		// finalize here!
-		Ok(try!(self.finalize(t, substate, gas_left, output, tracer.traces().pop(), vm_tracer.drain())))
+		let profile = vm_tracer.take_profile();
+		Ok(try!(self.finalize(t, substate, gas_left, output, tracer.traces().pop(), vm_tracer.drain(), profile)))
 	}
 
 	fn exec_vm<T, V>(
@@ -245,12 +275,13 @@ impl<'a> Executive<'a> {
 		// at first, transfer value to destination
 		if let ActionValue::Transfer(val) = params.value {
 			self.state.transfer_balance(&params.sender, &params.address, &val);
+			substate.touched.insert(params.address.clone());
 		}
 		trace!("Executive::call(params={:?}) self.env_info={:?}", params, self.info);
 
 		let delegate_call = params.code_address != params.address;
 
-		if self.engine.is_builtin(&params.code_address) {
+		if self.engine.is_builtin(&params.code_address, self.info.number) {
 			// if destination is builtin, try to execute it
 
 			let default = [];
@@ -258,30 +289,48 @@ impl<'a> Executive<'a> {
 
 			let trace_info = tracer.prepare_trace_call(&params);
 
-			let cost = self.engine.cost_of_builtin(&params.code_address, data);
+			let cost = self.engine.cost_of_builtin(&params.code_address, self.info.number, data);
+			// a flexible output gets exactly as much room as the builtin produces
+			if let BytesRef::Flexible(ref mut bytes) = output {
+				let size = self.engine.output_size_of_builtin(&params.code_address, self.info.number, data);
+				bytes.clear();
+				bytes.resize(size, 0);
+			}
 			match cost <= params.gas {
-				true => {
-					self.engine.execute_builtin(&params.code_address, data, &mut output);
-					self.state.clear_snapshot();
-
-					// trace only top level calls to builtins to avoid DDoS attacks
-					if self.depth == 0 {
-						let mut trace_output = tracer.prepare_trace_output();
-						if let Some(mut out) = trace_output.as_mut() {
-							*out = output.to_owned();
+				true => match self.execute_builtin(&params.code_address, data, &mut output, params.is_static) {
+					Ok(()) => {
+						self.state.clear_snapshot();
+
+						// trace only top level calls to builtins to avoid DDoS attacks
+						if self.depth == 0 {
+							let mut trace_output = tracer.prepare_trace_output();
+							if let Some(mut out) = trace_output.as_mut() {
+								*out = output.to_owned();
+							}
+
+							tracer.trace_call(
+								trace_info,
+								cost,
+								trace_output,
+								self.depth,
+								vec![],
+								delegate_call
+							);
 						}
 
-						tracer.trace_call(
-							trace_info,
-							cost,
-							trace_output,
-							self.depth,
-							vec![],
-							delegate_call
-						);
-					}
+						Ok(params.gas - cost)
+					},
+					// builtin rejected its input: drain the whole gas
+					Err(BuiltinError(reason)) => {
+						self.state.revert_snapshot();
+
+						// as above, trace only top level calls to builtins
+						if self.depth == 0 {
+							tracer.trace_failed_builtin_call(trace_info, self.depth, reason.to_owned(), delegate_call);
+						}
 
-					Ok(params.gas - cost)
+						Err(evm::Error::BuiltIn(reason))
+					}
 				},
 				// just drain the whole gas
 				false => {
@@ -324,6 +373,14 @@ impl<'a> Executive<'a> {
 						traces,
 						delegate_call
 					),
+					Err(evm::Error::Reverted { gas_left, ref data }) => tracer.trace_reverted_call(
+						trace_info,
+						gas - gas_left,
+						trace_output.map(|_| data.clone()),
+						self.depth,
+						traces,
+						delegate_call
+					),
 					_ => tracer.trace_failed_call(trace_info, self.depth, traces, delegate_call),
 				};
 
@@ -390,6 +447,13 @@ impl<'a> Executive<'a> {
 				self.depth,
 				subtracer.traces()
 			),
+			Err(evm::Error::Reverted { gas_left, ref data }) => tracer.trace_reverted_create(
+				trace_info,
+				gas - gas_left,
+				trace_output.map(|_| data.clone()),
+				self.depth,
+				subtracer.traces()
+			),
 			_ => tracer.trace_failed_create(trace_info, self.depth, subtracer.traces())
 		};
 
@@ -401,11 +465,12 @@ impl<'a> Executive<'a> {
 	fn finalize(
 		&mut self,
 		t: &SignedTransaction,
-		substate: Substate,
+		mut substate: Substate,
 		result: evm::Result<U256>,
 		output: Bytes,
 		trace: Option<Trace>,
-		vm_trace: Option<VMTrace>
+		vm_trace: Option<VMTrace>,
+		profile: Option<Profile>
 	) -> ExecutionResult {
 		let schedule = self.engine.schedule(self.info);
 
@@ -416,7 +481,10 @@ impl<'a> Executive<'a> {
 		let refunds_bound = sstore_refunds + suicide_refunds;
 
 		// real ammount to refund
-		let gas_left_prerefund = match result { Ok(x) => x, _ => 0.into() };
+		let gas_left_prerefund = match result {
+			Ok(x) | Err(evm::Error::Reverted { gas_left: x, .. }) => x,
+			_ => 0.into(),
+		};
 		let refunded = cmp::min(refunds_bound, (t.gas - gas_left_prerefund) / U256::from(2));
 		let gas_left = gas_left_prerefund + refunded;
 
@@ -431,15 +499,43 @@ impl<'a> Executive<'a> {
 		self.state.add_balance(&t.sender().unwrap(), &refund_value);
 		trace!("exec::finalize: Compensating author: fees_value={}, author={}\n", fees_value, &self.info.author);
 		self.state.add_balance(&self.info.author, &fees_value);
+		substate.touched.insert(self.info.author.clone());
 
 		// perform suicides
 		for address in &substate.suicides {
 			self.state.kill_account(address);
 		}
 
+		// remove empty accounts touched by the transaction; builtins have no code, but may keep
+		// state such as the keys of the zkSNARK registry.
+		if schedule.kill_empty {
+			for address in &substate.touched {
+				if self.state.is_empty(address) && !self.engine.is_builtin(address, self.info.number) {
+					trace!("exec::finalize: Removing empty account {}\n", address);
+					self.state.kill_account(address);
+				}
+			}
+		}
+
 		match result {
 			Err(evm::Error::Internal) => Err(ExecutionError::Internal),
-			Err(_) => {
+			Err(evm::Error::Reverted { data, .. }) => {
+				Ok(Executed {
+					gas: t.gas,
+					gas_used: gas_used,
+					refunded: refunded,
+					cumulative_gas_used: self.info.gas_used + gas_used,
+					logs: vec![],
+					contracts_created: vec![],
+					output: data,
+					trace: trace,
+					vm_trace: vm_trace,
+					state_diff: None,
+					profile: profile,
+					exception: Some(ExecutionException::Reverted),
+				})
+			},
+			Err(e) => {
 				Ok(Executed {
 					gas: t.gas,
 					gas_used: t.gas,
@@ -451,6 +547,11 @@ impl<'a> Executive<'a> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					profile: profile,
+					exception: Some(match e {
+						evm::Error::OutOfGas => ExecutionException::OutOfGas,
+						_ => ExecutionException::Failed,
+					}),
 				})
 			},
 			_ => {
@@ -465,18 +566,29 @@ impl<'a> Executive<'a> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					profile: profile,
+					exception: None,
 				})
 			},
 		}
 	}
 
+	fn execute_builtin(&mut self, address: &Address, input: &[u8], output: &mut [u8], is_static: bool) -> Result<(), BuiltinError> {
+		let mut storage = BuiltinAccount { state: self.state, address: address, is_static: is_static };
+		self.engine.execute_builtin(address, self.info.number, input, output, &mut storage)
+	}
+
 	fn enact_result(&mut self, result: &evm::Result<U256>, substate: &mut Substate, un_substate: Substate) {
 		match *result {
 			Err(evm::Error::OutOfGas)
 				| Err(evm::Error::BadJumpDestination {..})
 				| Err(evm::Error::BadInstruction {.. })
 				| Err(evm::Error::StackUnderflow {..})
-				| Err(evm::Error::OutOfStack {..}) => {
+				| Err(evm::Error::OutOfStack {..})
+				| Err(evm::Error::BuiltIn(_))
+				| Err(evm::Error::MutableCallInStaticContext)
+				| Err(evm::Error::ReturnDataOutOfBounds)
+				| Err(evm::Error::Reverted {..}) => {
 					self.state.revert_snapshot();
 			},
 			Ok(_) | Err(evm::Error::Internal) => {
@@ -498,6 +610,9 @@ mod tests {
 	use trace::trace;
 	use trace::{Trace, Tracer, NoopTracer, ExecutiveTracer};
 	use trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, VMTracer, NoopVMTracer, ExecutiveVMTracer};
+	use types::account_diff::Diff;
+	use spec::Spec;
+	use zksnark;
 
 	#[test]
 	fn test_contract_address() {
@@ -1012,7 +1127,7 @@ mod tests {
 			gas: U256::from(100_000),
 			gas_price: U256::zero(),
 			nonce: U256::zero()
-		}.sign(&keypair.secret());
+		}.sign(&keypair.secret(), None);
 		let sender = t.sender().unwrap();
 		let contract = contract_address(&sender, &U256::zero());
 
@@ -1025,7 +1140,7 @@ mod tests {
 
 		let executed = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
 			ex.transact(&t, opts).unwrap()
 		};
 
@@ -1059,7 +1174,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1079,7 +1194,7 @@ mod tests {
 			gas: U256::from(100_000),
 			gas_price: U256::zero(),
 			nonce: U256::one()
-		}.sign(&keypair.secret());
+		}.sign(&keypair.secret(), None);
 		let sender = t.sender().unwrap();
 
 		let mut state_result = get_temp_state();
@@ -1091,7 +1206,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1112,7 +1227,7 @@ mod tests {
 			gas: U256::from(80_001),
 			gas_price: U256::zero(),
 			nonce: U256::zero()
-		}.sign(&keypair.secret());
+		}.sign(&keypair.secret(), None);
 		let sender = t.sender().unwrap();
 
 		let mut state_result = get_temp_state();
@@ -1125,7 +1240,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1147,7 +1262,7 @@ mod tests {
 			gas: U256::from(100_000),
 			gas_price: U256::one(),
 			nonce: U256::zero()
-		}.sign(&keypair.secret());
+		}.sign(&keypair.secret(), None);
 		let sender = t.sender().unwrap();
 
 		let mut state_result = get_temp_state();
@@ -1159,7 +1274,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
 			ex.transact(&t, opts)
 		};
 
@@ -1205,4 +1320,576 @@ mod tests {
 			}
 		}
 	}
+
+	/// Spec removing empty touched accounts from block 10 on, with a zkSNARK key registry at 0x10.
+	fn eip161_spec() -> Spec {
+		let registry = r#""0000000000000000000000000000000000000010": { "balance": "0", "nonce": "0", "builtin": { "name": "zkSNARK_registry", "pricing": { "zksnark_registry": { "base": 100000, "vkWord": 100, "inputWord": 100 } } } },"#;
+		let spec = include_str!("../res/null.json")
+			.replace(r#""networkID" : "0x2""#, r#""networkID" : "0x2", "eip161Transition": "0x0a""#)
+			.replace(r#""accounts": {"#, &format!(r#""accounts": {{ {}"#, registry));
+		Spec::load(spec.as_bytes()).unwrap()
+	}
+
+	#[test]
+	fn removes_empty_account_touched_by_zero_value_call_after_eip161() {
+		let spec = eip161_spec();
+		let factory = Factory::default();
+		let empty = Address::from(0x1234);
+
+		for &(number, removed) in &[(9, false), (10, true)] {
+			let keypair = KeyPair::create().unwrap();
+			let t = Transaction {
+				action: Action::Call(empty.clone()),
+				value: U256::zero(),
+				data: vec![],
+				gas: U256::from(100_000),
+				gas_price: U256::zero(),
+				nonce: U256::zero()
+			}.sign(&keypair.secret(), None);
+			let sender = t.sender().unwrap();
+
+			let mut state_result = get_temp_state();
+			let mut state = state_result.reference_mut();
+			state.add_balance(&sender, &U256::from(1));
+			state.add_balance(&empty, &U256::zero());
+			let orig = state.clone();
+			let mut info = EnvInfo::default();
+			info.number = number;
+			info.gas_limit = U256::from(100_000);
+
+			{
+				let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+				let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
+				ex.transact(&t, opts).unwrap();
+			}
+
+			assert_eq!(state.exists(&empty), !removed);
+			assert!(state.exists(&sender));
+			let diff = state.diff_from(orig);
+			match removed {
+				true => assert_eq!(diff.raw[&empty].balance, Diff::Died(U256::zero())),
+				false => assert!(!diff.raw.contains_key(&empty)),
+			}
+		}
+	}
+
+	#[test]
+	fn keeps_zksnark_registry_keys_after_eip161() {
+		let spec = eip161_spec();
+		let factory = Factory::default();
+		let registry = Address::from(0x10);
+		let keypair = KeyPair::create().unwrap();
+		let vk: Bytes = include_str!("../res/zksnark/vk.hex").from_hex().unwrap();
+		let proof: Bytes = include_str!("../res/zksnark/proof.hex").from_hex().unwrap();
+		let primary_input: Bytes = include_str!("../res/zksnark/input.hex").from_hex().unwrap();
+
+		let mut register = zksnark::selector(zksnark::REGISTER_VK).to_vec();
+		register.extend_from_slice(&H256::from(U256::from(0x20)));
+		register.extend_from_slice(&H256::from(U256::from(vk.len())));
+		register.extend_from_slice(&vk);
+
+		let mut verify = zksnark::selector(zksnark::VERIFY_REGISTERED).to_vec();
+		verify.extend_from_slice(&vk.sha3());
+		verify.extend_from_slice(&H256::from(U256::from(0x60)));
+		verify.extend_from_slice(&H256::from(U256::from(0x80 + proof.len())));
+		verify.extend_from_slice(&H256::from(U256::from(proof.len())));
+		verify.extend_from_slice(&proof);
+		verify.extend_from_slice(&H256::from(U256::from(primary_input.len())));
+		verify.extend_from_slice(&primary_input);
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.add_balance(&keypair.address(), &U256::from(1));
+		let mut info = EnvInfo::default();
+		info.number = 10;
+		info.gas_limit = U256::from(10_000_000);
+
+		for (nonce, data) in vec![register, verify].into_iter().enumerate() {
+			let t = Transaction {
+				action: Action::Call(registry.clone()),
+				value: U256::zero(),
+				data: data,
+				gas: U256::from(5_000_000),
+				gas_price: U256::zero(),
+				nonce: U256::from(nonce)
+			}.sign(&keypair.secret(), None);
+			let executed = {
+				let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+				let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
+				ex.transact(&t, opts).unwrap()
+			};
+			// verification fails with an unknown key, should the registration have been removed
+			assert_eq!(executed.exception, None);
+			assert!(state.exists(&registry));
+		}
+		assert_eq!(state.storage_at(&registry, &vk.sha3()), H256::from(U256::from(vk.len())));
+	}
+
+	/// Spec with `REVERT`, `RETURNDATASIZE`/`RETURNDATACOPY` and `STATICCALL` enabled from genesis,
+	/// with a zkSNARK key registry at 0x10.
+	fn metropolis_spec() -> Spec {
+		let registry = r#""0000000000000000000000000000000000000010": { "balance": "0", "nonce": "0", "builtin": { "name": "zkSNARK_registry", "pricing": { "zksnark_registry": { "base": 100000, "vkWord": 100, "inputWord": 100 } } } },"#;
+		let spec = include_str!("../res/null.json")
+			.replace(r#""networkID" : "0x2""#, r#""networkID" : "0x2", "eip140Transition": "0x00", "eip211Transition": "0x00", "eip214Transition": "0x00""#)
+			.replace(r#""accounts": {"#, &format!(r#""accounts": {{ {}"#, registry));
+		Spec::load(spec.as_bytes()).unwrap()
+	}
+
+	#[test]
+	fn returns_whole_output_of_top_level_builtin_call() {
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let keypair = KeyPair::create().unwrap();
+		let t = Transaction {
+			action: Action::Call(Address::from(4)),
+			value: U256::zero(),
+			data: vec![1, 2, 3],
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero()
+		}.sign(&keypair.secret(), None);
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
+			ex.transact(&t, opts).unwrap()
+		};
+
+		assert_eq!(executed.exception, None);
+		assert_eq!(executed.output, vec![1, 2, 3]);
+	}
+
+	#[test]
+	fn keeps_whole_builtin_output_as_return_data() {
+		// 64 0102030405 - push5 0102030405
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 02 - push 2 (out size)
+		// 60 40 - push 40 (out offset)
+		// 60 05 - push 5 (in size)
+		// 60 1b - push 1b (in offset)
+		// 60 00 - push 0 (value)
+		// 60 04 - push 4 (identity)
+		// 61 ffff - push ffff (gas)
+		// f1 - call
+		// 50 - pop
+		// 3d - returndatasize
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 40 - push 40
+		// 51 - mload
+		// 60 01 - push 1
+		// 55 - sstore
+		let code = "640102030405600052600260406005601b6000600461fffff1503d600055604051600155".from_hex().unwrap();
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let address = Address::from(0x1234);
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(code.clone());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code);
+		let info = EnvInfo::default();
+		let mut substate = Substate::new();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+		}
+
+		// the caller only gets the part of the output which fits, but the return data is whole
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::zero())), H256::from(&U256::from(5)));
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::one())), H256::from("0102000000000000000000000000000000000000000000000000000000000000"));
+	}
+
+	#[test]
+	fn failed_builtin_call_leaves_output_untouched_without_return_data() {
+		// 60 00 - push 0
+		// 19 - not
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 20 (out size)
+		// 60 00 - push 0 (out offset)
+		// 60 80 - push 80 (in size)
+		// 60 20 - push 20 (in offset)
+		// 60 00 - push 0 (value)
+		// 60 01 - push 1 (ecrecover)
+		// 61 ffff - push ffff (gas)
+		// f1 - call
+		// 50 - pop
+		// 60 00 - push 0
+		// 51 - mload
+		// 60 00 - push 0
+		// 55 - sstore
+		let code = "60001960005260206000608060206000600161fffff150600051600055".from_hex().unwrap();
+		let spec = eip161_spec();
+		let factory = Factory::default();
+		let address = Address::from(0x1234);
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(code.clone());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code);
+		let info = EnvInfo::default();
+		let mut substate = Substate::new();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+		}
+
+		// ecrecover cannot recover a zero signature and writes nothing, so the output area keeps its contents
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::zero())), H256::from(&!U256::zero()));
+	}
+
+	#[test]
+	fn static_call_returns_output_of_callee() {
+		// callee:
+		// 60 2a - push 2a
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 20
+		// 60 00 - push 0
+		// f3 - return
+		//
+		// caller:
+		// 60 20 - push 20 (out size)
+		// 60 00 - push 0 (out offset)
+		// 60 00 - push 0 (in size)
+		// 60 00 - push 0 (in offset)
+		// 60 0b - push 0b (callee)
+		// 61 ffff - push ffff (gas)
+		// fa - staticcall
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 00 - push 0
+		// 51 - mload
+		// 60 01 - push 1
+		// 55 - sstore
+		let callee_code = "602a60005260206000f3".from_hex().unwrap();
+		let code = "6020600060006000600b61fffffa600055600051600155".from_hex().unwrap();
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let address = Address::from(0x0a);
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(code.clone());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code);
+		state.init_code(&Address::from(0x0b), callee_code);
+		let info = EnvInfo::default();
+		let mut substate = Substate::new();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+		}
+
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::zero())), H256::from(&U256::one()));
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::one())), H256::from(&U256::from(0x2a)));
+	}
+
+	#[test]
+	fn static_flag_carries_into_nested_calls() {
+		// 0x0c, called by 0x0b with no value:
+		// 60 00 - push 0 (out size)
+		// 60 00 - push 0 (out offset)
+		// 60 00 - push 0 (in size)
+		// 60 00 - push 0 (in offset)
+		// 60 01 - push 1 (value)
+		// 60 0d - push 0d
+		// 61 ffff - push ffff (gas)
+		// f1 - call
+		// 00 - stop
+		//
+		// 0x0b returns whether its call to 0x0c succeeded:
+		// 60 00 60 00 60 00 60 00 60 00 - push 0 (out size, out offset, in size, in offset, value)
+		// 60 0c - push 0c
+		// 62 0e0000 - push 0e0000 (gas)
+		// f1 - call
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 20
+		// 60 00 - push 0
+		// f3 - return
+		//
+		// the caller calls (or static calls) 0x0b and keeps its output in storage
+		let nested_code = "60006000600060006001600d61fffff100".from_hex().unwrap();
+		let callee_code = "60006000600060006000600c620e0000f160005260206000f3".from_hex().unwrap();
+		let static_code = "6020600060006000600b620f0000fa600055600051600155".from_hex().unwrap();
+		let call_code = "60206000600060006000600b620f0000f1600055600051600155".from_hex().unwrap();
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let address = Address::from(0x0a);
+		let receiver = Address::from(0x0d);
+
+		for &(ref code, transferred) in &[(static_code, false), (call_code, true)] {
+			let mut params = ActionParams::default();
+			params.address = address.clone();
+			params.code_address = address.clone();
+			params.gas = U256::from(1_000_000);
+			params.code = Some(code.clone());
+			let mut state_result = get_temp_state();
+			let mut state = state_result.reference_mut();
+			state.init_code(&address, code.clone());
+			state.init_code(&Address::from(0x0b), callee_code.clone());
+			state.init_code(&Address::from(0x0c), nested_code.clone());
+			state.add_balance(&Address::from(0x0c), &U256::one());
+			let info = EnvInfo::default();
+			let mut substate = Substate::new();
+
+			{
+				let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+				ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+			}
+
+			// 0x0b itself succeeds either way, but under a static call the value transfer of 0x0c fails
+			assert_eq!(state.storage_at(&address, &H256::from(&U256::zero())), H256::from(&U256::one()));
+			assert_eq!(state.storage_at(&address, &H256::from(&U256::one())), H256::from(&U256::from(transferred as u8)));
+			assert_eq!(state.balance(&receiver), U256::from(transferred as u8));
+		}
+	}
+
+	#[test]
+	fn static_call_cannot_register_zksnark_key() {
+		// 36 - calldatasize
+		// 60 00 - push 0
+		// 60 00 - push 0
+		// 37 - calldatacopy
+		// 60 00 - push 0 (out size)
+		// 60 00 - push 0 (out offset)
+		// 36 - calldatasize (in size)
+		// 60 00 - push 0 (in offset)
+		// (60 00 - push 0 (value), for a call)
+		// 60 10 - push 10 (registry)
+		// 62 3fffff - push 3fffff (gas)
+		// fa / f1 - staticcall / call
+		// 60 00 - push 0
+		// 55 - sstore
+		let static_code = "366000600037600060003660006010623ffffffa600055".from_hex().unwrap();
+		let call_code = "3660006000376000600036600060006010623ffffff1600055".from_hex().unwrap();
+		let vk: Bytes = include_str!("../res/zksnark/vk.hex").from_hex().unwrap();
+		let mut register = zksnark::selector(zksnark::REGISTER_VK).to_vec();
+		register.extend_from_slice(&H256::from(U256::from(0x20)));
+		register.extend_from_slice(&H256::from(U256::from(vk.len())));
+		register.extend_from_slice(&vk);
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let address = Address::from(0x0a);
+		let registry = Address::from(0x10);
+
+		for &(ref code, registered) in &[(static_code, false), (call_code, true)] {
+			let mut params = ActionParams::default();
+			params.address = address.clone();
+			params.code_address = address.clone();
+			params.gas = U256::from(5_000_000);
+			params.code = Some(code.clone());
+			params.data = Some(register.clone());
+			let mut state_result = get_temp_state();
+			let mut state = state_result.reference_mut();
+			state.init_code(&address, code.clone());
+			let info = EnvInfo::default();
+			let mut substate = Substate::new();
+
+			{
+				let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+				ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+			}
+
+			// like `SSTORE`, a registration writing the registry's storage fails under a static call
+			assert_eq!(state.storage_at(&address, &H256::from(&U256::zero())), H256::from(&U256::from(registered as u8)));
+			let stored = match registered { true => U256::from(vk.len()), false => U256::zero() };
+			assert_eq!(state.storage_at(&registry, &vk.sha3()), H256::from(stored));
+		}
+	}
+
+	#[test]
+	fn return_data_follows_calls_and_creates() {
+		// callee (0x0b):
+		// 60 2a 60 00 52 60 20 60 00 f3 - return 2a as a word
+		//
+		// reverting init code:
+		// 61 beef - push beef
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 02 - push 2
+		// 60 1e - push 1e
+		// fd - revert
+		//
+		// caller:
+		// 60 20 60 00 60 00 60 00 60 00 - push 20 (out size), 0 (out offset, in size, in offset, value)
+		// 60 0b - push 0b
+		// 61 ffff - push ffff (gas)
+		// f1 - call
+		// 50 - pop
+		// 3d - returndatasize
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 20 - push 20 (size)
+		// 60 00 - push 0 (offset)
+		// 60 20 - push 20 (memory offset)
+		// 3e - returndatacopy
+		// 60 20 - push 20
+		// 51 - mload
+		// 60 01 - push 1
+		// 55 - sstore
+		// 6a 61beef6000526002601efd - push the reverting init code
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 0b - push 0b (size)
+		// 60 15 - push 15 (offset)
+		// 60 00 - push 0 (value)
+		// f0 - create
+		// 50 - pop
+		// 3d - returndatasize
+		// 60 02 - push 2
+		// 55 - sstore
+		// 60 00 60 00 60 00 - push 0 (size, offset, value)
+		// f0 - create
+		// 50 - pop
+		// 3d - returndatasize
+		// 60 01 - push 1
+		// 01 - add
+		// 60 03 - push 3
+		// 55 - sstore
+		let callee_code = "602a60005260206000f3".from_hex().unwrap();
+		let code = concat!(
+			"60206000600060006000600b61fffff150",
+			"3d600055",
+			"6020600060203e602051600155",
+			"6a61beef6000526002601efd600052600b60156000f050",
+			"3d600255",
+			"600060006000f050",
+			"3d600101600355"
+		).from_hex().unwrap();
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let address = Address::from(0x0a);
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.code_address = address.clone();
+		params.gas = U256::from(1_000_000);
+		params.code = Some(code.clone());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code);
+		state.init_code(&Address::from(0x0b), callee_code);
+		let info = EnvInfo::default();
+		let mut substate = Substate::new();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+		}
+
+		// the output of the call, the data of the reverted creation, and nothing after a successful creation
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::zero())), H256::from(&U256::from(0x20)));
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::one())), H256::from(&U256::from(0x2a)));
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::from(2))), H256::from(&U256::from(2)));
+		assert_eq!(state.storage_at(&address, &H256::from(&U256::from(3))), H256::from(&U256::one()));
+	}
+
+	/// Transaction to a contract which stores 1, then reverts with the word 2a. The sender pays for gas.
+	fn reverting_transaction(state: &mut State) -> (SignedTransaction, Address) {
+		// 60 01 - push 1
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 2a - push 2a
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 20
+		// 60 00 - push 0
+		// fd - revert
+		let code = "6001600055602a60005260206000fd".from_hex().unwrap();
+		let contract = Address::from(0x0a);
+		let keypair = KeyPair::create().unwrap();
+		let t = Transaction {
+			action: Action::Call(contract.clone()),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::one(),
+			nonce: U256::zero()
+		}.sign(&keypair.secret(), None);
+		state.init_code(&contract, code);
+		state.add_balance(&t.sender().unwrap(), &U256::from(100_000));
+		(t, contract)
+	}
+
+	#[test]
+	fn revert_refunds_gas_left() {
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let (t, contract) = reverting_transaction(&mut state);
+		let sender = t.sender().unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, profiling: false };
+			ex.transact(&t, opts).unwrap()
+		};
+
+		// 21000 for the transaction, 20024 for the code
+		assert_eq!(executed.exception, Some(ExecutionException::Reverted));
+		assert_eq!(executed.gas_used, U256::from(41_024));
+		assert_eq!(executed.refunded, U256::zero());
+		assert_eq!(executed.cumulative_gas_used, U256::from(41_024));
+		assert_eq!(executed.output, H256::from(&U256::from(0x2a)).to_vec());
+		assert_eq!(state.balance(&sender), U256::from(100_000 - 41_024));
+		assert_eq!(state.nonce(&sender), U256::one());
+		assert_eq!(state.storage_at(&contract, &H256::new()), H256::new());
+	}
+
+	#[test]
+	fn traces_reverted_call() {
+		let spec = metropolis_spec();
+		let factory = Factory::default();
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let (t, contract) = reverting_transaction(&mut state);
+		let sender = t.sender().unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &*spec.engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: true, vm_tracing: false, profiling: false };
+			ex.transact(&t, opts).unwrap()
+		};
+
+		let expected_trace = Trace {
+			depth: 0,
+			action: trace::Action::Call(trace::Call {
+				from: sender,
+				to: contract,
+				value: 0.into(),
+				gas: 79_000.into(),
+				input: vec![],
+			}),
+			result: trace::Res::Reverted(trace::CallResult {
+				gas_used: 20_024.into(),
+				output: H256::from(&U256::from(0x2a)).to_vec(),
+			}),
+			subs: vec![]
+		};
+		assert_eq!(executed.trace, Some(expected_trace));
+	}
 }
@@ -37,7 +37,8 @@ pub struct OriginInfo {
 	address: Address,
 	origin: Address,
 	gas_price: U256,
-	value: U256
+	value: U256,
+	is_static: bool,
 }
 
 impl OriginInfo {
@@ -49,7 +50,8 @@ impl OriginInfo {
 			gas_price: params.gas_price,
 			value: match params.value {
 				ActionValue::Transfer(val) | ActionValue::Apparent(val) => val
-			}
+			},
+			is_static: params.is_static,
 		}
 	}
 }
@@ -147,7 +149,9 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 			gas_price: self.origin_info.gas_price,
 			value: ActionValue::Transfer(*value),
 			code: Some(code.to_vec()),
+			code_hash: code.sha3(),
 			data: None,
+			is_static: false,
 		};
 
 		self.state.inc_nonce(&self.origin_info.address);
@@ -159,6 +163,7 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 				self.substate.contracts_created.push(address.clone());
 				ContractCreateResult::Created(address, gas_left)
 			},
+			Err(evm::Error::Reverted { gas_left, data }) => ContractCreateResult::Reverted(gas_left, data),
 			_ => ContractCreateResult::Failed
 		}
 	}
@@ -170,7 +175,8 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 		value: Option<U256>,
 		data: &[u8],
 		code_address: &Address,
-		output: &mut [u8]
+		output: &mut [u8],
+		is_static: bool
 	) -> MessageCallResult {
 		trace!(target: "externalities", "call");
 
@@ -183,17 +189,43 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 			gas: *gas,
 			gas_price: self.origin_info.gas_price,
 			code: self.state.code(code_address),
+			code_hash: self.state.code_hash(code_address),
 			data: Some(data.to_vec()),
+			is_static: self.origin_info.is_static || is_static,
 		};
 
 		if let Some(value) = value {
 			params.value = ActionValue::Transfer(value);
 		}
 
-		let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.vm_factory, self.depth);
+		// without return data (EIP-211), builtins write to the output as given and leave the rest of it untouched.
+		let writes_output = !self.schedule.have_return_data && self.engine.is_builtin(code_address, self.env_info.number);
+		let mut return_data = vec![];
+		let result = {
+			let mut ex = Executive::from_parent(self.state, self.env_info, self.engine, self.vm_factory, self.depth);
+			match writes_output {
+				true => ex.call(params, self.substate, BytesRef::Fixed(&mut *output), self.tracer, self.vm_tracer),
+				false => ex.call(params, self.substate, BytesRef::Flexible(&mut return_data), self.tracer, self.vm_tracer),
+			}
+		};
+
+		fn copy_output(output: &mut [u8], data: &[u8]) {
+			let len = cmp::min(output.len(), data.len());
+			(&mut output[..len]).copy_from_slice(&data[..len]);
+		}
 
-		match ex.call(params, self.substate, BytesRef::Fixed(output), self.tracer, self.vm_tracer) {
-			Ok(gas_left) => MessageCallResult::Success(gas_left),
+		match result {
+			Ok(gas_left) => {
+				match writes_output {
+					true => return_data = output.to_vec(),
+					false => copy_output(output, &return_data),
+				}
+				MessageCallResult::Success(gas_left, return_data)
+			},
+			Err(evm::Error::Reverted { gas_left, data }) => {
+				copy_output(output, &data);
+				MessageCallResult::Reverted(gas_left, data)
+			},
 			_ => MessageCallResult::Failed
 		}
 	}
@@ -263,6 +295,7 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 			self.state.transfer_balance(&address, refund_address, &balance);
 		}
 
+		self.substate.touched.insert(refund_address.clone());
 		self.substate.suicides.insert(address);
 	}
 
@@ -286,6 +319,10 @@ impl<'a, T, V> Ext for Externalities<'a, T, V> where T: 'a + Tracer, V: 'a + VMT
 		self.vm_tracer.trace_prepare_execute(pc, instruction, gas_cost)
 	}
 
+	fn trace_state(&mut self, gas: &U256, stack: &[U256], mem: &[u8]) {
+		self.vm_tracer.trace_state(gas, stack, mem)
+	}
+
 	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
 		self.vm_tracer.trace_executed(gas_used, stack_push, mem_diff, store_diff)
 	}
@@ -308,7 +345,8 @@ mod tests {
 			address: Address::zero(),
 			origin: Address::zero(),
 			gas_price: U256::zero(),
-			value: U256::zero()
+			value: U256::zero(),
+			is_static: false,
 		}
 	}
 
@@ -420,7 +458,8 @@ mod tests {
 			Some(U256::from_str("0000000000000000000000000000000000000000000000000000000000150000").unwrap()),
 			&[],
 			&Address::new(),
-			&mut output);
+			&mut output,
+			false);
 	}
 
 	#[test]
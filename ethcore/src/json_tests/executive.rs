@@ -115,14 +115,15 @@ impl<'a, T, V> Ext for TestExt<'a, T, V> where T: Tracer, V: VMTracer {
 			value: Option<U256>,
 			data: &[u8],
 			_code_address: &Address,
-			_output: &mut [u8]) -> MessageCallResult {
+			_output: &mut [u8],
+			_is_static: bool) -> MessageCallResult {
 		self.callcreates.push(CallCreate {
 			data: data.to_vec(),
 			destination: Some(receive_address.clone()),
 			gas_limit: *gas,
 			value: value.unwrap()
 		});
-		MessageCallResult::Success(*gas)
+		MessageCallResult::Success(*gas, vec![])
 	}
 
 	fn extcode(&self, address: &Address) -> Bytes  {
@@ -96,6 +96,7 @@ extern crate rayon;
 extern crate hyper;
 extern crate ethash;
 extern crate hackishlibsnarkbindings;
+extern crate bn;
 pub extern crate ethstore;
 extern crate semver;
 extern crate ethcore_ipc_nano as nanoipc;
@@ -105,6 +106,9 @@ extern crate ethcore_devtools as devtools;
 
 pub mod account_provider;
 pub mod basic_authority;
+pub mod authority_round;
+pub mod tendermint;
+pub mod validator_set;
 pub mod block;
 pub mod block_queue;
 pub mod client;
@@ -121,6 +125,8 @@ pub mod migrations;
 pub mod miner;
 pub mod snapshot;
 pub mod action_params;
+pub mod builtin;
+pub mod zksnark;
 #[macro_use] pub mod evm;
 
 mod blooms;
@@ -133,7 +139,7 @@ mod state;
 mod account;
 mod account_db;
 mod null_engine;
-mod builtin;
+mod instant_seal;
 mod substate;
 mod executive;
 mod externalities;
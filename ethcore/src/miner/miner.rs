@@ -27,6 +27,7 @@ use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptio
 use block::{ClosedBlock, IsBlock, Block};
 use error::*;
 use transaction::SignedTransaction;
+use header::Header;
 use receipt::Receipt;
 use spec::Spec;
 use engine::Engine;
@@ -291,7 +292,8 @@ impl Miner {
 		// TODO: push new uncles, too.
 		for tx in transactions {
 			let hash = tx.hash();
-			match open_block.push_transaction(tx, None) {
+			let chain_id_valid = self.engine().verify_transaction_chain_id(&tx, &open_block.block().fields().header);
+			match chain_id_valid.and_then(|_| open_block.push_transaction(tx, None).map(|_| ())) {
 				Err(Error::Execution(ExecutionError::BlockGasLimitReached { gas_limit, gas_used, .. })) => {
 					debug!(target: "miner", "Skipping adding transaction to block because of gas limit: {:?}", hash);
 					// Exit early if gas left is smaller then min_tx_gas
@@ -420,13 +422,21 @@ impl Miner {
 			balance: chain.latest_balance(a),
 		};
 
+		// transactions for another chain are turned down straight away; they could only go into the next block.
+		let mut header = Header::default();
+		header.set_number(chain.chain_info().best_block_number + 1);
+
 		transactions.into_iter()
-			.map(|tx| transaction_queue.add(tx, &fetch_account, origin))
+			.map(|tx| self.engine().verify_transaction_chain_id(&tx, &header)
+				.and_then(|_| transaction_queue.add(tx, &fetch_account, origin)))
 			.collect()
 	}
 
 	/// Are we allowed to do a non-mandatory reseal?
-	fn tx_reseal_allowed(&self) -> bool { Instant::now() > *self.next_allowed_reseal.lock() }
+	fn tx_reseal_allowed(&self) -> bool {
+		// engines sealing internally seal each transaction as soon as it arrives.
+		self.engine().seals_internally() || Instant::now() > *self.next_allowed_reseal.lock()
+	}
 }
 
 const SEALING_TIMEOUT_IN_BLOCKS : u64 = 5;
@@ -482,7 +492,7 @@ impl MinerService for Miner {
 					// give the sender a sufficient balance
 					state.add_balance(&sender, &(needed_balance - balance));
 				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, profiling: analytics.profiling, check_nonce: false };
 				let mut ret = Executive::new(&mut state, &env_info, self.engine(), chain.vm_factory()).transact(t, options);
 
 				// TODO gav move this into Executive.
@@ -604,12 +614,17 @@ impl MinerService for Miner {
 			)
 		};
 
-		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.tx_reseal_allowed() {
+		let imported = results.iter().any(|r| r.is_ok());
+		let seals_internally = self.engine().seals_internally();
+		if imported && (self.options.reseal_on_external_tx || seals_internally) && self.tx_reseal_allowed() {
 			// --------------------------------------------------------------------------
 			// | NOTE Code below requires transaction_queue and sealing_work locks.     |
 			// | Make sure to release the locks before calling that method.             |
 			// --------------------------------------------------------------------------
-			self.update_sealing(chain);
+			// engines sealing internally seal the transaction right away, even if nobody asked for work yet.
+			if !seals_internally || !self.enable_and_prepare_sealing(chain) {
+				self.update_sealing(chain);
+			}
 		}
 		results
 	}
@@ -648,7 +663,7 @@ impl MinerService for Miner {
 		// | NOTE Code below requires transaction_queue and sealing_work locks.     |
 		// | Make sure to release the locks before calling that method.             |
 		// --------------------------------------------------------------------------
-		if imported.is_ok() && self.options.reseal_on_own_tx && self.tx_reseal_allowed() {
+		if imported.is_ok() && (self.options.reseal_on_own_tx || self.engine().seals_internally()) && self.tx_reseal_allowed() {
 			// Make sure to do it after transaction is imported and lock is droped.
 			// We need to create pending block and enable sealing
 			let prepared = self.enable_and_prepare_sealing(chain);
@@ -854,8 +869,11 @@ mod tests {
 	use client::{TestBlockChainClient, EachBlockWith};
 	use client::{TransactionImportResult};
 	use types::transaction::{Transaction, Action};
+	use error::{Error, TransactionError};
 	use block::*;
 	use spec::Spec;
+	use client::{BlockChainClient, Client, ClientConfig};
+	use devtools::RandomTempPath;
 
 	#[test]
 	fn should_prepare_block_to_seal() {
@@ -923,7 +941,7 @@ mod tests {
 				gas: U256::from(100_000),
 				gas_price: U256::zero(),
 				nonce: U256::zero(),
-			}.sign(keypair.secret())
+			}.sign(keypair.secret(), None)
 		};
 
 		// when
@@ -953,7 +971,7 @@ mod tests {
 				gas: U256::from(100_000),
 				gas_price: U256::zero(),
 				nonce: U256::zero(),
-			}.sign(keypair.secret())
+			}.sign(keypair.secret(), None)
 		};
 
 		// when
@@ -968,4 +986,68 @@ mod tests {
 		// This method will let us know if pending block was created (before calling that method)
 		assert_eq!(miner.enable_and_prepare_sealing(&client), true);
 	}
+
+	#[test]
+	fn should_reject_transactions_for_another_chain() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let transaction = || {
+			let keypair = KeyPair::create().unwrap();
+			Transaction {
+				action: Action::Create,
+				value: U256::zero(),
+				data: "3331600055".from_hex().unwrap(),
+				gas: U256::from(100_000),
+				gas_price: U256::zero(),
+				nonce: U256::zero(),
+			}.sign(keypair.secret(), Some(2))
+		};
+
+		// when
+		let own = miner.import_own_transaction(&client, transaction());
+		let external = miner.import_external_transactions(&client, vec![transaction()]).pop().unwrap();
+
+		// then
+		// replay-protected transactions are not allowed on the test chain.
+		match (own, external) {
+			(Err(Error::Transaction(TransactionError::InvalidChainId(2))), Err(Error::Transaction(TransactionError::InvalidChainId(2)))) => {},
+			r => panic!("should be invalid chain id errors (got {:?})", r),
+		}
+		assert_eq!(miner.all_transactions().len(), 0);
+	}
+
+	#[test]
+	fn should_seal_external_transaction_with_internally_sealing_engine() {
+		// given
+		let dir = RandomTempPath::new();
+		let miner = Miner::new(
+			MinerOptions {
+				reseal_on_external_tx: false,
+				.. Default::default()
+			},
+			GasPricer::new_fixed(0u64.into()),
+			Spec::new_instant(),
+			None,
+		);
+		let client = Client::new(ClientConfig::default(), Spec::new_instant(), dir.as_path(), miner.clone(), IoChannel::disconnected()).unwrap();
+		let transaction = Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(&"".sha3(), None);
+
+		// when
+		let res = miner.import_external_transactions(client.deref(), vec![transaction]).pop().unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+
+		// then
+		assert_eq!(res.unwrap(), TransactionImportResult::Current);
+		assert_eq!(client.chain_info().best_block_number, 1);
+		assert_eq!(miner.all_transactions().len(), 0);
+	}
 }
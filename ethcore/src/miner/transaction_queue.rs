@@ -42,8 +42,8 @@
 //!		let t2 = Transaction { action: Action::Create, value: U256::from(100), data: "3331600055".from_hex().unwrap(),
 //!			gas: U256::from(100_000), gas_price: U256::one(), nonce: U256::from(11) };
 //!
-//!		let st1 = t1.sign(&key.secret());
-//!		let st2 = t2.sign(&key.secret());
+//!		let st1 = t1.sign(&key.secret(), None);
+//!		let st2 = t2.sign(&key.secret(), None);
 //!		let default_nonce = |_a: &Address| AccountDetails {
 //!			nonce: U256::from(10),
 //!			balance: U256::from(1_000_000),
@@ -827,7 +827,7 @@ mod test {
 
 	fn new_tx() -> SignedTransaction {
 		let keypair = KeyPair::create().unwrap();
-		new_unsigned_tx(U256::from(123)).sign(keypair.secret())
+		new_unsigned_tx(U256::from(123)).sign(keypair.secret(), None)
 	}
 
 
@@ -851,7 +851,7 @@ mod test {
 		let mut tx2 = new_unsigned_tx(nonce);
 		tx2.gas_price = U256::from(2);
 
-		(tx.sign(secret), tx2.sign(secret))
+		(tx.sign(secret, None), tx2.sign(secret, None))
 	}
 
 	fn new_txs(second_nonce: U256) -> (SignedTransaction, SignedTransaction) {
@@ -866,7 +866,7 @@ mod test {
 		let mut tx2 = new_unsigned_tx(nonce + second_nonce);
 		tx2.gas_price = tx2.gas_price + gas_price;
 
-		(tx.sign(secret), tx2.sign(secret))
+		(tx.sign(secret, None), tx2.sign(secret, None))
 	}
 
 	#[test]
@@ -1235,9 +1235,9 @@ mod test {
 		let mut txq = TransactionQueue::new();
 		let kp = KeyPair::create().unwrap();
 		let secret = kp.secret();
-		let tx = new_unsigned_tx(U256::from(123)).sign(secret);
-		let tx1 = new_unsigned_tx(U256::from(124)).sign(secret);
-		let tx2 = new_unsigned_tx(U256::from(125)).sign(secret);
+		let tx = new_unsigned_tx(U256::from(123)).sign(secret, None);
+		let tx1 = new_unsigned_tx(U256::from(124)).sign(secret, None);
+		let tx2 = new_unsigned_tx(U256::from(125)).sign(secret, None);
 
 		txq.add(tx, &default_nonce, TransactionOrigin::External).unwrap();
 		assert_eq!(txq.status().pending, 1);
@@ -1465,11 +1465,11 @@ mod test {
 		// given
 		let mut txq = TransactionQueue::new();
 		let keypair = KeyPair::create().unwrap();
-		let tx = new_unsigned_tx(U256::from(123)).sign(keypair.secret());
+		let tx = new_unsigned_tx(U256::from(123)).sign(keypair.secret(), None);
 		let tx2 = {
 			let mut tx2 = tx.deref().clone();
 			tx2.gas_price = U256::from(200);
-			tx2.sign(keypair.secret())
+			tx2.sign(keypair.secret(), None)
 		};
 
 		// when
@@ -1488,16 +1488,16 @@ mod test {
 		// given
 		let mut txq = TransactionQueue::new();
 		let keypair = KeyPair::create().unwrap();
-		let tx0 = new_unsigned_tx(U256::from(123)).sign(keypair.secret());
+		let tx0 = new_unsigned_tx(U256::from(123)).sign(keypair.secret(), None);
 		let tx1 = {
 			let mut tx1 = tx0.deref().clone();
 			tx1.nonce = U256::from(124);
-			tx1.sign(keypair.secret())
+			tx1.sign(keypair.secret(), None)
 		};
 		let tx2 = {
 			let mut tx2 = tx1.deref().clone();
 			tx2.gas_price = U256::from(200);
-			tx2.sign(keypair.secret())
+			tx2.sign(keypair.secret(), None)
 		};
 
 		// when
@@ -1650,7 +1650,7 @@ mod test {
 			let tx3 = new_unsigned_tx(nonce + 2.into());
 
 
-			(tx.sign(secret), tx2.sign(secret), tx2_2.sign(secret), tx3.sign(secret))
+			(tx.sign(secret, None), tx2.sign(secret, None), tx2_2.sign(secret, None), tx3.sign(secret, None))
 		};
 		let sender = tx1.sender().unwrap();
 		txq.add(tx1, &default_nonce, TransactionOrigin::Local).unwrap();
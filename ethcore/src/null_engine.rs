@@ -51,7 +51,9 @@ impl Engine for NullEngine {
 		&self.builtins
 	}
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_homestead()
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_homestead();
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 }
@@ -119,15 +119,20 @@ struct ClientIoHandler {
 
 const CLIENT_TICK_TIMER: TimerToken = 0;
 const CLIENT_TICK_MS: u64 = 5000;
+const ENGINE_TICK_TIMER: TimerToken = 1;
+const ENGINE_TICK_MS: u64 = 100;
 
 impl IoHandler<ClientIoMessage> for ClientIoHandler {
 	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
 		io.register_timer(CLIENT_TICK_TIMER, CLIENT_TICK_MS).expect("Error registering client timer");
+		io.register_timer(ENGINE_TICK_TIMER, ENGINE_TICK_MS).expect("Error registering engine timer");
 	}
 
 	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
-		if timer == CLIENT_TICK_TIMER {
-			self.client.tick();
+		match timer {
+			CLIENT_TICK_TIMER => self.client.tick(),
+			ENGINE_TICK_TIMER => self.client.engine().tick(),
+			_ => {},
 		}
 	}
 
@@ -20,11 +20,15 @@ use common::*;
 use engine::*;
 use pod_state::*;
 use null_engine::*;
+use instant_seal::InstantSeal;
 use account_db::*;
 use super::genesis::Genesis;
 use super::seal::Generic as GenericSeal;
 use ethereum;
 use basic_authority::BasicAuthority;
+use authority_round::AuthorityRound;
+use tendermint::Tendermint;
+use evm::{Schedule, ScheduleOverrides};
 use ethjson;
 
 /// Parameters common to all engines.
@@ -36,8 +40,35 @@ pub struct CommonParams {
 	pub maximum_extra_data_size: usize,
 	/// Network id.
 	pub network_id: U256,
+	/// Chain id for replay-protected transactions (EIP-155).
+	pub chain_id: u64,
+	/// Block from which replay-protected transactions are allowed.
+	pub eip155_transition: BlockNumber,
+	/// Block from which empty accounts touched by a transaction are removed (EIP-161).
+	pub eip161_transition: BlockNumber,
+	/// Block from which the `REVERT` instruction is enabled (EIP-140).
+	pub eip140_transition: BlockNumber,
+	/// Block from which the `RETURNDATASIZE` and `RETURNDATACOPY` instructions are enabled (EIP-211).
+	pub eip211_transition: BlockNumber,
+	/// Block from which the `STATICCALL` instruction is enabled (EIP-214).
+	pub eip214_transition: BlockNumber,
 	/// Minimum gas limit.
 	pub min_gas_limit: U256,
+	/// EVM schedule overrides, keyed by the block number from which they apply.
+	pub schedule_transitions: BTreeMap<BlockNumber, ScheduleOverrides>,
+}
+
+impl CommonParams {
+	/// Apply to `schedule` the rule changes and the overrides of all transitions up to block `number`, in order.
+	pub fn update_schedule(&self, schedule: &mut Schedule, number: BlockNumber) {
+		schedule.kill_empty = number >= self.eip161_transition;
+		schedule.have_revert = number >= self.eip140_transition;
+		schedule.have_return_data = number >= self.eip211_transition;
+		schedule.have_static_call = number >= self.eip214_transition;
+		for (_, overrides) in self.schedule_transitions.iter().take_while(|&(transition, _)| *transition <= number) {
+			overrides.apply(schedule);
+		}
+	}
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -46,7 +77,14 @@ impl From<ethjson::spec::Params> for CommonParams {
 			account_start_nonce: p.account_start_nonce.into(),
 			maximum_extra_data_size: p.maximum_extra_data_size.into(),
 			network_id: p.network_id.into(),
+			chain_id: p.chain_id.unwrap_or(p.network_id).into(),
+			eip155_transition: p.eip155_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip161_transition: p.eip161_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip140_transition: p.eip140_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip211_transition: p.eip211_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip214_transition: p.eip214_transition.map_or(BlockNumber::max_value(), Into::into),
 			min_gas_limit: p.min_gas_limit.into(),
+			schedule_transitions: p.schedule_transitions.map_or_else(BTreeMap::new, |transitions| transitions.into_iter().map(|(number, overrides)| (number.into(), overrides.into())).collect()),
 		}
 	}
 }
@@ -95,13 +133,21 @@ pub struct Spec {
 	genesis_state: PodState,
 }
 
-impl From<ethjson::spec::Spec> for Spec {
-	fn from(s: ethjson::spec::Spec) -> Self {
-		let builtins = s.accounts.builtins().into_iter().map(|p| (p.0.into(), From::from(p.1))).collect();
+impl Spec {
+	/// Create a new Spec from its JSON description.
+	/// Fails if the description refers to a builtin which has not been registered.
+	pub fn from_json(s: ethjson::spec::Spec) -> Result<Spec, Error> {
+		let mut builtins = BTreeMap::new();
+		for (address, builtin) in s.accounts.builtins().into_iter() {
+			builtins.insert(address.into(), try!(Builtin::from_json(builtin)));
+		}
 		let g = Genesis::from(s.genesis);
 		let seal: GenericSeal = g.seal.into();
 		let params = CommonParams::from(s.params);
-		Spec {
+		if params.chain_id > MAX_CHAIN_ID {
+			return Err(TransactionError::InvalidChainId(params.chain_id).into());
+		}
+		Ok(Spec {
 			name: s.name.into(),
 			params: params.clone(),
 			engine: Spec::engine(s.engine, params, builtins),
@@ -119,18 +165,19 @@ impl From<ethjson::spec::Spec> for Spec {
 			seal_rlp: seal.rlp,
 			state_root_memo: RwLock::new(g.state_root),
 			genesis_state: From::from(s.accounts)
-		}
+		})
 	}
-}
 
-impl Spec {
 	/// Convert engine spec into a boxed Engine of the right underlying type.
 	/// TODO avoid this hard-coded nastiness - use dynamic-linked plugin framework instead.
 	fn engine(engine_spec: ethjson::spec::Engine, params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Box<Engine> {
 		match engine_spec {
 			ethjson::spec::Engine::Null => Box::new(NullEngine::new(params, builtins)),
+			ethjson::spec::Engine::InstantSeal => Box::new(InstantSeal::new(params, builtins)),
 			ethjson::spec::Engine::Ethash(ethash) => Box::new(ethereum::Ethash::new(params, From::from(ethash.params), builtins)),
 			ethjson::spec::Engine::BasicAuthority(basic_authority) => Box::new(BasicAuthority::new(params, From::from(basic_authority.params), builtins)),
+			ethjson::spec::Engine::AuthorityRound(authority_round) => Box::new(AuthorityRound::new(params, From::from(authority_round.params), builtins)),
+			ethjson::spec::Engine::Tendermint(tendermint) => Box::new(Tendermint::new(params, From::from(tendermint.params), builtins)),
 		}
 	}
 
@@ -236,18 +283,25 @@ impl Spec {
 	}
 
 	/// Loads spec from json file.
-	pub fn load(reader: &[u8]) -> Self {
-		From::from(ethjson::spec::Spec::load(reader).expect("invalid json file"))
+	pub fn load(reader: &[u8]) -> Result<Self, String> {
+		ethjson::spec::Spec::load(reader)
+			.map_err(|e| format!("Invalid json file: {}", e))
+			.and_then(|s| Spec::from_json(s).map_err(|e| format!("Invalid chain spec: {}", e)))
 	}
 
 	/// Create a new Spec which conforms to the Frontier-era Morden chain except that it's a NullEngine consensus.
 	pub fn new_test() -> Spec {
-		Spec::load(include_bytes!("../../res/null_morden.json"))
+		Spec::load(include_bytes!("../../res/null_morden.json")).expect("null_morden.json is a valid spec; qed")
 	}
 
 	/// Create a new Spec which is a NullEngine consensus with a premine of address whose secret is sha3('').
 	pub fn new_null() -> Spec {
-		Spec::load(include_bytes!("../../res/null.json"))
+		Spec::load(include_bytes!("../../res/null.json")).expect("null.json is a valid spec; qed")
+	}
+
+	/// Create a new Spec for development which is an InstantSeal consensus with a premine of address whose secret is sha3('').
+	pub fn new_instant() -> Spec {
+		Spec::load(include_bytes!("../../res/instant_seal.json")).expect("instant_seal.json is a valid spec; qed")
 	}
 }
 
@@ -257,6 +311,7 @@ mod tests {
 	use util::hash::*;
 	use util::sha3::*;
 	use views::*;
+	use transaction::MAX_CHAIN_ID;
 	use super::*;
 
 	#[test]
@@ -267,4 +322,12 @@ mod tests {
 		let genesis = test_spec.genesis_block();
 		assert_eq!(BlockView::new(&genesis).header_view().sha3(), H256::from_str("0cd786a2425d16f152c658316c423e6ce1181e15c3295826d7c9904cba9ce303").unwrap());
 	}
+
+	#[test]
+	fn rejects_too_large_chain_id() {
+		let spec = |chain_id: &str| Spec::load(include_str!("../../res/null.json")
+			.replace(r#""networkID" : "0x2""#, &format!(r#""networkID" : "0x2", "chainID": "{}""#, chain_id)).as_bytes());
+		assert_eq!(spec("0x7fffffffffffffed").unwrap().params.chain_id, MAX_CHAIN_ID);
+		assert!(spec("0x7fffffffffffffee").is_err());
+	}
 }
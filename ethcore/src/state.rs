@@ -165,6 +165,11 @@ impl State {
 		self.cache.borrow().get(&a).unwrap_or(&None).is_some() || db.contains(&a)
 	}
 
+	/// Determine whether account `a` exists and is empty, so it may be removed once touched (EIP-161).
+	pub fn is_empty(&self, a: &Address) -> bool {
+		self.get(a, false).as_ref().map_or(false, |account| account.is_empty(&self.account_start_nonce))
+	}
+
 	/// Get the balance of account `a`.
 	pub fn balance(&self, a: &Address) -> U256 {
 		self.get(a, false).as_ref().map_or(U256::zero(), |account| *account.balance())
@@ -185,6 +190,12 @@ impl State {
 		self.get(a, true).as_ref().map_or(None, |a|a.code().map(|x|x.to_vec()))
 	}
 
+	/// Get the hash of the code of account `a`. `SHA3_EMPTY` if it has no code or its code
+	/// has not been committed yet.
+	pub fn code_hash(&self, a: &Address) -> H256 {
+		self.get(a, false).as_ref().map_or(SHA3_EMPTY, |a| a.code_hash())
+	}
+
 	/// Add `incr` to the balance of account `a`.
 	pub fn add_balance(&mut self, a: &Address, incr: &U256) {
 		trace!(target: "state", "add_balance({}, {}): {}", a, incr, self.balance(a));
@@ -229,7 +240,7 @@ impl State {
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, vm_factory: &EvmFactory, t: &SignedTransaction, tracing: bool) -> ApplyResult {
 //		let old = self.to_pod();
 
-		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true };
+		let options = TransactOptions { tracing: tracing, vm_tracing: false, profiling: false, check_nonce: true };
 		let e = try!(Executive::new(self, env_info, engine, vm_factory).transact(t, options));
 
 		// TODO uncomment once to_pod() works correctly.
@@ -304,22 +315,28 @@ impl State {
 		}))
 	}
 
-	fn query_pod(&mut self, query: &PodState) {
-		for (ref address, ref pod_account) in query.get() {
+	/// Pull the accounts at `addresses` into the cache, along with the storage `query` has for them.
+	fn query_pod(&mut self, query: &PodState, addresses: &[Address]) {
+		let pod = query.get();
+		for address in addresses {
 			if self.get(address, true).is_some() {
-				for key in pod_account.storage.keys() {
-					self.storage_at(address, key);
+				if let Some(pod_account) = pod.get(address) {
+					for key in pod_account.storage.keys() {
+						self.storage_at(address, key);
+					}
 				}
 			}
 		}
 	}
 
-	/// Returns a `StateDiff` describing the difference from `orig` to `self`.
+	/// Returns a `StateDiff` describing the difference from `orig` to `self`,
+	/// including the accounts which were removed.
 	/// Consumes self.
 	pub fn diff_from(&self, orig: State) -> StateDiff {
+		let addresses_post: Vec<Address> = self.cache.borrow().keys().cloned().collect();
 		let pod_state_post = self.to_pod();
 		let mut state_pre = orig;
-		state_pre.query_pod(&pod_state_post);
+		state_pre.query_pod(&pod_state_post, &addresses_post);
 		pod_state::diff_pod(&state_pre.to_pod(), &pod_state_post)
 	}
 
@@ -422,7 +439,7 @@ fn should_apply_create_transaction() {
 		action: Action::Create,
 		value: 100.into(),
 		data: FromHex::from_hex("601080600c6000396000f3006000355415600957005b60203560003555").unwrap(),
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
 	let vm_factory = Default::default();
@@ -483,7 +500,7 @@ fn should_trace_failed_create_transaction() {
 		action: Action::Create,
 		value: 100.into(),
 		data: FromHex::from_hex("5b600056").unwrap(),
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
 	let vm_factory = Default::default();
@@ -521,7 +538,7 @@ fn should_trace_call_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("6000").unwrap());
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
@@ -564,7 +581,7 @@ fn should_trace_basic_call_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
 	let vm_factory = Default::default();
@@ -606,7 +623,7 @@ fn should_trace_call_transaction_to_builtin() {
 		action: Action::Call(0x1.into()),
 		value: 0.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	let vm_factory = Default::default();
 	let result = state.apply(&info, engine.deref(), &vm_factory, &t, true).unwrap();
@@ -628,6 +645,49 @@ fn should_trace_call_transaction_to_builtin() {
 	}));
 }
 
+#[test]
+fn should_trace_failed_call_transaction_to_builtin() {
+	use builtin::Builtin;
+	use null_engine::NullEngine;
+
+	init_log();
+
+	let temp = RandomTempPath::new();
+	let mut state = get_temp_state_in(temp.as_path());
+
+	let mut info = EnvInfo::default();
+	info.gas_limit = 1_000_000.into();
+	let mut builtins = BTreeMap::new();
+	builtins.insert(0x10.into(), Builtin::from_named_linear("zkSNARK", 1000, 0).unwrap());
+	let engine = NullEngine::new(Spec::new_test().params, builtins);
+
+	let t = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100_000.into(),
+		action: Action::Call(0x10.into()),
+		value: 0.into(),
+		data: vec![0, 1],
+	}.sign(&"".sha3(), None);
+
+	let vm_factory = Default::default();
+	let result = state.apply(&info, &engine, &vm_factory, &t, true).unwrap();
+
+	assert_eq!(result.receipt.gas_used, U256::from(100_000));
+	assert_eq!(result.trace, Some(Trace {
+		depth: 0,
+		action: trace::Action::Call(trace::Call {
+			from: "9cce34f7ab185c7aba1b7c8140d620b4bda941d6".into(),
+			to: "0000000000000000000000000000000000000010".into(),
+			value: 0.into(),
+			gas: 78_928.into(),
+			input: vec![0, 1],
+		}),
+		result: trace::Res::FailedBuiltinCall("input too short".to_owned()),
+		subs: vec![]
+	}));
+}
+
 #[test]
 fn should_not_trace_subcall_transaction_to_builtin() {
 	init_log();
@@ -646,7 +706,7 @@ fn should_not_trace_subcall_transaction_to_builtin() {
 		action: Action::Call(0xa.into()),
 		value: 0.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("600060006000600060006001610be0f1").unwrap());
 	let vm_factory = Default::default();
@@ -670,6 +730,41 @@ fn should_not_trace_subcall_transaction_to_builtin() {
 	assert_eq!(result.trace, expected_trace);
 }
 
+#[test]
+fn should_not_trace_failed_subcall_to_builtin() {
+	use builtin::Builtin;
+	use null_engine::NullEngine;
+
+	init_log();
+
+	let temp = RandomTempPath::new();
+	let mut state = get_temp_state_in(temp.as_path());
+
+	let mut info = EnvInfo::default();
+	info.gas_limit = 1_000_000.into();
+	let mut builtins = BTreeMap::new();
+	builtins.insert(0x10.into(), Builtin::from_named_linear("zkSNARK", 1000, 0).unwrap());
+	let engine = NullEngine::new(Spec::new_test().params, builtins);
+
+	let t = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100_000.into(),
+		action: Action::Call(0xa.into()),
+		value: 0.into(),
+		data: vec![],
+	}.sign(&"".sha3(), None);
+
+	// call the builtin with empty input, which it rejects
+	state.init_code(&0xa.into(), FromHex::from_hex("600060006000600060006010610be0f1").unwrap());
+	let vm_factory = Default::default();
+	let result = state.apply(&info, &engine, &vm_factory, &t, true).unwrap();
+
+	let trace = result.trace.unwrap();
+	assert_eq!(trace.depth, 0);
+	assert!(trace.subs.is_empty());
+}
+
 #[test]
 fn should_not_trace_callcode() {
 	init_log();
@@ -688,7 +783,7 @@ fn should_not_trace_callcode() {
 		action: Action::Call(0xa.into()),
 		value: 0.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("60006000600060006000600b611000f2").unwrap());
 	state.init_code(&0xb.into(), FromHex::from_hex("6000").unwrap());
@@ -734,7 +829,7 @@ fn should_not_trace_delegatecall() {
 		action: Action::Call(0xa.into()),
 		value: 0.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("6000600060006000600b618000f4").unwrap());
 	state.init_code(&0xb.into(), FromHex::from_hex("6000").unwrap());
@@ -777,7 +872,7 @@ fn should_trace_failed_call_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("5b600056").unwrap());
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
@@ -819,7 +914,7 @@ fn should_trace_call_with_subcall_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("60006000600060006000600b602b5a03f1").unwrap());
 	state.init_code(&0xb.into(), FromHex::from_hex("6000").unwrap());
@@ -877,7 +972,7 @@ fn should_trace_call_with_basic_subcall_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("60006000600060006045600b6000f1").unwrap());
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
@@ -931,7 +1026,7 @@ fn should_not_trace_call_with_invalid_basic_subcall_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("600060006000600060ff600b6000f1").unwrap());	// not enough funds.
 	state.add_balance(t.sender().as_ref().unwrap(), &(100.into()));
@@ -974,7 +1069,7 @@ fn should_trace_failed_subcall_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],//600480600b6000396000f35b600056
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("60006000600060006000600b602b5a03f1").unwrap());
 	state.init_code(&0xb.into(), FromHex::from_hex("5b600056").unwrap());
@@ -1029,7 +1124,7 @@ fn should_trace_call_with_subcall_with_subcall_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("60006000600060006000600b602b5a03f1").unwrap());
 	state.init_code(&0xb.into(), FromHex::from_hex("60006000600060006000600c602b5a03f1").unwrap());
@@ -1102,7 +1197,7 @@ fn should_trace_failed_subcall_with_subcall_transaction() {
 		action: Action::Call(0xa.into()),
 		value: 100.into(),
 		data: vec![],//600480600b6000396000f35b600056
-	}.sign(&"".sha3());
+	}.sign(&"".sha3(), None);
 
 	state.init_code(&0xa.into(), FromHex::from_hex("60006000600060006000600b602b5a03f1").unwrap());
 	state.init_code(&0xb.into(), FromHex::from_hex("60006000600060006000600c602b5a03f1505b601256").unwrap());
@@ -1248,6 +1343,36 @@ fn remove_from_database() {
 	assert_eq!(state.nonce(&a), U256::from(0u64));
 }
 
+#[test]
+fn empty_account() {
+	let a = Address::zero();
+	let mut state_result = get_temp_state();
+	let mut state = state_result.reference_mut();
+	assert_eq!(state.is_empty(&a), false);
+	state.add_balance(&a, &U256::zero());
+	assert_eq!(state.is_empty(&a), true);
+	state.inc_nonce(&a);
+	assert_eq!(state.is_empty(&a), false);
+}
+
+#[test]
+fn diff_includes_killed_account() {
+	let a = Address::zero();
+	let temp = RandomTempPath::new();
+	let (root, db) = {
+		let mut state = get_temp_state_in(temp.as_path());
+		state.add_balance(&a, &U256::from(69u64));
+		state.commit();
+		state.drop()
+	};
+
+	let orig = State::from_existing(db.boxed_clone(), root, U256::from(0u8), Default::default()).unwrap();
+	let mut state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+	state.kill_account(&a);
+	let diff = state.diff_from(orig);
+	assert_eq!(diff.raw[&a].balance, ::types::account_diff::Diff::Died(U256::from(69u64)));
+}
+
 #[test]
 fn alter_balance() {
 	let mut state_result = get_temp_state();
@@ -34,6 +34,10 @@ pub struct Substate {
 
 	/// Created contracts.
 	pub contracts_created: Vec<Address>,
+
+	/// Accounts which received a value transfer, even of zero; removed at the end of the
+	/// transaction if they are empty and the schedule says so (EIP-161).
+	pub touched: HashSet<Address>,
 }
 
 impl Substate {
@@ -48,6 +52,7 @@ impl Substate {
 		self.logs.extend(s.logs.into_iter());
 		self.sstore_clears_count = self.sstore_clears_count + s.sstore_clears_count;
 		self.contracts_created.extend(s.contracts_created.into_iter());
+		self.touched.extend(s.touched.into_iter());
 	}
 }
 
@@ -73,6 +78,7 @@ mod tests {
 		});
 		sub_state.sstore_clears_count = 5.into();
 		sub_state.suicides.insert(10u64.into());
+		sub_state.touched.insert(10u64.into());
 
 		let mut sub_state_2 = Substate::new();
 		sub_state_2.contracts_created.push(2u64.into());
@@ -82,10 +88,12 @@ mod tests {
 			data: vec![]
 		});
 		sub_state_2.sstore_clears_count = 7.into();
+		sub_state_2.touched.insert(11u64.into());
 
 		sub_state.accrue(sub_state_2);
 		assert_eq!(sub_state.contracts_created.len(), 2);
 		assert_eq!(sub_state.sstore_clears_count, 12.into());
 		assert_eq!(sub_state.suicides.len(), 1);
+		assert_eq!(sub_state.touched.len(), 2);
 	}
 }
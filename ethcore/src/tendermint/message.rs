@@ -0,0 +1,172 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tendermint consensus messages.
+
+use common::*;
+
+/// Height of the block being decided.
+pub type Height = BlockNumber;
+/// Round of voting at a height.
+pub type Round = u64;
+
+/// Step of a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Step {
+	/// The proposer of the round proposes a block.
+	Propose,
+	/// Validators vote for the proposal or for nil.
+	Prevote,
+	/// Validators commit to the block which got enough prevotes, or to nil.
+	Precommit,
+	/// Enough precommits are collected and the block awaits import.
+	Commit,
+}
+
+impl Step {
+	fn number(&self) -> u8 {
+		match *self {
+			Step::Propose => 0,
+			Step::Prevote => 1,
+			Step::Precommit => 2,
+			Step::Commit => 3,
+		}
+	}
+}
+
+impl Encodable for Step {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.append(&self.number());
+	}
+}
+
+impl Decodable for Step {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		match try!(decoder.as_rlp().as_val::<u8>()) {
+			0 => Ok(Step::Propose),
+			1 => Ok(Step::Prevote),
+			2 => Ok(Step::Precommit),
+			_ => Err(DecoderError::Custom("Invalid step.")),
+		}
+	}
+}
+
+/// Hash signed by a consensus message: of the vote for `block_hash`, or for nil if `None`.
+pub fn vote_hash(height: Height, round: Round, step: Step, block_hash: &Option<H256>) -> H256 {
+	let mut s = RlpStream::new_list(4);
+	s.append(&height).append(&round).append(&step);
+	match *block_hash {
+		Some(ref hash) => s.append(hash),
+		None => s.append_empty_data(),
+	};
+	s.out().sha3()
+}
+
+/// A signed proposal or vote.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConsensusMessage {
+	/// Signature of the vote hash.
+	pub signature: H520,
+	/// Height the message is for.
+	pub height: Height,
+	/// Round the message is for.
+	pub round: Round,
+	/// Step the message is for.
+	pub step: Step,
+	/// Proposed or voted for block, by its bare hash; `None` for a vote for nil.
+	pub block_hash: Option<H256>,
+	/// RLP of the proposed block without its seal; empty for votes.
+	pub block: Bytes,
+}
+
+impl ConsensusMessage {
+	/// Hash signed by this message.
+	pub fn vote_hash(&self) -> H256 {
+		vote_hash(self.height, self.round, self.step, &self.block_hash)
+	}
+
+	/// Recover the account which signed this message.
+	pub fn signer(&self) -> Result<Address, Error> {
+		Ok(Address::from(try!(ec::recover(&self.signature, &self.vote_hash())).sha3()))
+	}
+}
+
+impl Encodable for ConsensusMessage {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(6);
+		s.append(&self.signature).append(&self.height).append(&self.round).append(&self.step);
+		match self.block_hash {
+			Some(ref hash) => s.append(hash),
+			None => s.append_empty_data(),
+		};
+		s.append(&self.block);
+	}
+}
+
+impl Decodable for ConsensusMessage {
+	fn decode<D>(decoder: &D) -> Result<Self, DecoderError> where D: Decoder {
+		let rlp = decoder.as_rlp();
+		if rlp.item_count() != 6 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		let block_hash = try!(rlp.at(4));
+		Ok(ConsensusMessage {
+			signature: try!(rlp.val_at(0)),
+			height: try!(rlp.val_at(1)),
+			round: try!(rlp.val_at(2)),
+			step: try!(rlp.val_at(3)),
+			block_hash: match block_hash.is_empty() {
+				true => None,
+				false => Some(try!(block_hash.as_val())),
+			},
+			block: try!(rlp.val_at(5)),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use super::*;
+
+	#[test]
+	fn encodes_and_recovers_messages() {
+		let keypair = KeyPair::from_secret("1".sha3()).unwrap();
+		let hash = vote_hash(3, 1, Step::Prevote, &Some(H256::from(7)));
+		let message = ConsensusMessage {
+			signature: ec::sign(keypair.secret(), &hash).unwrap(),
+			height: 3,
+			round: 1,
+			step: Step::Prevote,
+			block_hash: Some(H256::from(7)),
+			block: Vec::new(),
+		};
+
+		let decoded: ConsensusMessage = decode(&encode(&message));
+		assert_eq!(decoded, message);
+		assert_eq!(decoded.signer().unwrap(), keypair.address());
+
+		let nil = ConsensusMessage { block_hash: None, ..message.clone() };
+		let decoded: ConsensusMessage = decode(&encode(&nil));
+		assert_eq!(decoded, nil);
+		assert!(decoded.signer().unwrap() != keypair.address());
+
+		// proposals carry their block
+		let proposal = ConsensusMessage { step: Step::Propose, block: vec![0xc0], ..message.clone() };
+		let decoded: ConsensusMessage = decode(&encode(&proposal));
+		assert_eq!(decoded, proposal);
+	}
+}
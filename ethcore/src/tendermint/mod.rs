@@ -0,0 +1,928 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A BFT proof-of-authority engine with instant finality, after Tendermint.
+//!
+//! At each height the validators go through rounds of propose, prevote and precommit steps,
+//! exchanging signed consensus messages over the network. A block is sealed with the signature
+//! of the proposer and the precommits of more than two thirds of the validators, so it is final
+//! as soon as it is imported. The proposer's miner has to be able to prepare blocks for sealing.
+
+mod message;
+
+use common::*;
+use spec::{CommonParams, Spec};
+use engine::*;
+use evm::Schedule;
+use ethjson;
+use account_provider::AccountProvider;
+use client::{Client, BlockChainClient, BlockID};
+use block::Block;
+use validator_set::{ValidatorSet, new_validator_set};
+use std::sync::Weak;
+use std::time::{Duration, Instant};
+
+pub use self::message::{ConsensusMessage, Step, Height, Round, vote_hash};
+
+/// Durations of the steps of a round, after which a validator moves on without a decision.
+pub struct TendermintTimeouts {
+	/// Time to wait for the proposal.
+	pub propose: Duration,
+	/// Time to wait for prevotes to reach a decision.
+	pub prevote: Duration,
+	/// Time to wait for precommits to reach a decision.
+	pub precommit: Duration,
+	/// Time to wait for the committed block before trying another round.
+	pub commit: Duration,
+}
+
+impl TendermintTimeouts {
+	fn for_step(&self, step: Step) -> Duration {
+		match step {
+			Step::Propose => self.propose,
+			Step::Prevote => self.prevote,
+			Step::Precommit => self.precommit,
+			Step::Commit => self.commit,
+		}
+	}
+}
+
+/// `Tendermint` params.
+pub struct TendermintParams {
+	/// Gas limit divisor.
+	pub gas_limit_bound_divisor: U256,
+	/// Valid validators, in the order in which they propose.
+	pub validators: Box<ValidatorSet>,
+	/// Step timeouts.
+	pub timeouts: TendermintTimeouts,
+}
+
+fn to_duration(ms: Option<ethjson::uint::Uint>, default_ms: u64) -> Duration {
+	Duration::from_millis(ms.map_or(default_ms, Into::into))
+}
+
+impl From<ethjson::spec::TendermintParams> for TendermintParams {
+	fn from(p: ethjson::spec::TendermintParams) -> Self {
+		TendermintParams {
+			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
+			validators: new_validator_set(p.validators),
+			timeouts: TendermintTimeouts {
+				propose: to_duration(p.timeout_propose, 3000),
+				prevote: to_duration(p.timeout_prevote, 1000),
+				precommit: to_duration(p.timeout_precommit, 1000),
+				commit: to_duration(p.timeout_commit, 1000),
+			},
+		}
+	}
+}
+
+/// Number of messages for the next height kept until the best block catches up.
+const MAX_FUTURE_MESSAGES: usize = 1024;
+
+/// Whether `votes` out of `validators` are more than two thirds.
+fn is_supermajority(votes: usize, validators: usize) -> bool {
+	votes * 3 > validators * 2
+}
+
+/// Progress in deciding the block at the current height.
+struct RoundState {
+	height: Height,
+	/// Best block the height is on top of.
+	parent: H256,
+	round: Round,
+	step: Step,
+	/// When the current step times out.
+	deadline: Instant,
+	/// Proposals by round, with whether their block is valid.
+	proposals: HashMap<Round, (ConsensusMessage, bool)>,
+	/// Proposal of the block this validator precommitted to, which it keeps proposing and prevoting for in later rounds.
+	lock: Option<ConsensusMessage>,
+	/// Votes at this height, by round and step and then by signer.
+	votes: HashMap<(Round, Step), HashMap<Address, ConsensusMessage>>,
+	/// Messages for the next height, received before its parent was imported.
+	future: Vec<Bytes>,
+}
+
+impl RoundState {
+	fn new(height: Height, parent: H256, deadline: Instant) -> Self {
+		RoundState {
+			height: height,
+			parent: parent,
+			round: 0,
+			step: Step::Propose,
+			deadline: deadline,
+			proposals: HashMap::new(),
+			lock: None,
+			votes: HashMap::new(),
+			future: Vec::new(),
+		}
+	}
+
+	fn lock_hash(&self) -> Option<H256> {
+		self.lock.as_ref().and_then(|lock| lock.block_hash.clone())
+	}
+}
+
+/// What to do with the client once the round state is released.
+#[derive(Default)]
+struct Outbox {
+	/// Consensus messages to broadcast.
+	messages: Vec<Bytes>,
+	/// Committed block with its seal, to import.
+	block: Option<Bytes>,
+}
+
+/// Engine using `Tendermint` BFT consensus among the validators.
+pub struct Tendermint {
+	params: CommonParams,
+	our_params: TendermintParams,
+	builtins: BTreeMap<Address, Builtin>,
+	client: RwLock<Option<Weak<Client>>>,
+	/// Account used to sign consensus messages.
+	signer: RwLock<Option<(Arc<AccountProvider>, Address)>>,
+	state: Mutex<RoundState>,
+}
+
+impl Tendermint {
+	/// Create a new instance of Tendermint engine
+	pub fn new(params: CommonParams, our_params: TendermintParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		let deadline = Instant::now() + our_params.timeouts.propose;
+		Tendermint {
+			params: params,
+			our_params: our_params,
+			builtins: builtins,
+			client: RwLock::new(None),
+			signer: RwLock::new(None),
+			state: Mutex::new(RoundState::new(0, H256::new(), deadline)),
+		}
+	}
+
+	/// The validator which is to propose in `round` at `height` on top of block `parent`, if there are any validators.
+	pub fn round_proposer(&self, parent: &H256, height: Height, round: Round) -> Option<Address> {
+		let validators = self.our_params.validators.validators(parent, height);
+		match validators.is_empty() {
+			true => None,
+			false => Some(validators[((height + round) % validators.len() as u64) as usize].clone()),
+		}
+	}
+
+	fn client(&self) -> Option<Arc<Client>> {
+		self.client.read().as_ref().and_then(|c| c.upgrade())
+	}
+
+	fn signer_address(&self) -> Option<Address> {
+		self.signer.read().as_ref().map(|&(_, ref address)| address.clone())
+	}
+
+	/// Sign a message for the current round with our account, if it belongs to a validator.
+	fn sign(&self, state: &RoundState, step: Step, block_hash: Option<H256>, block: Bytes) -> Option<ConsensusMessage> {
+		let signer = self.signer.read();
+		let (accounts, address) = match *signer {
+			Some((ref accounts, ref address)) if self.our_params.validators.contains(&state.parent, state.height, address) => (accounts, address),
+			_ => return None,
+		};
+		// account should be permanently unlocked, otherwise signing will fail
+		match accounts.sign(*address, vote_hash(state.height, state.round, step, &block_hash)) {
+			Ok(signature) => Some(ConsensusMessage {
+				signature: signature,
+				height: state.height,
+				round: state.round,
+				step: step,
+				block_hash: block_hash,
+				block: block,
+			}),
+			Err(e) => {
+				warn!(target: "tendermint", "Could not sign consensus message with {}: {:?}", address, e);
+				None
+			},
+		}
+	}
+
+	/// Moves to the height on top of the best block if it changed since, and takes the messages
+	/// received for it in advance.
+	fn refresh_height(&self, state: &mut RoundState, outbox: &mut Outbox) {
+		if let Some(client) = self.client() {
+			let info = client.chain_info();
+			if info.best_block_hash != state.parent {
+				trace!(target: "tendermint", "Starting height {}", info.best_block_number + 1);
+				let future = mem::replace(&mut state.future, Vec::new());
+				*state = RoundState::new(info.best_block_number + 1, info.best_block_hash, Instant::now() + self.our_params.timeouts.propose);
+				for rlp in future {
+					if let Err(e) = self.take_message(state, outbox, &rlp) {
+						trace!(target: "tendermint", "Dropping message received in advance: {}", e);
+					}
+				}
+			}
+		}
+	}
+
+	/// Check a consensus message and record it, relaying it and taking the steps it allows.
+	fn take_message(&self, state: &mut RoundState, outbox: &mut Outbox, rlp: &[u8]) -> Result<(), Error> {
+		let message: ConsensusMessage = try!(UntrustedRlp::new(rlp).as_val());
+		if message.height == state.height + 1 && state.future.len() < MAX_FUTURE_MESSAGES {
+			state.future.push(rlp.to_vec());
+			return Ok(());
+		}
+		if message.height != state.height {
+			return Err(From::from(EngineError::WrongHeight(Mismatch { expected: state.height, found: message.height })));
+		}
+		let signer = try!(message.signer());
+		if !self.our_params.validators.contains(&state.parent, state.height, &signer) {
+			return Err(From::from(EngineError::NotAuthorized(signer)));
+		}
+		if self.is_known(state, &signer, &message) {
+			return Ok(());
+		}
+
+		let step = message.step;
+		match step {
+			Step::Propose => {
+				let proposer = self.round_proposer(&state.parent, message.height, message.round);
+				if proposer != Some(signer) {
+					return Err(From::from(EngineError::NotProposer(Mismatch { expected: proposer.unwrap_or_else(Address::new), found: signer })));
+				}
+				let valid = try!(self.verify_proposal(state, &message));
+				state.proposals.insert(message.round, (message, valid));
+			},
+			_ => {
+				state.votes.entry((message.round, step)).or_insert_with(HashMap::new).insert(signer, message);
+			},
+		}
+		// relay the messages we took to the peers which are not connected to the sender.
+		outbox.messages.push(rlp.to_vec());
+		self.advance(state, outbox);
+		Ok(())
+	}
+
+	fn to_step(&self, state: &mut RoundState, step: Step) {
+		trace!(target: "tendermint", "Height {}, round {}: {:?}", state.height, state.round, step);
+		state.step = step;
+		state.deadline = Instant::now() + self.our_params.timeouts.for_step(step);
+	}
+
+	fn to_round(&self, state: &mut RoundState, round: Round) {
+		state.round = round;
+		self.to_step(state, Step::Propose);
+	}
+
+	/// Propose the block we are locked on, or else the one our miner is sealing, if it is our turn in the current round.
+	fn propose(&self, state: &mut RoundState, outbox: &mut Outbox) {
+		if state.proposals.contains_key(&state.round) {
+			return;
+		}
+		if self.signer_address().is_none() || self.round_proposer(&state.parent, state.height, state.round) != self.signer_address() {
+			return;
+		}
+		let (block_hash, block) = match state.lock {
+			Some(ref lock) => (lock.block_hash.clone(), lock.block.clone()),
+			None => match self.client().and_then(|c| c.sealing_block()) {
+				Some(block) => {
+					let header: Header = BlockView::new(&block).header();
+					if header.parent_hash() != &state.parent {
+						trace!(target: "tendermint", "propose: no block ready for sealing on top of {}", state.parent);
+						return;
+					}
+					(Some(header.bare_hash()), block)
+				},
+				None => {
+					trace!(target: "tendermint", "propose: no block ready for sealing on top of {}", state.parent);
+					return;
+				},
+			},
+		};
+		if let Some(proposal) = self.sign(state, Step::Propose, block_hash, block) {
+			outbox.messages.push(encode(&proposal).to_vec());
+			state.proposals.insert(state.round, (proposal, true));
+		}
+	}
+
+	/// Check the difficulty and gas limit of `header` follow from those of its parent.
+	fn verify_parent(&self, header: &Header, parent: &Header) -> Result<(), Error> {
+		if header.difficulty() != parent.difficulty() {
+			return Err(From::from(BlockError::InvalidDifficulty(Mismatch { expected: *parent.difficulty(), found: *header.difficulty() })))
+		}
+		let gas_limit_divisor = self.our_params.gas_limit_bound_divisor;
+		let min_gas = parent.gas_limit - parent.gas_limit / gas_limit_divisor;
+		let max_gas = parent.gas_limit + parent.gas_limit / gas_limit_divisor;
+		if header.gas_limit <= min_gas || header.gas_limit >= max_gas {
+			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit })));
+		}
+		Ok(())
+	}
+
+	/// Whether the block of `proposal` can be sealed at the current height. Fails if the proposal
+	/// does not carry the block it proposes.
+	fn verify_proposal(&self, state: &RoundState, proposal: &ConsensusMessage) -> Result<bool, Error> {
+		let block: Block = try!(UntrustedRlp::new(&proposal.block).as_val());
+		let header = &block.header;
+		if proposal.block_hash != Some(header.bare_hash()) {
+			return Err(From::from(EngineError::ProposalMismatch(Mismatch { expected: proposal.block_hash.unwrap_or_else(H256::new), found: header.bare_hash() })));
+		}
+		if header.parent_hash() != &state.parent || header.number() != state.height {
+			trace!(target: "tendermint", "Proposed block {} is not on top of {}", header.bare_hash(), state.parent);
+			return Ok(false);
+		}
+		let client = match self.client() {
+			Some(client) => client,
+			None => return Ok(false),
+		};
+		let parent: Header = match client.block_header(BlockID::Hash(state.parent.clone())) {
+			Some(parent) => decode(&parent),
+			None => return Ok(false),
+		};
+		match self.verify_parent(header, &parent).and_then(|_| client.verify_proposal(&proposal.block)) {
+			Ok(()) => Ok(true),
+			Err(e) => {
+				trace!(target: "tendermint", "Proposed block {} is invalid: {}", header.bare_hash(), e);
+				Ok(false)
+			},
+		}
+	}
+
+	fn vote(&self, state: &mut RoundState, outbox: &mut Outbox, step: Step, block_hash: Option<H256>) {
+		if let Some(message) = self.sign(state, step, block_hash, Vec::new()) {
+			outbox.messages.push(encode(&message).to_vec());
+			let address = self.signer_address().expect("message was signed; qed");
+			state.votes.entry((message.round, step)).or_insert_with(HashMap::new).insert(address, message);
+		}
+	}
+
+	/// The value more than two thirds of the validators voted for in `step` of `round`, if any.
+	fn decision(&self, state: &RoundState, round: Round, step: Step) -> Option<Option<H256>> {
+		let validators = self.our_params.validators.validators(&state.parent, state.height).len();
+		let mut counts: HashMap<&Option<H256>, usize> = HashMap::new();
+		if let Some(votes) = state.votes.get(&(round, step)) {
+			for message in votes.values() {
+				*counts.entry(&message.block_hash).or_insert(0) += 1;
+			}
+		}
+		counts.into_iter().find(|&(_, count)| is_supermajority(count, validators)).map(|(block_hash, _)| block_hash.clone())
+	}
+
+	/// The latest round after the current one in which more than two thirds of the validators voted.
+	fn future_round(&self, state: &RoundState) -> Option<Round> {
+		let validators = self.our_params.validators.validators(&state.parent, state.height).len();
+		let mut voters: HashMap<Round, HashSet<&Address>> = HashMap::new();
+		for (&(round, _), votes) in &state.votes {
+			if round > state.round {
+				voters.entry(round).or_insert_with(HashSet::new).extend(votes.keys());
+			}
+		}
+		voters.into_iter().filter(|&(_, ref voters)| is_supermajority(voters.len(), validators)).map(|(round, _)| round).max()
+	}
+
+	/// Take every step the messages received so far allow.
+	fn advance(&self, state: &mut RoundState, outbox: &mut Outbox) {
+		loop {
+			if state.step == Step::Commit {
+				return;
+			}
+			let round = state.round;
+			match self.decision(state, round, Step::Precommit) {
+				Some(Some(block_hash)) => {
+					self.to_step(state, Step::Commit);
+					self.commit(state, outbox, block_hash);
+					return;
+				},
+				Some(None) => {
+					self.to_round(state, round + 1);
+					continue;
+				},
+				None => {},
+			}
+
+			if state.step == Step::Propose {
+				self.propose(state, outbox);
+				let proposed = state.proposals.get(&round).map(|&(ref proposal, valid)| match valid {
+					true => proposal.block_hash.clone(),
+					false => None,
+				});
+				if let Some(block_hash) = proposed {
+					self.to_step(state, Step::Prevote);
+					let vote = state.lock_hash().or(block_hash);
+					self.vote(state, outbox, Step::Prevote, vote);
+					continue;
+				}
+			}
+
+			if state.step != Step::Precommit {
+				if let Some(block_hash) = self.decision(state, round, Step::Prevote) {
+					let vote = match block_hash {
+						Some(block_hash) => match state.proposals.get(&round) {
+							Some(&(ref proposal, true)) if proposal.block_hash == Some(block_hash) => {
+								state.lock = Some(proposal.clone());
+								Some(block_hash)
+							},
+							// we cannot precommit to a block we have not verified.
+							_ => None,
+						},
+						None => {
+							state.lock = None;
+							None
+						},
+					};
+					self.to_step(state, Step::Precommit);
+					self.vote(state, outbox, Step::Precommit, vote);
+					continue;
+				}
+			}
+
+			match self.future_round(state) {
+				Some(round) => {
+					trace!(target: "tendermint", "Catching up with round {}", round);
+					self.to_round(state, round);
+				},
+				None => return,
+			}
+		}
+	}
+
+	/// Seal the committed block if we proposed it, so that there is a single sealed block which
+	/// the other validators sync.
+	fn commit(&self, state: &RoundState, outbox: &mut Outbox, block_hash: H256) {
+		if self.round_proposer(&state.parent, state.height, state.round) != self.signer_address() {
+			return;
+		}
+		let proposal = match state.proposals.get(&state.round) {
+			Some(&(ref proposal, _)) if proposal.block_hash == Some(block_hash) => proposal,
+			_ => return,
+		};
+		let precommits: Vec<H520> = state.votes.get(&(state.round, Step::Precommit))
+			.map(|votes| votes.values().filter(|m| m.block_hash == Some(block_hash)).map(|m| m.signature.clone()).collect())
+			.unwrap_or_else(Vec::new);
+		let mut block: Block = decode(&proposal.block);
+		block.header.set_seal(vec![encode(&state.round).to_vec(), encode(&proposal.signature).to_vec(), encode(&precommits).to_vec()]);
+		outbox.block = Some(block.rlp_bytes(Seal::With));
+	}
+
+	fn is_known(&self, state: &RoundState, signer: &Address, message: &ConsensusMessage) -> bool {
+		match message.step {
+			Step::Propose => state.proposals.contains_key(&message.round),
+			_ => state.votes.get(&(message.round, message.step)).map_or(false, |votes| votes.contains_key(signer)),
+		}
+	}
+
+	fn flush(&self, outbox: Outbox) {
+		let client = match self.client() {
+			Some(client) => client,
+			None => return,
+		};
+		for message in outbox.messages {
+			client.broadcast_consensus_message(message);
+		}
+		if let Some(block) = outbox.block {
+			if let Err(e) = client.import_block(block) {
+				trace!(target: "tendermint", "Could not import committed block: {:?}", e);
+			}
+		}
+	}
+}
+
+/// Round recorded in the seal of `header`.
+fn header_round(header: &Header) -> Result<Round, DecoderError> {
+	UntrustedRlp::new(&header.seal()[0]).as_val()
+}
+
+/// Signature of the proposal recorded in the seal of `header`.
+fn header_proposal_signature(header: &Header) -> Result<H520, DecoderError> {
+	UntrustedRlp::new(&header.seal()[1]).as_val()
+}
+
+/// Precommit signatures recorded in the seal of `header`.
+fn header_precommits(header: &Header) -> Result<Vec<H520>, DecoderError> {
+	UntrustedRlp::new(&header.seal()[2]).as_val()
+}
+
+/// Recover the proposer of `header` from its seal.
+fn recover_proposer(header: &Header) -> Result<Address, Error> {
+	let hash = vote_hash(header.number(), try!(header_round(header)), Step::Propose, &Some(header.bare_hash()));
+	Ok(Address::from(try!(ec::recover(&try!(header_proposal_signature(header)), &hash)).sha3()))
+}
+
+/// Recover the validators which precommitted to `header` from its seal.
+fn recover_precommitters(header: &Header) -> Result<Vec<Address>, Error> {
+	let hash = vote_hash(header.number(), try!(header_round(header)), Step::Precommit, &Some(header.bare_hash()));
+	let mut precommitters = Vec::new();
+	for signature in try!(header_precommits(header)) {
+		precommitters.push(Address::from(try!(ec::recover(&signature, &hash)).sha3()));
+	}
+	Ok(precommitters)
+}
+
+impl Engine for Tendermint {
+	fn name(&self) -> &str { "Tendermint" }
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+	// Three fields - the round, the proposal signature and the precommit signatures
+	fn seal_fields(&self) -> usize { 3 }
+
+	fn params(&self) -> &CommonParams { &self.params }
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+
+	/// Additional engine-specific information for the user/developer concerning `header`.
+	fn extra_info(&self, header: &Header) -> HashMap<String, String> {
+		if header.seal.len() != self.seal_fields() {
+			return HashMap::new();
+		}
+		hash_map![
+			"round".to_owned() => header_round(header).map(|r| r.to_string()).unwrap_or_else(|_| "invalid".to_owned()),
+			"signer".to_owned() => self.signer(header).map_or_else(|| "invalid".to_owned(), |s| format!("0x{}", s.hex())),
+			"precommits".to_owned() => header_precommits(header).map(|p| p.len().to_string()).unwrap_or_else(|_| "invalid".to_owned())
+		]
+	}
+
+	fn signer(&self, header: &Header) -> Option<Address> {
+		match header.seal.len() == self.seal_fields() {
+			true => recover_proposer(header).ok(),
+			false => None,
+		}
+	}
+
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_homestead();
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
+		header.difficulty = parent.difficulty;
+		header.gas_limit = {
+			let gas_limit = parent.gas_limit;
+			let bound_divisor = self.our_params.gas_limit_bound_divisor;
+			if gas_limit < gas_floor_target {
+				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else {
+				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
+			}
+		};
+		header.note_dirty();
+	}
+
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
+		if header.seal.len() != self.seal_fields() {
+			return Err(From::from(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: header.seal.len() }
+			)));
+		}
+		try!(header_round(header));
+		try!(header_proposal_signature(header));
+		try!(header_precommits(header));
+		Ok(())
+	}
+
+	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
+		// check the signatures are legit.
+		try!(recover_proposer(header));
+		try!(recover_precommitters(header));
+		Ok(())
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> result::Result<(), Error> {
+		// we should not calculate difficulty for genesis blocks
+		if header.number() == 0 {
+			return Err(From::from(BlockError::RidiculousNumber(OutOfBounds { min: Some(1), max: None, found: header.number() })));
+		}
+
+		let validators = self.our_params.validators.validators(header.parent_hash(), header.number());
+
+		// check the proposal is by the proposer of the round.
+		let proposer = try!(recover_proposer(header));
+		let expected = self.round_proposer(header.parent_hash(), header.number(), try!(header_round(header)));
+		if expected != Some(proposer) {
+			return Err(From::from(EngineError::NotProposer(Mismatch { expected: expected.unwrap_or_else(Address::new), found: proposer })));
+		}
+
+		// check more than two thirds of the validators precommitted to the block.
+		let mut precommitters = HashSet::new();
+		for precommitter in try!(recover_precommitters(header)) {
+			if !validators.contains(&precommitter) {
+				return Err(From::from(EngineError::NotAuthorized(precommitter)));
+			}
+			precommitters.insert(precommitter);
+		}
+		if !is_supermajority(precommitters.len(), validators.len()) {
+			return Err(From::from(EngineError::InsufficientPrecommits(
+				OutOfBounds { min: Some(validators.len() * 2 / 3 + 1), max: Some(validators.len()), found: precommitters.len() }
+			)));
+		}
+
+		self.verify_parent(header, parent)
+	}
+
+	fn verify_transaction_basic(&self, t: &SignedTransaction, header: &Header) -> result::Result<(), Error> {
+		try!(t.check_low_s());
+		self.verify_transaction_chain_id(t, header)
+	}
+
+	fn verify_transaction(&self, t: &SignedTransaction, _header: &Header) -> Result<(), Error> {
+		t.sender().map(|_|()) // Perform EC recovery and cache sender
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		self.our_params.validators.register_client(client.clone());
+		*self.client.write() = Some(client);
+	}
+
+	fn set_signer(&self, accounts: Arc<AccountProvider>, address: Address) {
+		*self.signer.write() = Some((accounts, address));
+	}
+
+	fn handle_message(&self, rlp: &[u8]) -> Result<(), Error> {
+		let mut outbox = Outbox::default();
+		let result = {
+			let mut state = self.state.lock();
+			self.refresh_height(&mut state, &mut outbox);
+			self.take_message(&mut state, &mut outbox, rlp)
+		};
+		self.flush(outbox);
+		result
+	}
+
+	fn tick(&self) {
+		let mut outbox = Outbox::default();
+		{
+			let mut state = self.state.lock();
+			self.refresh_height(&mut state, &mut outbox);
+			if Instant::now() >= state.deadline {
+				match state.step {
+					Step::Propose => {
+						self.to_step(&mut state, Step::Prevote);
+						let vote = state.lock_hash();
+						self.vote(&mut state, &mut outbox, Step::Prevote, vote);
+					},
+					Step::Prevote => {
+						self.to_step(&mut state, Step::Precommit);
+						self.vote(&mut state, &mut outbox, Step::Precommit, None);
+					},
+					Step::Precommit => {
+						let round = state.round + 1;
+						self.to_round(&mut state, round);
+					},
+					// the block is final; keep waiting for it.
+					Step::Commit => {},
+				}
+			}
+			self.advance(&mut state, &mut outbox);
+		}
+		self.flush(outbox);
+	}
+}
+
+/// Create a new test chain spec with `Tendermint` consensus engine among four validators,
+/// the accounts of secrets `"".sha3()` to `"3".sha3()`. Timeouts do not expire during a test.
+pub fn new_test_tendermint() -> Spec { Spec::load(include_bytes!("../../res/tendermint.json")).expect("invalid chain spec") }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use common::*;
+	use block::Block;
+	use engine::Engine;
+	use client::{BlockChainClient, Client, ClientConfig, ChainNotify};
+	use spec::Spec;
+	use miner::{Miner, MinerService, GasPricer};
+	use account_provider::AccountProvider;
+	use devtools::RandomTempPath;
+	use std::thread;
+	use std::time::Duration;
+
+	fn keypair(secret: &str) -> KeyPair {
+		KeyPair::from_secret(secret.sha3()).unwrap()
+	}
+
+	fn child_of(parent: &Header) -> Header {
+		let mut header = Header::default();
+		header.set_number(parent.number() + 1);
+		header.set_parent_hash(parent.hash());
+		header.set_difficulty(*parent.difficulty());
+		header.set_gas_limit(parent.gas_limit);
+		header
+	}
+
+	fn seal(header: &Header, round: Round, proposer: &KeyPair, precommitters: &[KeyPair]) -> Vec<Bytes> {
+		let proposal = vote_hash(header.number(), round, Step::Propose, &Some(header.bare_hash()));
+		let precommit = vote_hash(header.number(), round, Step::Precommit, &Some(header.bare_hash()));
+		let precommits: Vec<H520> = precommitters.iter().map(|k| k.sign(&precommit).unwrap()).collect();
+		vec![encode(&round).to_vec(), encode(&proposer.sign(&proposal).unwrap()).to_vec(), encode(&precommits).to_vec()]
+	}
+
+	fn message(signer: &KeyPair, height: Height, round: Round, step: Step, block_hash: Option<H256>, block: Bytes) -> Bytes {
+		encode(&ConsensusMessage {
+			signature: signer.sign(&vote_hash(height, round, step, &block_hash)).unwrap(),
+			height: height,
+			round: round,
+			step: step,
+			block_hash: block_hash,
+			block: block,
+		}).to_vec()
+	}
+
+	fn vote(signer: &KeyPair, height: Height, round: Round, step: Step, block_hash: Option<H256>) -> Bytes {
+		message(signer, height, round, step, block_hash, Vec::new())
+	}
+
+	fn proposal(signer: &KeyPair, height: Height, round: Round, block: &Bytes) -> Bytes {
+		let header: Header = BlockView::new(block).header();
+		message(signer, height, round, Step::Propose, Some(header.bare_hash()), block.clone())
+	}
+
+	/// Collects the consensus messages the client broadcasts.
+	#[derive(Default)]
+	struct TestNotify {
+		messages: RwLock<Vec<Bytes>>,
+	}
+
+	impl ChainNotify for TestNotify {
+		fn broadcast(&self, message: Vec<u8>) {
+			self.messages.write().push(message);
+		}
+	}
+
+	impl TestNotify {
+		fn sent(&self, round: Round, step: Step) -> Vec<ConsensusMessage> {
+			self.messages.read().iter().map(|m| decode::<ConsensusMessage>(m)).filter(|m| m.round == round && m.step == step).collect()
+		}
+	}
+
+	/// Client of the first validator, with a precommit timeout of 50ms.
+	fn validator_client(dir: &RandomTempPath) -> (Arc<Client>, Arc<TestNotify>) {
+		let spec = || Spec::load(include_str!("../../res/tendermint.json").replace("\"timeoutPrecommit\": \"0xffffffff\"", "\"timeoutPrecommit\": \"0x32\"").as_bytes()).unwrap();
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let address = tap.insert_account("".sha3(), "").unwrap();
+		tap.unlock_account_permanently(address, "".into()).unwrap();
+		let miner = Miner::new(Default::default(), GasPricer::new_fixed(0.into()), spec(), Some(tap.clone()));
+		miner.set_author(address);
+		let client = Client::new(ClientConfig::default(), spec(), dir.as_path(), miner, IoChannel::disconnected()).unwrap();
+		client.engine().set_signer(tap, address);
+		let notify = Arc::new(TestNotify::default());
+		client.add_notify(notify.clone());
+		(client, notify)
+	}
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = new_test_tendermint().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.version().major >= 1);
+	}
+
+	#[test]
+	fn can_do_seal_verification_fail() {
+		let engine = new_test_tendermint().engine;
+		let header: Header = Header::default();
+
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Block(BlockError::InvalidSealArity(_))) => {},
+			r => panic!("should be block seal-arity mismatch error (got {:?})", r),
+		}
+	}
+
+	#[test]
+	fn verifies_seal_with_precommits() {
+		let spec = new_test_tendermint();
+		let engine = &spec.engine;
+		let genesis_header = spec.genesis_header();
+		let mut header = child_of(&genesis_header);
+		// the second validator proposes in the first round at height 1
+		let precommitters = vec![keypair(""), keypair("1"), keypair("2")];
+		header.set_seal(seal(&header, 0, &keypair("1"), &precommitters));
+
+		assert!(engine.verify_block_basic(&header, None).is_ok());
+		assert!(engine.verify_block_unordered(&header, None).is_ok());
+		assert!(engine.verify_block_family(&header, &genesis_header, None).is_ok());
+		assert_eq!(engine.signer(&header), Some(keypair("1").address()));
+	}
+
+	#[test]
+	fn rejects_seal_without_supermajority() {
+		let spec = new_test_tendermint();
+		let engine = &spec.engine;
+		let genesis_header = spec.genesis_header();
+		let mut header = child_of(&genesis_header);
+		header.set_seal(seal(&header, 0, &keypair("1"), &[keypair(""), keypair("1")]));
+
+		match engine.verify_block_family(&header, &genesis_header, None) {
+			Err(Error::Engine(EngineError::InsufficientPrecommits(_))) => {},
+			r => panic!("should be insufficient precommits error (got {:?})", r),
+		}
+
+		// precommits by the same validator count once
+		header.set_seal(seal(&header, 0, &keypair("1"), &[keypair(""), keypair("1"), keypair("1")]));
+		match engine.verify_block_family(&header, &genesis_header, None) {
+			Err(Error::Engine(EngineError::InsufficientPrecommits(_))) => {},
+			r => panic!("should be insufficient precommits error (got {:?})", r),
+		}
+
+		header.set_seal(seal(&header, 0, &keypair("1"), &[keypair(""), keypair("1"), keypair("4")]));
+		match engine.verify_block_family(&header, &genesis_header, None) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			r => panic!("should be not authorized error (got {:?})", r),
+		}
+	}
+
+	#[test]
+	fn rejects_proposal_out_of_turn() {
+		let spec = new_test_tendermint();
+		let engine = &spec.engine;
+		let genesis_header = spec.genesis_header();
+		let mut header = child_of(&genesis_header);
+		let precommitters = vec![keypair(""), keypair("1"), keypair("2")];
+		header.set_seal(seal(&header, 0, &keypair("2"), &precommitters));
+
+		match engine.verify_block_family(&header, &genesis_header, None) {
+			Err(Error::Engine(EngineError::NotProposer(_))) => {},
+			r => panic!("should be not proposer error (got {:?})", r),
+		}
+
+		// in the next round it is the third validator's turn
+		header.set_seal(seal(&header, 1, &keypair("2"), &precommitters));
+		assert!(engine.verify_block_family(&header, &genesis_header, None).is_ok());
+	}
+
+	#[test]
+	fn rejects_messages_from_non_validators() {
+		let spec = new_test_tendermint();
+		let engine = &spec.engine;
+		// without a client the engine stays at height 0.
+		match engine.handle_message(&vote(&keypair("4"), 0, 0, Step::Prevote, None)) {
+			Err(Error::Engine(EngineError::NotAuthorized(_))) => {},
+			r => panic!("should be not authorized error (got {:?})", r),
+		}
+		// messages for the next height are kept for later
+		assert!(engine.handle_message(&vote(&keypair("1"), 1, 0, Step::Prevote, None)).is_ok());
+		match engine.handle_message(&vote(&keypair("1"), 2, 0, Step::Prevote, None)) {
+			Err(Error::Engine(EngineError::WrongHeight(_))) => {},
+			r => panic!("should be wrong height error (got {:?})", r),
+		}
+		let block = Block { header: child_of(&spec.genesis_header()), transactions: Vec::new(), uncles: Vec::new() }.rlp_bytes(Seal::Without);
+		match engine.handle_message(&proposal(&keypair("1"), 0, 0, &block)) {
+			Err(Error::Engine(EngineError::NotProposer(_))) => {},
+			r => panic!("should be not proposer error (got {:?})", r),
+		}
+		match engine.handle_message(&message(&keypair(""), 0, 0, Step::Propose, Some(H256::from(1)), block.clone())) {
+			Err(Error::Engine(EngineError::ProposalMismatch(_))) => {},
+			r => panic!("should be proposal mismatch error (got {:?})", r),
+		}
+		assert!(engine.handle_message(&proposal(&keypair(""), 0, 0, &block)).is_ok());
+		assert!(engine.handle_message(&vote(&keypair("1"), 0, 0, Step::Prevote, None)).is_ok());
+	}
+
+	#[test]
+	fn keeps_prevoting_for_locked_block() {
+		let dir = RandomTempPath::new();
+		let (client, notify) = validator_client(&dir);
+		let engine = client.engine();
+		let block = client.sealing_block().unwrap();
+		let block_hash = BlockView::new(&block).header().bare_hash();
+
+		// the second validator proposes in the first round at height 1
+		engine.handle_message(&proposal(&keypair("1"), 1, 0, &block)).unwrap();
+		assert_eq!(notify.sent(0, Step::Prevote)[0].block_hash, Some(block_hash));
+		engine.handle_message(&vote(&keypair("1"), 1, 0, Step::Prevote, Some(block_hash))).unwrap();
+		engine.handle_message(&vote(&keypair("2"), 1, 0, Step::Prevote, Some(block_hash))).unwrap();
+		assert_eq!(notify.sent(0, Step::Precommit)[0].block_hash, Some(block_hash));
+
+		// precommits do not come in time, so the next round starts
+		thread::sleep(Duration::from_millis(100));
+		engine.tick();
+
+		// the third validator proposes another valid block, but we stay locked
+		let mut other: Block = decode(&block);
+		other.header.set_extra_data(vec![1]);
+		let other = other.rlp_bytes(Seal::Without);
+		engine.handle_message(&proposal(&keypair("2"), 1, 1, &other)).unwrap();
+		let prevotes = notify.sent(1, Step::Prevote);
+		assert_eq!(prevotes.len(), 1);
+		assert_eq!(prevotes[0].block_hash, Some(block_hash));
+		assert_eq!(client.chain_info().best_block_number, 0);
+	}
+
+	#[test]
+	fn catches_up_with_later_round() {
+		let dir = RandomTempPath::new();
+		let (client, notify) = validator_client(&dir);
+		let engine = client.engine();
+
+		engine.handle_message(&vote(&keypair("1"), 1, 5, Step::Prevote, None)).unwrap();
+		engine.handle_message(&vote(&keypair("2"), 1, 5, Step::Prevote, None)).unwrap();
+		assert!(notify.sent(5, Step::Precommit).is_empty());
+		// more than two thirds of the validators are in round 5, which has a polka for nil
+		engine.handle_message(&vote(&keypair("3"), 1, 5, Step::Prevote, None)).unwrap();
+		let precommits = notify.sent(5, Step::Precommit);
+		assert_eq!(precommits.len(), 1);
+		assert_eq!(precommits[0].block_hash, None);
+		assert!(notify.sent(0, Step::Prevote).is_empty());
+	}
+}
@@ -14,7 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockID};
+use client::{BlockChainClient, MiningBlockChainClient, DebuggingBlockChainClient, Client, ClientConfig, BlockID, TransactionID, CallAnalytics};
+use types::account_diff::Diff;
+use spec::Spec;
 use block::IsBlock;
 use tests::helpers::*;
 use common::*;
@@ -154,3 +156,53 @@ fn can_mine() {
 
 	assert_eq!(*b.block().header().parent_hash(), BlockView::new(&dummy_blocks[0]).header_view().sha3());
 }
+
+#[test]
+fn replays_transactions_of_the_chain() {
+	// store 1 at 0, then load it
+	let init_code = "600160005560005400".from_hex().unwrap();
+	let client_result = generate_dummy_client_with_spec_and_init_code(Spec::new_null, 1, 2, &[0.into()], &init_code);
+	let client = client_result.reference();
+	let id = TransactionID::Location(BlockID::Number(1), 1);
+	let receipt = client.transaction_receipt(id.clone()).unwrap();
+	let created = receipt.contract_address.clone().unwrap();
+
+	let analytics = CallAnalytics { state_diffing: true, ..Default::default() };
+	let executed = client.replay(id.clone(), analytics).unwrap();
+	assert_eq!(executed.gas_used, receipt.gas_used);
+	assert_eq!(executed.exception, None);
+	assert_eq!(executed.state_diff.unwrap().raw[&created].storage[&H256::new()], Diff::Born(H256::from(&U256::from(1))));
+
+	let (executed, frames) = client.replay_frames(id, true, true).unwrap();
+	assert_eq!(executed.gas_used, receipt.gas_used);
+	assert_eq!(frames.iter().map(|f| (f.pc, f.depth)).collect::<Vec<_>>(), vec![(0, 1), (2, 1), (4, 1), (5, 1), (7, 1), (8, 1)]);
+	assert_eq!(frames[0].gas, U256::from(100_000) - executed.gas_used + U256::from(20_000 + 3 + 3 + 3 + 50));
+	assert_eq!(frames[4].stack, vec![U256::zero()]);
+	assert_eq!(frames[4].storage, map![U256::zero() => U256::one()]);
+	assert_eq!(frames[5].stack, vec![U256::one()]);
+}
+
+#[test]
+fn returns_schedule_of_requested_block() {
+	let spec = || Spec::load(include_str!("../../res/null.json")
+		.replace(r#""networkID" : "0x2""#, r#""networkID" : "0x2", "eip140Transition": "0x2""#).as_bytes()).unwrap();
+	let client_result = generate_dummy_client_with_spec_and_data(spec, 1, 0, &[]);
+	let client = client_result.reference();
+
+	assert!(!client.schedule_at(BlockID::Earliest).unwrap().have_revert);
+	assert!(!client.schedule_at(BlockID::Latest).unwrap().have_revert);
+	assert!(client.schedule_at(BlockID::Pending).unwrap().have_revert);
+	assert!(client.schedule_at(BlockID::Hash(H256::from(1u64))).is_none());
+}
+
+#[test]
+fn verifies_snark_with_builtin_of_next_block() {
+	let builtin = r#""0000000000000000000000000000000000000010": { "balance": "1", "nonce": "0", "builtin": { "name": "zkSNARK", "activate_at": "0x2", "pricing": { "linear": { "base": 1000, "word": 0 } } } },"#;
+	let spec = || Spec::load(include_str!("../../res/null.json")
+		.replace(r#""accounts": {"#, &format!(r#""accounts": {{ {}"#, builtin)).as_bytes()).unwrap();
+	let client_result = generate_dummy_client_with_spec_and_data(spec, 1, 0, &[]);
+	let client = client_result.reference();
+
+	// the best block is 1, but a transaction would run in block 2
+	assert_eq!(client.verify_snark(vec![1], vec![2], vec![3]).gas, Some(U256::from(1000)));
+}
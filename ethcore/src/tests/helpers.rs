@@ -129,6 +129,10 @@ pub fn generate_dummy_client_with_data(block_number: u32, txs_per_block: usize,
 }
 
 pub fn generate_dummy_client_with_spec_and_data<F>(get_test_spec: F, block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256]) -> GuardedTempResult<Arc<Client>> where F: Fn()->Spec {
+	generate_dummy_client_with_spec_and_init_code(get_test_spec, block_number, txs_per_block, tx_gas_prices, &[])
+}
+
+pub fn generate_dummy_client_with_spec_and_init_code<F>(get_test_spec: F, block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256], init_code: &[u8]) -> GuardedTempResult<Arc<Client>> where F: Fn()->Spec {
 	let dir = RandomTempPath::new();
 
 	let test_spec = get_test_spec();
@@ -176,9 +180,9 @@ pub fn generate_dummy_client_with_spec_and_data<F>(get_test_spec: F, block_numbe
 				gas_price: tx_gas_prices[n % tx_gas_prices.len()],
 				gas: 100000.into(),
 				action: Action::Create,
-				data: vec![],
+				data: init_code.to_vec(),
 				value: U256::zero(),
-			}.sign(kp.secret()), None).unwrap();
+			}.sign(kp.secret(), None), None).unwrap();
 			n += 1;
 		}
 
@@ -87,6 +87,21 @@ impl Tracer for ExecutiveTracer {
 		self.traces.push(trace);
 	}
 
+	fn trace_failed_builtin_call(&mut self, call: Option<Call>, depth: usize, reason: String, delegate_call: bool) {
+		// don't trace if it's DELEGATECALL or CALLCODE.
+		if delegate_call {
+			return;
+		}
+
+		let trace = Trace {
+			depth: depth,
+			subs: vec![],
+			action: Action::Call(call.expect("self.prepare_trace_call().is_some(): so we must be tracing: qed")),
+			result: Res::FailedBuiltinCall(reason),
+		};
+		self.traces.push(trace);
+	}
+
 	fn trace_failed_create(&mut self, create: Option<Create>, depth: usize, subs: Vec<Trace>) {
 		let trace = Trace {
 			depth: depth,
@@ -97,6 +112,37 @@ impl Tracer for ExecutiveTracer {
 		self.traces.push(trace);
 	}
 
+	fn trace_reverted_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, depth: usize, subs: Vec<Trace>, delegate_call: bool) {
+		// don't trace if it's DELEGATECALL or CALLCODE.
+		if delegate_call {
+			return;
+		}
+
+		let trace = Trace {
+			depth: depth,
+			subs: subs,
+			action: Action::Call(call.expect("self.prepare_trace_call().is_some(): so we must be tracing: qed")),
+			result: Res::Reverted(CallResult {
+				gas_used: gas_used,
+				output: output.expect("self.prepare_trace_output().is_some(): so we must be tracing: qed")
+			})
+		};
+		self.traces.push(trace);
+	}
+
+	fn trace_reverted_create(&mut self, create: Option<Create>, gas_used: U256, output: Option<Bytes>, depth: usize, subs: Vec<Trace>) {
+		let trace = Trace {
+			depth: depth,
+			subs: subs,
+			action: Action::Create(create.expect("self.prepare_trace_create().is_some(): so we must be tracing: qed")),
+			result: Res::Reverted(CallResult {
+				gas_used: gas_used,
+				output: output.expect("self.prepare_trace_output().is_some(): so we must be tracing: qed")
+			})
+		};
+		self.traces.push(trace);
+	}
+
 	fn subtracer(&self) -> Self {
 		ExecutiveTracer::default()
 	}
@@ -25,6 +25,7 @@ mod executive_tracer;
 pub mod flat;
 mod import;
 mod noop_tracer;
+mod profiling_tracer;
 
 pub use types::trace_types::*;
 pub use self::block::BlockTraces;
@@ -34,6 +35,7 @@ pub use self::error::Error;
 pub use types::trace_types::trace::{Trace, VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff};
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
+pub use self::profiling_tracer::ProfilingVMTracer;
 pub use types::trace_types::filter::{Filter, AddressesFilter};
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
@@ -41,6 +43,7 @@ use util::{Bytes, Address, U256, H256};
 use self::trace::{Call, Create};
 use action_params::ActionParams;
 use header::BlockNumber;
+use types::profile::Profile;
 
 /// This trait is used by executive to build traces.
 pub trait Tracer: Send {
@@ -78,9 +81,18 @@ pub trait Tracer: Send {
 	/// Stores failed call trace.
 	fn trace_failed_call(&mut self, call: Option<Call>, depth: usize, subs: Vec<Trace>, delegate_call: bool);
 
+	/// Stores trace of a builtin call which failed on its input, along with the reason.
+	fn trace_failed_builtin_call(&mut self, call: Option<Call>, depth: usize, reason: String, delegate_call: bool);
+
 	/// Stores failed create trace.
 	fn trace_failed_create(&mut self, create: Option<Create>, depth: usize, subs: Vec<Trace>);
 
+	/// Stores trace of a call which stopped on `REVERT`, along with the data it reverted with.
+	fn trace_reverted_call(&mut self, call: Option<Call>, gas_used: U256, output: Option<Bytes>, depth: usize, subs: Vec<Trace>, delegate_call: bool);
+
+	/// Stores trace of a create which stopped on `REVERT`, along with the data it reverted with.
+	fn trace_reverted_create(&mut self, create: Option<Create>, gas_used: U256, output: Option<Bytes>, depth: usize, subs: Vec<Trace>);
+
 	/// Spawn subtracer which will be used to trace deeper levels of execution.
 	fn subtracer(&self) -> Self where Self: Sized;
 
@@ -94,6 +106,10 @@ pub trait VMTracer: Send {
 	/// @returns true if `trace_executed` should be called.
 	fn trace_prepare_execute(&mut self, _pc: usize, _instruction: u8, _gas_cost: &U256) -> bool { false }
 
+	/// Trace the machine state just before the prepared instruction is executed: the gas left,
+	/// the stack (bottom first) and the memory. Only called if `trace_prepare_execute` returned true.
+	fn trace_state(&mut self, _gas: &U256, _stack: &[U256], _mem: &[u8]) {}
+
 	/// Trace the finalised execution of a single instruction.
 	fn trace_executed(&mut self, _gas_used: U256, _stack_push: &[U256], _mem_diff: Option<(usize, &[u8])>, _store_diff: Option<(U256, U256)>) {}
 
@@ -103,6 +119,9 @@ pub trait VMTracer: Send {
 	/// Spawn subtracer which will be used to trace deeper levels of execution.
 	fn done_subtrace(&mut self, sub: Self) where Self: Sized;
 
+	/// Takes the execution profile collected so far, if this tracer profiles.
+	fn take_profile(&mut self) -> Option<Profile> { None }
+
 	/// Consumes self and returns the VM trace.
 	fn drain(self) -> Option<VMTrace>;
 }
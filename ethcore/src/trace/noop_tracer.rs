@@ -51,10 +51,24 @@ impl Tracer for NoopTracer {
 		assert!(call.is_none(), "self.prepare_trace_call().is_none(): so we can't be tracing: qed");
 	}
 
+	fn trace_failed_builtin_call(&mut self, call: Option<Call>, _: usize, _: String, _: bool) {
+		assert!(call.is_none(), "self.prepare_trace_call().is_none(): so we can't be tracing: qed");
+	}
+
 	fn trace_failed_create(&mut self, create: Option<Create>, _: usize, _: Vec<Trace>) {
 		assert!(create.is_none(), "self.prepare_trace_create().is_none(): so we can't be tracing: qed");
 	}
 
+	fn trace_reverted_call(&mut self, call: Option<Call>, _: U256, output: Option<Bytes>, _: usize, _: Vec<Trace>, _: bool) {
+		assert!(call.is_none(), "self.prepare_trace_call().is_none(): so we can't be tracing: qed");
+		assert!(output.is_none(), "self.prepare_trace_output().is_none(): so we can't be tracing: qed");
+	}
+
+	fn trace_reverted_create(&mut self, create: Option<Create>, _: U256, output: Option<Bytes>, _: usize, _: Vec<Trace>) {
+		assert!(create.is_none(), "self.prepare_trace_create().is_none(): so we can't be tracing: qed");
+		assert!(output.is_none(), "self.prepare_trace_output().is_none(): so we can't be tracing: qed");
+	}
+
 	fn subtracer(&self) -> Self {
 		NoopTracer
 	}
@@ -0,0 +1,177 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! VM tracer collecting per-instruction execution statistics.
+
+use std::collections::{HashMap, BTreeMap};
+use std::collections::btree_map::Entry;
+use std::time::{Instant, Duration};
+use util::{H256, U256, Hashable};
+use trace::VMTracer;
+use trace::trace::VMTrace;
+use types::profile::{Profile, InstructionProfile};
+
+/// Collects, by code hash, how often each instruction ran, what it cost and how long it took.
+/// The time of instructions calling or creating contracts excludes that of the subcall.
+/// VM tracing is passed on to the wrapped tracer.
+pub struct ProfilingVMTracer<V> {
+	inner: V,
+	code_hash: H256,
+	stats: HashMap<H256, BTreeMap<u8, InstructionProfile>>,
+	current: Option<(u8, U256, Instant)>,
+	inner_traces: bool,
+	started: Instant,
+	subcalls_ns: u64,
+}
+
+fn as_ns(duration: Duration) -> u64 {
+	duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
+
+impl<V: VMTracer> ProfilingVMTracer<V> {
+	/// Create a new profiler, handing VM tracing to `inner`.
+	pub fn new(inner: V) -> Self {
+		ProfilingVMTracer {
+			inner: inner,
+			code_hash: H256::new(),
+			stats: HashMap::new(),
+			current: None,
+			inner_traces: false,
+			started: Instant::now(),
+			subcalls_ns: 0,
+		}
+	}
+}
+
+impl<V: VMTracer> VMTracer for ProfilingVMTracer<V> {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		self.inner_traces = self.inner.trace_prepare_execute(pc, instruction, gas_cost);
+		self.current = Some((instruction, gas_cost.clone(), Instant::now()));
+		self.subcalls_ns = 0;
+		true
+	}
+
+	fn trace_state(&mut self, gas: &U256, stack: &[U256], mem: &[u8]) {
+		if self.inner_traces {
+			self.inner.trace_state(gas, stack, mem);
+		}
+	}
+
+	fn trace_executed(&mut self, gas_used: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		if let Some((instruction, gas_cost, start)) = self.current.take() {
+			let elapsed = as_ns(start.elapsed()).saturating_sub(self.subcalls_ns);
+			let stats = self.stats.entry(self.code_hash.clone()).or_insert_with(BTreeMap::new)
+				.entry(instruction).or_insert_with(|| InstructionProfile {
+					instruction: instruction,
+					count: 0,
+					gas: U256::zero(),
+					time_ns: 0,
+				});
+			stats.count += 1;
+			stats.gas = stats.gas + gas_cost;
+			stats.time_ns += elapsed;
+		}
+		if self.inner_traces {
+			self.inner.trace_executed(gas_used, stack_push, mem_diff, store_diff);
+		}
+	}
+
+	fn prepare_subtrace(&self, code: &[u8]) -> Self {
+		ProfilingVMTracer {
+			inner: self.inner.prepare_subtrace(code),
+			code_hash: code.sha3(),
+			stats: HashMap::new(),
+			current: None,
+			inner_traces: false,
+			started: Instant::now(),
+			subcalls_ns: 0,
+		}
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.subcalls_ns += as_ns(sub.started.elapsed());
+		for (code_hash, sub_stats) in sub.stats {
+			let stats = self.stats.entry(code_hash).or_insert_with(BTreeMap::new);
+			for (instruction, sub_stat) in sub_stats {
+				match stats.entry(instruction) {
+					Entry::Occupied(mut entry) => {
+						let stat = entry.get_mut();
+						stat.count += sub_stat.count;
+						stat.gas = stat.gas + sub_stat.gas;
+						stat.time_ns += sub_stat.time_ns;
+					},
+					Entry::Vacant(entry) => {
+						entry.insert(sub_stat);
+					},
+				}
+			}
+		}
+		self.inner.done_subtrace(sub.inner);
+	}
+
+	fn take_profile(&mut self) -> Option<Profile> {
+		let code = self.stats.drain()
+			.map(|(code_hash, stats)| (code_hash, stats.into_iter().map(|(_, stat)| stat).collect()))
+			.collect();
+		Some(Profile { code: code })
+	}
+
+	fn drain(self) -> Option<VMTrace> { self.inner.drain() }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::thread;
+	use std::time::Duration;
+	use util::{U256, Hashable};
+	use trace::{VMTracer, NoopVMTracer};
+	use super::ProfilingVMTracer;
+
+	#[test]
+	fn merges_statistics_of_subtraces() {
+		let code = vec![0x60, 0x00, 0x60, 0x00, 0x01];
+		let mut tracer = ProfilingVMTracer::new(NoopVMTracer);
+		for _ in 0..2 {
+			let mut sub = tracer.prepare_subtrace(&code);
+			for &(pc, instruction, cost) in &[(0, 0x60, 3), (2, 0x60, 3), (4, 0x01, 3)] {
+				assert!(sub.trace_prepare_execute(pc, instruction, &U256::from(cost)));
+				sub.trace_executed(U256::zero(), &[], None, None);
+			}
+			tracer.done_subtrace(sub);
+		}
+
+		let profile = tracer.take_profile().unwrap();
+		let stats = &profile.code[&code.sha3()];
+		assert_eq!(stats.len(), 2);
+		assert_eq!((stats[0].instruction, stats[0].count, stats[0].gas), (0x01, 2, U256::from(6)));
+		assert_eq!((stats[1].instruction, stats[1].count, stats[1].gas), (0x60, 4, U256::from(12)));
+	}
+
+	#[test]
+	fn excludes_subcall_time_from_calling_instruction() {
+		let mut tracer = ProfilingVMTracer::new(NoopVMTracer);
+		assert!(tracer.trace_prepare_execute(0, 0xf1, &U256::from(40)));
+		let sub = tracer.prepare_subtrace(&[]);
+		thread::sleep(Duration::from_millis(50));
+		tracer.done_subtrace(sub);
+		tracer.trace_executed(U256::zero(), &[], None, None);
+
+		let profile = tracer.take_profile().unwrap();
+		let stats = profile.code.values().next().unwrap();
+		assert_eq!((stats[0].instruction, stats[0].count), (0xf1, 1));
+		assert!(stats[0].time_ns < 50_000_000);
+	}
+}
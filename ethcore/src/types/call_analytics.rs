@@ -29,4 +29,6 @@ pub struct CallAnalytics {
 	pub vm_tracing: bool,
 	/// Make a diff.
 	pub state_diffing: bool,
+	/// Collect per-instruction execution statistics.
+	pub profiling: bool,
 }
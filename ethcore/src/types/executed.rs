@@ -21,6 +21,7 @@ use util::Bytes;
 use trace::{Trace, VMTrace};
 use types::log_entry::LogEntry;
 use types::state_diff::StateDiff;
+use types::profile::Profile;
 use ipc::binary::BinaryConvertError;
 use std::fmt;
 use std::mem;
@@ -64,6 +65,21 @@ pub struct Executed {
 	pub vm_trace: Option<VMTrace>,
 	/// The state diff, if we traced it.
 	pub state_diff: Option<StateDiff>,
+	/// The execution profile, if we profiled.
+	pub profile: Option<Profile>,
+	/// Why the execution failed, if it did. Its state changes are then reverted.
+	pub exception: Option<ExecutionException>,
+}
+
+/// Reason the execution of a transaction failed.
+#[derive(Debug, PartialEq, Clone, Copy, Binary)]
+pub enum ExecutionException {
+	/// Ran out of gas. All the gas was used.
+	OutOfGas,
+	/// Stopped on `REVERT`. The gas left was refunded and `output` holds the data reverted with.
+	Reverted,
+	/// Failed otherwise, e.g. on a bad jump destination or instruction. All the gas was used.
+	Failed,
 }
 
 /// Result of executing the transaction.
@@ -133,5 +149,26 @@ impl fmt::Display for ExecutionError {
 	}
 }
 
+/// Reason a transaction of the chain could not be replayed.
+#[derive(PartialEq, Debug, Binary)]
+pub enum ReplayError {
+	/// The transaction is not in the chain.
+	TransactionNotFound,
+	/// The state the transaction's block was built on has been pruned.
+	StatePruned,
+	/// Executing the transaction, or one before it in its block, failed.
+	Execution(ExecutionError),
+}
+
+impl fmt::Display for ReplayError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ReplayError::TransactionNotFound => write!(f, "Transaction not found."),
+			ReplayError::StatePruned => write!(f, "State of the transaction's parent block has been pruned."),
+			ReplayError::Execution(ref e) => write!(f, "{}", e),
+		}
+	}
+}
+
 /// Transaction execution result.
 pub type ExecutionResult = Result<Executed, ExecutionError>;
@@ -0,0 +1,43 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Execution profile types.
+
+use std::mem;
+use std::collections::{VecDeque, BTreeMap};
+use util::numbers::*;
+use ipc::binary::BinaryConvertError;
+
+/// Execution statistics of one instruction within some code.
+#[derive(Debug, PartialEq, Clone, Binary)]
+pub struct InstructionProfile {
+	/// The instruction.
+	pub instruction: u8,
+	/// Number of times it was executed.
+	pub count: u64,
+	/// Total gas it cost.
+	pub gas: U256,
+	/// Total wall time spent executing it, in nanoseconds. For instructions which run other code
+	/// (calls and creates), the time spent in that code is left out.
+	pub time_ns: u64,
+}
+
+/// Per-instruction execution statistics of a transaction, by hash of the code executed.
+#[derive(Debug, PartialEq, Clone, Default, Binary)]
+pub struct Profile {
+	/// Statistics of the executed instructions of each code, ordered by instruction.
+	pub code: BTreeMap<H256, Vec<InstructionProfile>>,
+}
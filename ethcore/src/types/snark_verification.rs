@@ -0,0 +1,42 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline zkSNARK verification result types.
+
+use std::mem;
+use ipc::binary::BinaryConvertError;
+use std::collections::VecDeque;
+use util::numbers::U256;
+
+/// Outcome of verifying a proof with the zkSNARK builtin.
+#[derive(Debug, Clone, PartialEq, Binary)]
+pub enum SnarkVerificationResult {
+	/// The proof is valid for the given verification key and primary input.
+	Verified,
+	/// The arguments were decoded, but the proof is not valid.
+	Rejected,
+	/// The builtin could not process the arguments.
+	Error(String),
+}
+
+/// Result of verifying a proof with the zkSNARK builtin without making a transaction.
+#[derive(Debug, Clone, PartialEq, Binary)]
+pub struct SnarkVerification {
+	/// Outcome of the verification.
+	pub result: SnarkVerificationResult,
+	/// Gas the builtin charges for the call, if the chain has a zkSNARK builtin.
+	pub gas: Option<U256>,
+}
@@ -263,6 +263,10 @@ pub enum Res {
 	FailedCall,
 	/// Failed create.
 	FailedCreate,
+	/// Call to a builtin which failed on its input, with the reason given by the builtin.
+	FailedBuiltinCall(String),
+	/// Call or create which stopped on `REVERT`, with the gas it used and the data it reverted with.
+	Reverted(CallResult),
 }
 
 impl Encodable for Res {
@@ -285,6 +289,16 @@ impl Encodable for Res {
 			Res::FailedCreate => {
 				s.begin_list(1);
 				s.append(&3u8);
+			},
+			Res::FailedBuiltinCall(ref reason) => {
+				s.begin_list(2);
+				s.append(&4u8);
+				s.append(reason);
+			},
+			Res::Reverted(ref reverted) => {
+				s.begin_list(2);
+				s.append(&5u8);
+				s.append(reverted);
 			}
 		}
 	}
@@ -299,6 +313,8 @@ impl Decodable for Res {
 			1 => d.val_at(1).map(Res::Create),
 			2 => Ok(Res::FailedCall),
 			3 => Ok(Res::FailedCreate),
+			4 => d.val_at(1).map(Res::FailedBuiltinCall),
+			5 => d.val_at(1).map(Res::Reverted),
 			_ => Err(DecoderError::Custom("Invalid result type.")),
 		}
 	}
@@ -30,6 +30,9 @@ use ipc::binary::BinaryConvertError;
 use std::mem;
 use std::collections::VecDeque;
 
+/// The largest chain id whose replay-protected `v` fits in a `u64` (EIP-155).
+pub const MAX_CHAIN_ID: u64 = (::std::u64::MAX - 36) / 2;
+
 #[derive(Debug, Clone, PartialEq, Eq, Binary)]
 /// Transaction action type.
 pub enum Action {
@@ -74,9 +77,10 @@ pub struct Transaction {
 }
 
 impl Transaction {
-	/// Append object with a without signature into RLP stream
-	pub fn rlp_append_unsigned_transaction(&self, s: &mut RlpStream) {
-		s.begin_list(6);
+	/// Append object with a without signature into RLP stream; replay-protected transactions
+	/// additionally commit to `chain_id`.
+	pub fn rlp_append_unsigned_transaction(&self, s: &mut RlpStream, chain_id: Option<u64>) {
+		s.begin_list(if chain_id.is_some() { 9 } else { 6 });
 		s.append(&self.nonce);
 		s.append(&self.gas_price);
 		s.append(&self.gas);
@@ -86,6 +90,11 @@ impl Transaction {
 		};
 		s.append(&self.value);
 		s.append(&self.data);
+		if let Some(chain_id) = chain_id {
+			s.append(&chain_id);
+			s.append(&0u8);
+			s.append(&0u8);
+		}
 	}
 }
 
@@ -102,7 +111,7 @@ impl From<ethjson::state::Transaction> for SignedTransaction {
 			},
 			value: t.value.into(),
 			data: t.data.into(),
-		}.sign(&t.secret.into())
+		}.sign(&t.secret.into(), None)
 	}
 }
 
@@ -131,27 +140,33 @@ impl From<ethjson::transaction::Transaction> for SignedTransaction {
 }
 
 impl Transaction {
-	/// The message hash of the transaction.
-	pub fn hash(&self) -> H256 {
+	/// The message hash of the transaction, to be signed for the chain `chain_id` if given (EIP-155).
+	pub fn hash(&self, chain_id: Option<u64>) -> H256 {
 		let mut stream = RlpStream::new();
-		self.rlp_append_unsigned_transaction(&mut stream);
+		self.rlp_append_unsigned_transaction(&mut stream, chain_id);
 		stream.out().sha3()
 	}
 
-	/// Signs the transaction as coming from `sender`.
-	pub fn sign(self, secret: &Secret) -> SignedTransaction {
-		let sig = ec::sign(secret, &self.hash()).unwrap();
-		self.with_signature(sig)
+	/// Signs the transaction as coming from `sender`, replay-protected if `chain_id` is given.
+	pub fn sign(self, secret: &Secret, chain_id: Option<u64>) -> SignedTransaction {
+		let sig = ec::sign(secret, &self.hash(chain_id)).unwrap();
+		self.with_signature(sig, chain_id)
 	}
 
-	/// Signs the transaction with signature.
-	pub fn with_signature(self, sig: H520) -> SignedTransaction {
+	/// Signs the transaction with signature, which must be of `hash(chain_id)`.
+	///
+	/// Panics if `chain_id` is greater than `MAX_CHAIN_ID`.
+	pub fn with_signature(self, sig: H520, chain_id: Option<u64>) -> SignedTransaction {
 		let (r, s, v) = sig.to_rsv();
+		let v = match chain_id {
+			Some(n) => n.checked_mul(2).and_then(|n| n.checked_add(35 + v as u64)).expect("chain id is greater than MAX_CHAIN_ID"),
+			None => 27 + v as u64,
+		};
 		SignedTransaction {
 			unsigned: self,
 			r: r,
 			s: s,
-			v: v + 27,
+			v: v,
 			hash: Cell::new(None),
 			sender: Cell::new(None),
 		}
@@ -201,8 +216,9 @@ impl Transaction {
 pub struct SignedTransaction {
 	/// Plain Transaction.
 	unsigned: Transaction,
-	/// The V field of the signature, either 27 or 28; helps describe the point on the curve.
-	v: u8,
+	/// The V field of the signature; either 27 or 28, or `chain_id * 2 + 35` or `+ 36` for
+	/// replay-protected transactions (EIP-155); helps describe the point on the curve.
+	v: u64,
 	/// The R field of the signature; helps describe the point on the curve.
 	r: U256,
 	/// The S field of the signature; helps describe the point on the curve.
@@ -286,8 +302,23 @@ impl SignedTransaction {
 		}
 	}
 
-	/// 0 is `v` is 27, 1 if 28, and 4 otherwise.
-	pub fn standard_v(&self) -> u8 { match self.v { 27 => 0, 28 => 1, _ => 4 } }
+	/// 0 is `v` is 27, 1 if 28, the same for replay-protected transactions, and 4 otherwise.
+	pub fn standard_v(&self) -> u8 {
+		match self.v {
+			27 => 0,
+			28 => 1,
+			v if v >= 35 => ((v - 1) % 2) as u8,
+			_ => 4,
+		}
+	}
+
+	/// The chain this transaction is replay-protected for, if any (EIP-155).
+	pub fn chain_id(&self) -> Option<u64> {
+		match self.v {
+			v if v >= 35 => Some((v - 35) / 2),
+			_ => None,
+		}
+	}
 
 	/// Construct a signature object from the sig.
 	pub fn signature(&self) -> Signature { Signature::from_rsv(&From::from(&self.r), &From::from(&self.s), self.standard_v()) }
@@ -307,7 +338,7 @@ impl SignedTransaction {
 		match sender {
 			Some(s) => Ok(s),
 			None => {
-				let s = Address::from(try!(ec::recover(&self.signature(), &self.unsigned.hash())).sha3());
+				let s = Address::from(try!(ec::recover(&self.signature(), &self.unsigned.hash(self.chain_id()))).sha3());
 				self.sender.set(Some(s));
 				Ok(s)
 			}
@@ -376,10 +407,70 @@ fn signing() {
 		gas: U256::from(50_000),
 		value: U256::from(1),
 		data: b"Hello!".to_vec()
-	}.sign(&key.secret());
+	}.sign(&key.secret(), None);
 	assert_eq!(Address::from(key.public().sha3()), t.sender().unwrap());
 }
 
+#[test]
+fn signing_with_chain_id() {
+	let key = ::util::crypto::KeyPair::create().unwrap();
+	let t = Transaction {
+		action: Action::Create,
+		nonce: U256::from(42),
+		gas_price: U256::from(3000),
+		gas: U256::from(50_000),
+		value: U256::from(1),
+		data: b"Hello!".to_vec()
+	}.sign(&key.secret(), Some(69));
+	assert_eq!(t.chain_id(), Some(69));
+	assert!(t.v == 173 || t.v == 174);
+	assert_eq!(Address::from(key.public().sha3()), t.sender().unwrap());
+
+	let t: SignedTransaction = decode(&encode(&t));
+	assert_eq!(t.chain_id(), Some(69));
+	assert_eq!(Address::from(key.public().sha3()), t.sender().unwrap());
+}
+
+#[test]
+fn signing_with_extreme_chain_ids() {
+	let key = ::util::crypto::KeyPair::create().unwrap();
+	let t = Transaction {
+		action: Action::Create,
+		nonce: U256::from(42),
+		gas_price: U256::from(3000),
+		gas: U256::from(50_000),
+		value: U256::from(1),
+		data: b"Hello!".to_vec()
+	};
+
+	for &chain_id in &[0, MAX_CHAIN_ID] {
+		let t = t.clone().sign(&key.secret(), Some(chain_id));
+		assert_eq!(t.chain_id(), Some(chain_id));
+		assert_eq!(Address::from(key.public().sha3()), t.sender().unwrap());
+	}
+}
+
+#[test]
+#[should_panic]
+fn signing_with_too_large_chain_id() {
+	let key = ::util::crypto::KeyPair::create().unwrap();
+	Transaction {
+		action: Action::Create,
+		nonce: U256::from(42),
+		gas_price: U256::from(3000),
+		gas: U256::from(50_000),
+		value: U256::from(1),
+		data: b"Hello!".to_vec()
+	}.sign(&key.secret(), Some(::std::u64::MAX / 2));
+}
+
+#[test]
+fn should_agree_with_eip155_test_vector() {
+	let t: SignedTransaction = decode(&::rustc_serialize::hex::FromHex::from_hex("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap());
+	assert_eq!(t.chain_id(), Some(1));
+	assert_eq!(t.sender().unwrap(), address_from_hex("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"));
+}
+
 #[test]
 fn fake_signing() {
 	let t = Transaction {
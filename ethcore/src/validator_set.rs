@@ -0,0 +1,250 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sets of validators for the authority engines, either fixed in the spec or read from
+//! a contract at the parent of each block, possibly changing at given block numbers.
+
+use std::sync::Weak;
+use common::*;
+use ethabi;
+use ethabi::spec::ParamType;
+use ethabi::Token;
+use ethjson;
+use client::{Client, BlockID};
+use zksnark::selector;
+
+/// Signature of the function returning the validators of a validator contract.
+pub const GET_VALIDATORS: &'static str = "getValidators()";
+
+/// A set of validators, which may change from block to block.
+pub trait ValidatorSet: Send + Sync {
+	/// The validators, in order, of block `number` built on top of block `parent`.
+	fn validators(&self, parent: &H256, number: BlockNumber) -> Vec<Address>;
+
+	/// Whether `address` is a validator of block `number` built on top of block `parent`.
+	fn contains(&self, parent: &H256, number: BlockNumber, address: &Address) -> bool {
+		self.validators(parent, number).contains(address)
+	}
+
+	/// Gives the set access to the chain it validates; needed by sets which read the state.
+	fn register_client(&self, _client: Weak<Client>) {}
+}
+
+/// Create the validator set described by a spec.
+pub fn new_validator_set(spec: ethjson::spec::ValidatorSet) -> Box<ValidatorSet> {
+	match spec {
+		ethjson::spec::ValidatorSet::List(list) => Box::new(SimpleList::new(list.into_iter().map(Into::into).collect())),
+		ethjson::spec::ValidatorSet::Contract(address) => Box::new(ValidatorContract::new(address.into())),
+		ethjson::spec::ValidatorSet::Multi(sets) => Box::new(Multi::new(sets.into_iter().map(|(number, set)| (number.into(), new_validator_set(set))).collect())),
+	}
+}
+
+/// A validator set fixed in the spec.
+pub struct SimpleList {
+	validators: Vec<Address>,
+}
+
+impl SimpleList {
+	/// Create a new fixed validator set.
+	pub fn new(validators: Vec<Address>) -> Self {
+		SimpleList { validators: validators }
+	}
+}
+
+impl ValidatorSet for SimpleList {
+	fn validators(&self, _parent: &H256, _number: BlockNumber) -> Vec<Address> {
+		self.validators.clone()
+	}
+
+	fn contains(&self, _parent: &H256, _number: BlockNumber, address: &Address) -> bool {
+		self.validators.contains(address)
+	}
+}
+
+/// A validator set read from `getValidators()` of a contract, so that validators can be
+/// added and removed with transactions. If the contract cannot be read, there are no validators.
+pub struct ValidatorContract {
+	address: Address,
+	client: RwLock<Option<Weak<Client>>>,
+	/// Validators after the most recently queried block.
+	cache: RwLock<Option<(H256, Vec<Address>)>>,
+}
+
+impl ValidatorContract {
+	/// Create a new validator set read from the contract at `address`.
+	pub fn new(address: Address) -> Self {
+		ValidatorContract {
+			address: address,
+			client: RwLock::new(None),
+			cache: RwLock::new(None),
+		}
+	}
+
+	fn query(&self, parent: &H256) -> Result<Vec<Address>, String> {
+		let client = try!(self.client.read().as_ref().and_then(|c| c.upgrade()).ok_or_else(|| "no client registered".to_owned()));
+		let output = try!(client.call_contract(BlockID::Hash(parent.clone()), self.address.clone(), selector(GET_VALIDATORS).to_vec()));
+		match ethabi::Decoder::decode(&[ParamType::Array(Box::new(ParamType::Address))], output).map(|mut t| t.pop()) {
+			Ok(Some(Token::Array(validators))) => validators.into_iter().map(|v| match v {
+				Token::Address(a) => Ok(Address::from_slice(&a)),
+				_ => Err("invalid validator".to_owned()),
+			}).collect(),
+			_ => Err("invalid getValidators() output".to_owned()),
+		}
+	}
+}
+
+impl ValidatorSet for ValidatorContract {
+	fn validators(&self, parent: &H256, _number: BlockNumber) -> Vec<Address> {
+		if let Some((ref hash, ref validators)) = *self.cache.read() {
+			if hash == parent {
+				return validators.clone();
+			}
+		}
+
+		match self.query(parent) {
+			Ok(validators) => {
+				*self.cache.write() = Some((parent.clone(), validators.clone()));
+				validators
+			},
+			// every node must reach the same answer for a failed call, whatever it queried before.
+			Err(e) => {
+				warn!(target: "engine", "Could not read validators from contract {} after block {}: {}", self.address, parent, e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		*self.client.write() = Some(client);
+	}
+}
+
+/// Validator sets which take over from each other at given block numbers.
+pub struct Multi {
+	sets: BTreeMap<BlockNumber, Box<ValidatorSet>>,
+}
+
+impl Multi {
+	/// Create a new validator set, using each set from its block number on.
+	pub fn new(sets: BTreeMap<BlockNumber, Box<ValidatorSet>>) -> Self {
+		if !sets.contains_key(&0) {
+			warn!(target: "engine", "Multi validator set does not start at block 0; earlier blocks have no validators");
+		}
+		Multi { sets: sets }
+	}
+
+	/// The set applying to block `number`: the one with the latest transition not after it.
+	fn correct_set(&self, number: BlockNumber) -> Option<&ValidatorSet> {
+		self.sets.iter().rev().find(|&(first, _)| *first <= number).map(|(_, set)| &**set)
+	}
+}
+
+impl ValidatorSet for Multi {
+	fn validators(&self, parent: &H256, number: BlockNumber) -> Vec<Address> {
+		self.correct_set(number).map_or_else(Vec::new, |set| set.validators(parent, number))
+	}
+
+	fn contains(&self, parent: &H256, number: BlockNumber, address: &Address) -> bool {
+		self.correct_set(number).map_or(false, |set| set.contains(parent, number, address))
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		for set in self.sets.values() {
+			set.register_client(client.clone());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use super::*;
+	use client::{BlockChainClient, Client, ClientConfig};
+	use miner::{Miner, MinerService};
+	use spec::Spec;
+	use executive::contract_address;
+	use devtools::RandomTempPath;
+
+	#[test]
+	fn simple_list_contains_validators() {
+		let set = SimpleList::new(vec![Address::from(1), Address::from(2)]);
+		assert!(set.contains(&H256::new(), 1, &Address::from(2)));
+		assert!(!set.contains(&H256::new(), 1, &Address::from(3)));
+		assert_eq!(set.validators(&H256::new(), 1), vec![Address::from(1), Address::from(2)]);
+	}
+
+	#[test]
+	fn contract_without_client_has_no_validators() {
+		let set = ValidatorContract::new(Address::from(0x1000));
+		assert!(set.validators(&H256::new(), 1).is_empty());
+	}
+
+	#[test]
+	fn contract_validators_are_read_at_the_parent() {
+		let dir = RandomTempPath::new();
+		let miner = Arc::new(Miner::with_spec(Spec::new_instant()));
+		let client = Client::new(ClientConfig::default(), Spec::new_instant(), dir.as_path(), miner.clone(), IoChannel::disconnected()).unwrap();
+		let genesis = client.chain_info().best_block_hash;
+
+		// deploy a contract returning `[validator]` from any call
+		let validator = Address::from_str("9cce34f7ab185c7aba1b7c8140d620b4bda941d6").unwrap();
+		let transaction = Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: "602780600b6000396000f360206000526001602052739cce34f7ab185c7aba1b7c8140d620b4bda941d660405260606000f3".from_hex().unwrap(),
+			gas: U256::from(1_000_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(&"".sha3(), None);
+		miner.import_own_transaction(client.deref(), transaction).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		let parent = client.chain_info().best_block_hash;
+		assert!(parent != genesis);
+
+		let set = ValidatorContract::new(contract_address(&validator, &U256::zero()));
+		set.register_client(Arc::downgrade(&client));
+		assert_eq!(set.validators(&parent, 2), vec![validator.clone()]);
+		assert!(set.contains(&parent, 2, &validator));
+
+		// before the contract was deployed it cannot be read, even though validators were read after it
+		assert!(set.validators(&genesis, 1).is_empty());
+		assert!(!set.contains(&genesis, 1, &validator));
+		assert_eq!(set.validators(&parent, 2), vec![validator]);
+	}
+
+	#[test]
+	fn multi_switches_sets_at_transitions() {
+		let mut sets: BTreeMap<BlockNumber, Box<ValidatorSet>> = BTreeMap::new();
+		sets.insert(0, Box::new(SimpleList::new(vec![Address::from(1)])));
+		sets.insert(10, Box::new(SimpleList::new(vec![Address::from(2), Address::from(3)])));
+		let set = Multi::new(sets);
+		assert_eq!(set.validators(&H256::new(), 9), vec![Address::from(1)]);
+		assert_eq!(set.validators(&H256::new(), 10), vec![Address::from(2), Address::from(3)]);
+		assert!(set.contains(&H256::new(), 1, &Address::from(1)));
+		assert!(!set.contains(&H256::new(), 11, &Address::from(1)));
+		assert!(set.contains(&H256::new(), 11, &Address::from(3)));
+	}
+
+	#[test]
+	fn multi_without_set_has_no_validators() {
+		let mut sets: BTreeMap<BlockNumber, Box<ValidatorSet>> = BTreeMap::new();
+		sets.insert(5, Box::new(SimpleList::new(vec![Address::from(1)])));
+		let set = Multi::new(sets);
+		assert!(set.validators(&H256::new(), 4).is_empty());
+		assert!(!set.contains(&H256::new(), 4, &Address::from(1)));
+	}
+}
@@ -58,7 +58,7 @@ impl<'a> TransactionView<'a> {
 	pub fn data(&self) -> Bytes { self.rlp.val_at(5) }
 
 	/// Get the v field of the transaction.
-	pub fn v(&self) -> u8 { let r: u16 = self.rlp.val_at(6); r as u8 }
+	pub fn v(&self) -> u64 { self.rlp.val_at(6) }
 
 	/// Get the r field of the transaction.
 	pub fn r(&self) -> U256 { self.rlp.val_at(7) }
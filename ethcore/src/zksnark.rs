@@ -0,0 +1,653 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! zkSNARK verification shared by the zkSNARK builtins.
+//!
+//! The `zkSNARK` builtin verifies with libsnark itself and takes keys and proofs as libsnark
+//! serializes them. The other zkSNARK builtins verify PGHR13 proofs (those of libsnark's
+//! `r1cs_ppzksnark`) on the alt_bn128 curve natively, with points encoded as for the alt_bn128
+//! builtins: a G1 point as its two 32-byte coordinates, a G2 point as its four 32-byte
+//! coordinates, imaginary parts first.
+//!
+//! - A verification key is `A` (G2), `B` (G1), `C` (G2), `gamma` (G2), `gammaBeta1` (G1),
+//!   `gammaBeta2` (G2) and `Z` (G2), followed by one G1 point `IC` per primary input element plus one.
+//! - A proof is `A`, `A'`, `B` (G2), `B'`, `C`, `C'`, `K` and `H`, all on G1 but `B`.
+//! - A primary input is a sequence of 32-byte scalars.
+//!
+//! Verification keys may be registered once with the registry builtin, which keeps them
+//! in the storage of its own account keyed by their hash. Later calls refer to a key by
+//! that hash; keys read from storage are kept parsed in an in-memory cache.
+
+use util::*;
+use bn::{self, Group};
+use ethabi;
+use ethabi::spec::ParamType;
+use ethabi::Token;
+use hackishlibsnarkbindings;
+use builtin::{Builtin, BuiltinError, BuiltinStorage, copy_to, read_fr, read_g1, read_g2};
+use types::snark_verification::{SnarkVerification, SnarkVerificationResult};
+use header::BlockNumber;
+
+/// Name under which the zkSNARK builtin is registered.
+pub const BUILTIN_NAME: &'static str = "zkSNARK";
+/// Name under which the native PGHR13 zkSNARK builtin is registered.
+pub const PGHR13_BUILTIN_NAME: &'static str = "zkSNARK_pghr13";
+/// Signature of the function called on the zkSNARK builtins.
+pub const VERIFY: &'static str = "verify(bytes,bytes,bytes)";
+/// Signature of the function called on the batch zkSNARK builtin.
+pub const VERIFY_BATCH: &'static str = "verifyBatch(bytes,bytes[],bytes[])";
+/// Maximum number of proofs verified by one batch call: one bit of the 32-byte result each.
+pub const MAX_BATCH: usize = 256;
+/// Signature of the registry function storing a verification key.
+pub const REGISTER_VK: &'static str = "registerVk(bytes)";
+/// Signature of the registry function verifying a proof against a registered key.
+pub const VERIFY_REGISTERED: &'static str = "verify(bytes32,bytes,bytes)";
+
+/// Maximum number of verification keys kept in the in-memory cache.
+const VK_CACHE_SIZE: usize = 64;
+/// Gas charged for each storage slot written by a key registration, as for an `SSTORE` of a new value.
+pub const REGISTER_SLOT_GAS: usize = 20000;
+
+const G1_LEN: usize = 64;
+const G2_LEN: usize = 128;
+/// Length of the points of a verification key before its `IC` points.
+const VK_FIXED_LEN: usize = 5 * G2_LEN + 2 * G1_LEN;
+const PROOF_LEN: usize = 7 * G1_LEN + G2_LEN;
+
+lazy_static! {
+	static ref VK_CACHE: Mutex<VkCache> = Mutex::new(VkCache::default());
+}
+
+/// Size-bounded cache of parsed verification keys by hash. Oldest keys are evicted first.
+#[derive(Default)]
+struct VkCache {
+	keys: HashMap<H256, Arc<VerifyingKey>>,
+	order: VecDeque<H256>,
+}
+
+impl VkCache {
+	fn get(&self, hash: &H256) -> Option<Arc<VerifyingKey>> {
+		self.keys.get(hash).cloned()
+	}
+
+	fn insert(&mut self, hash: H256, vk: Arc<VerifyingKey>) {
+		if self.keys.insert(hash.clone(), vk).is_none() {
+			self.order.push_back(hash);
+		}
+		while self.order.len() > VK_CACHE_SIZE {
+			let oldest = self.order.pop_front().expect("order.len() > VK_CACHE_SIZE; qed");
+			self.keys.remove(&oldest);
+		}
+	}
+}
+
+/// Verification key parsed from its encoding, ready to check proofs against.
+/// Parsing checks that every point is on the curve, so it is done once per key.
+#[derive(PartialEq)]
+pub struct VerifyingKey {
+	a: bn::G2,
+	b: bn::G1,
+	c: bn::G2,
+	gamma: bn::G2,
+	gamma_beta_1: bn::G1,
+	gamma_beta_2: bn::G2,
+	z: bn::G2,
+	ic: Vec<bn::G1>,
+}
+
+struct Proof {
+	a: bn::G1,
+	a_p: bn::G1,
+	b: bn::G2,
+	b_p: bn::G1,
+	c: bn::G1,
+	c_p: bn::G1,
+	k: bn::G1,
+	h: bn::G1,
+}
+
+impl Proof {
+	fn from_bytes(proof: &[u8]) -> Result<Self, BuiltinError> {
+		if proof.len() != PROOF_LEN {
+			return Err(BuiltinError("invalid proof length"));
+		}
+		let mut reader = proof;
+		Ok(Proof {
+			a: try!(read_g1(&mut reader)),
+			a_p: try!(read_g1(&mut reader)),
+			b: try!(read_g2(&mut reader)),
+			b_p: try!(read_g1(&mut reader)),
+			c: try!(read_g1(&mut reader)),
+			c_p: try!(read_g1(&mut reader)),
+			k: try!(read_g1(&mut reader)),
+			h: try!(read_g1(&mut reader)),
+		})
+	}
+}
+
+/// Whether the product of the pairings of `pairs` is one.
+fn pairing_product_is_one(pairs: &[(bn::G1, bn::G2)]) -> bool {
+	pairs.iter().fold(bn::Gt::one(), |acc, &(a, b)| acc * bn::pairing(a, b)) == bn::Gt::one()
+}
+
+impl VerifyingKey {
+	/// Parse a verification key from its encoding.
+	pub fn from_bytes(vk: &[u8]) -> Result<Self, BuiltinError> {
+		if vk.len() < VK_FIXED_LEN + G1_LEN || (vk.len() - VK_FIXED_LEN) % G1_LEN != 0 {
+			return Err(BuiltinError("invalid verification key length"));
+		}
+		let mut reader = vk;
+		let a = try!(read_g2(&mut reader));
+		let b = try!(read_g1(&mut reader));
+		let c = try!(read_g2(&mut reader));
+		let gamma = try!(read_g2(&mut reader));
+		let gamma_beta_1 = try!(read_g1(&mut reader));
+		let gamma_beta_2 = try!(read_g2(&mut reader));
+		let z = try!(read_g2(&mut reader));
+		let mut ic = Vec::with_capacity(reader.len() / G1_LEN);
+		while !reader.is_empty() {
+			ic.push(try!(read_g1(&mut reader)));
+		}
+
+		Ok(VerifyingKey {
+			a: a,
+			b: b,
+			c: c,
+			gamma: gamma,
+			gamma_beta_1: gamma_beta_1,
+			gamma_beta_2: gamma_beta_2,
+			z: z,
+			ic: ic,
+		})
+	}
+
+	/// Verify `proof` of knowledge of a witness for `primary_input`.
+	/// Proofs and primary inputs which cannot be decoded do not verify.
+	pub fn verify(&self, proof: &[u8], primary_input: &[u8]) -> bool {
+		match (Proof::from_bytes(proof), self.input_point(primary_input)) {
+			(Ok(proof), Some(x)) => self.check(&proof, x),
+			_ => false,
+		}
+	}
+
+	/// `IC[0] + sum(input[i] * IC[i + 1])`, or `None` if `primary_input` does not match the key.
+	fn input_point(&self, primary_input: &[u8]) -> Option<bn::G1> {
+		if primary_input.len() != (self.ic.len() - 1) * 32 {
+			return None;
+		}
+		let mut point = self.ic[0];
+		for (mut chunk, ic) in primary_input.chunks(32).zip(self.ic[1..].iter()) {
+			match read_fr(&mut chunk) {
+				Ok(scalar) => point = point + *ic * scalar,
+				Err(_) => return None,
+			}
+		}
+		Some(point)
+	}
+
+	fn check(&self, proof: &Proof, x: bn::G1) -> bool {
+		let g2 = bn::G2::one();
+		pairing_product_is_one(&[(proof.a, self.a), (-proof.a_p, g2)])
+			&& pairing_product_is_one(&[(self.b, proof.b), (-proof.b_p, g2)])
+			&& pairing_product_is_one(&[(proof.c, self.c), (-proof.c_p, g2)])
+			&& pairing_product_is_one(&[(proof.k, self.gamma), (-(x + proof.a + proof.c), self.gamma_beta_2), (-self.gamma_beta_1, proof.b)])
+			&& pairing_product_is_one(&[(x + proof.a, proof.b), (-proof.h, self.z), (-proof.c, g2)])
+	}
+}
+
+/// Verify `proof` of knowledge of a witness for `primary_input` against the verification key `vk` with libsnark,
+/// all of them serialized as libsnark does.
+fn libsnark_verify(vk: &[u8], proof: &[u8], primary_input: &[u8]) -> bool {
+	hackishlibsnarkbindings::snark_verify(vk, proof, primary_input)
+}
+
+/// Verify `proof` of knowledge of a witness for `primary_input` against the encoded verification key `vk`.
+pub fn verify(vk: &[u8], proof: &[u8], primary_input: &[u8]) -> Result<bool, BuiltinError> {
+	VerifyingKey::from_bytes(vk).map(|vk| vk.verify(proof, primary_input))
+}
+
+/// First four bytes of the hash of a function signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+	let hash = signature.as_bytes().sha3();
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Decode the ABI-encoded arguments following the function selector.
+fn decode_args(input: &[u8], types: &[ParamType]) -> Result<Vec<Token>, BuiltinError> {
+	if input.len() < 4 {
+		return Err(BuiltinError("input too short"));
+	}
+	let tokens = try!(ethabi::Decoder::decode(types, input[4..].to_vec()).map_err(|_| BuiltinError("invalid ABI encoding")));
+	match tokens.len() == types.len() {
+		true => Ok(tokens),
+		false => Err(BuiltinError("invalid ABI encoding")),
+	}
+}
+
+/// Decode the arguments `(bytes vk, bytes proof, bytes input)` of a zkSNARK builtin call.
+pub fn decode_verify_args(input: &[u8]) -> Result<(Bytes, Bytes, Bytes), BuiltinError> {
+	let mut tokens = try!(decode_args(input, &[ParamType::Bytes, ParamType::Bytes, ParamType::Bytes])).into_iter();
+	match (tokens.next(), tokens.next(), tokens.next()) {
+		(Some(Token::Bytes(vk)), Some(Token::Bytes(proof)), Some(Token::Bytes(primary_input))) => Ok((vk, proof, primary_input)),
+		_ => Err(BuiltinError("invalid ABI encoding")),
+	}
+}
+
+/// Decode the arguments `(bytes32 vkHash, bytes proof, bytes input)` of a registry verification call.
+fn decode_verify_registered_args(input: &[u8]) -> Result<(H256, Bytes, Bytes), BuiltinError> {
+	let mut tokens = try!(decode_args(input, &[ParamType::FixedBytes(32), ParamType::Bytes, ParamType::Bytes])).into_iter();
+	match (tokens.next(), tokens.next(), tokens.next()) {
+		(Some(Token::FixedBytes(ref hash)), Some(Token::Bytes(proof)), Some(Token::Bytes(primary_input))) if hash.len() == 32 =>
+			Ok((H256::from_slice(hash), proof, primary_input)),
+		_ => Err(BuiltinError("invalid ABI encoding")),
+	}
+}
+
+/// Decode the arguments `(bytes vk, bytes[] proofs, bytes[] inputs)` of a batch zkSNARK builtin call
+/// into the verification key and the (proof, primary input) pairs to check against it.
+pub fn decode_batch_args(input: &[u8]) -> Result<(Bytes, Vec<(Bytes, Bytes)>), BuiltinError> {
+	let bytes_array = || ParamType::Array(Box::new(ParamType::Bytes));
+	let mut tokens = try!(decode_args(input, &[ParamType::Bytes, bytes_array(), bytes_array()])).into_iter();
+	let (vk, proofs, inputs) = match (tokens.next(), tokens.next(), tokens.next()) {
+		(Some(Token::Bytes(vk)), Some(Token::Array(proofs)), Some(Token::Array(inputs))) => (vk, proofs, inputs),
+		_ => return Err(BuiltinError("invalid ABI encoding")),
+	};
+	if proofs.len() != inputs.len() {
+		return Err(BuiltinError("proof and input counts differ"));
+	}
+	if proofs.len() > MAX_BATCH {
+		return Err(BuiltinError("too many proofs"));
+	}
+
+	let mut pairs = Vec::with_capacity(proofs.len());
+	for (proof, primary_input) in proofs.into_iter().zip(inputs.into_iter()) {
+		match (proof, primary_input) {
+			(Token::Bytes(proof), Token::Bytes(primary_input)) => pairs.push((proof, primary_input)),
+			_ => return Err(BuiltinError("invalid ABI encoding")),
+		}
+	}
+	Ok((vk, pairs))
+}
+
+/// Length of the verification key, number of proofs and total length of the primary inputs
+/// of a batch zkSNARK builtin call, or `None` if it cannot be decoded.
+/// Only the head words and declared lengths are read; nothing is copied.
+pub fn batch_sizes(input: &[u8]) -> Option<(usize, usize, usize)> {
+	if input.len() < 4 {
+		return None;
+	}
+	// skip the function selector
+	let args = &input[4..];
+	let proofs_count = read_usize(args, 32).and_then(|offset| read_usize(args, offset));
+	let (vk_len, proofs, inputs_offset) = match (arg_len(input, 0), proofs_count, read_usize(args, 64)) {
+		(Some(vk_len), Some(proofs), Some(inputs_offset)) => (vk_len, proofs, inputs_offset),
+		_ => return None,
+	};
+	match read_usize(args, inputs_offset) {
+		Some(inputs) if inputs == proofs && inputs <= MAX_BATCH => {},
+		_ => return None,
+	}
+
+	// element offsets are relative to the start of the array's contents, after its length word
+	let contents = match inputs_offset.checked_add(32) {
+		Some(contents) => contents,
+		None => return None,
+	};
+	let mut input_len = 0usize;
+	for i in 0..proofs {
+		let len = read_usize(args, contents + i * 32)
+			.and_then(|offset| contents.checked_add(offset))
+			.and_then(|at| read_usize(args, at));
+		input_len = match len.and_then(|len| input_len.checked_add(len)) {
+			Some(input_len) => input_len,
+			None => return None,
+		};
+	}
+	Some((vk_len, proofs, input_len))
+}
+
+/// Reads a 32-byte big-endian word at `offset` of `data` as `usize`.
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+	match offset.checked_add(32) {
+		Some(end) if end <= data.len() => {
+			let word = U256::from(&data[offset..end]);
+			match word > U256::from(usize::max_value()) {
+				true => None,
+				false => Some(word.low_u64() as usize),
+			}
+		},
+		_ => None,
+	}
+}
+
+/// Declared length of the `bytes` argument at `index`, read from the ABI head without decoding.
+fn arg_len(input: &[u8], index: usize) -> Option<usize> {
+	if input.len() < 4 {
+		return None;
+	}
+	// skip the function selector
+	let args = &input[4..];
+	read_usize(args, index * 32).and_then(|offset| read_usize(args, offset))
+}
+
+/// Declared lengths of the verification key and primary input of a zkSNARK builtin call.
+pub fn verify_arg_lens(input: &[u8]) -> Option<(usize, usize)> {
+	match (arg_len(input, 0), arg_len(input, 2)) {
+		(Some(vk_len), Some(input_len)) => Some((vk_len, input_len)),
+		_ => None,
+	}
+}
+
+/// Declared lengths of the verification key and primary input of a registry call:
+/// a registration only carries a key, a verification only carries a primary input.
+pub fn registry_arg_lens(input: &[u8]) -> Option<(usize, usize)> {
+	if input.len() < 4 {
+		None
+	} else if input[0..4] == selector(REGISTER_VK) {
+		arg_len(input, 0).map(|vk_len| (vk_len, 0))
+	} else if input[0..4] == selector(VERIFY_REGISTERED) {
+		arg_len(input, 2).map(|input_len| (0, input_len))
+	} else {
+		None
+	}
+}
+
+/// Storage key of the `index`th 32-byte chunk of the verification key with hash `hash`.
+/// The key's length is stored under `hash` itself.
+fn vk_chunk_key(hash: &H256, index: usize) -> H256 {
+	let mut key = hash.to_vec();
+	key.extend_from_slice(&H256::from(U256::from(index)));
+	key.sha3()
+}
+
+/// Number of storage slots a registration of a key of length `vk_len` writes.
+pub fn register_slots(vk_len: usize) -> usize {
+	vk_len / 32 + (vk_len % 32 != 0) as usize + 1
+}
+
+/// Store `vk` in the registry `storage` unless it is already there. Returns its hash.
+/// Only keys which parse are stored.
+pub fn register_vk(storage: &mut BuiltinStorage, vk: &[u8]) -> Result<H256, BuiltinError> {
+	let parsed = try!(VerifyingKey::from_bytes(vk));
+	let hash = vk.sha3();
+	if storage.storage_at(&hash).is_zero() {
+		try!(storage.set_storage(hash.clone(), H256::from(U256::from(vk.len()))));
+		for (index, chunk) in vk.chunks(32).enumerate() {
+			let mut value = H256::new();
+			copy_to(chunk, &mut value);
+			try!(storage.set_storage(vk_chunk_key(&hash, index), value));
+		}
+	}
+	VK_CACHE.lock().insert(hash.clone(), Arc::new(parsed));
+	Ok(hash)
+}
+
+/// Load the parsed verification key with hash `hash` from the registry `storage`.
+/// Fails if no such key has been registered.
+pub fn load_vk(storage: &BuiltinStorage, hash: &H256) -> Result<Arc<VerifyingKey>, BuiltinError> {
+	// storage is the source of truth: a cached key may have been registered on another fork.
+	let len = storage.storage_at(hash);
+	if len.is_zero() {
+		return Err(BuiltinError("unknown verification key"));
+	}
+	if let Some(vk) = VK_CACHE.lock().get(hash) {
+		return Ok(vk);
+	}
+
+	let len = U256::from(len).low_u64() as usize;
+	let mut vk = Vec::with_capacity(len);
+	for index in 0..(len + 31) / 32 {
+		vk.extend_from_slice(&storage.storage_at(&vk_chunk_key(hash, index)));
+	}
+	vk.truncate(len);
+
+	let vk = Arc::new(try!(VerifyingKey::from_bytes(&vk)));
+	VK_CACHE.lock().insert(hash.clone(), vk.clone());
+	Ok(vk)
+}
+
+/// Write a verification result: the output is zeroed and its last byte set on success,
+/// which for 32 bytes of output is an ABI-encoded `bool`.
+fn write_result(verified: bool, output: &mut [u8]) {
+	for o in output.iter_mut() {
+		*o = 0;
+	}
+	if verified {
+		if let Some(last) = output.last_mut() {
+			*last = 1;
+		}
+	}
+}
+
+/// Execute a call to the zkSNARK builtin: `(bytes vk, bytes proof, bytes input)`, verified by libsnark.
+pub fn verify_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let (vk, proof, primary_input) = try!(decode_verify_args(input));
+	write_result(libsnark_verify(&vk, &proof, &primary_input), output);
+	Ok(())
+}
+
+/// Execute a call to the native PGHR13 zkSNARK builtin: `(bytes vk, bytes proof, bytes input)`.
+/// Keys which cannot be decoded are an error; proofs and primary inputs which cannot be decoded do not verify.
+pub fn pghr13_verify_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let (vk, proof, primary_input) = try!(decode_verify_args(input));
+	let verified = try!(verify(&vk, &proof, &primary_input));
+	write_result(verified, output);
+	Ok(())
+}
+
+/// Encode a call to a zkSNARK builtin verifying `proof` for `primary_input` against `vk`.
+pub fn encode_verify_call(vk: &[u8], proof: &[u8], primary_input: &[u8]) -> Bytes {
+	let mut call = selector(VERIFY).to_vec();
+	call.extend(ethabi::Encoder::encode(vec![Token::Bytes(vk.to_vec()), Token::Bytes(proof.to_vec()), Token::Bytes(primary_input.to_vec())]));
+	call
+}
+
+/// Encode a call to the batch zkSNARK builtin verifying each (proof, primary input) of `pairs` against `vk`.
+pub fn encode_batch_call(vk: &[u8], pairs: &[(Bytes, Bytes)]) -> Bytes {
+	let proofs = pairs.iter().map(|&(ref proof, _)| Token::Bytes(proof.clone())).collect();
+	let inputs = pairs.iter().map(|&(_, ref primary_input)| Token::Bytes(primary_input.clone())).collect();
+	let mut call = selector(VERIFY_BATCH).to_vec();
+	call.extend(ethabi::Encoder::encode(vec![Token::Bytes(vk.to_vec()), Token::Array(proofs), Token::Array(inputs)]));
+	call
+}
+
+/// Verify `proof` for `primary_input` against `vk` without making a transaction, by running
+/// the first zkSNARK builtin among `builtins` active at block number `at` exactly as a call to it would.
+/// Without such a builtin the proof is still verified, but no gas is reported.
+pub fn verify_offline(builtins: &BTreeMap<Address, Builtin>, at: BlockNumber, vk: &[u8], proof: &[u8], primary_input: &[u8]) -> SnarkVerification {
+	let call = encode_verify_call(vk, proof, primary_input);
+	let builtin = builtins.values().find(|b| b.name == BUILTIN_NAME && b.is_active(at));
+	let mut output = [0u8; 32];
+	// the zkSNARK builtin keeps no state.
+	let mut storage: HashMap<H256, H256> = HashMap::new();
+
+	let executed = match builtin {
+		Some(b) => b.execute(&call, &mut output, &mut storage),
+		None => verify_exec(&call, &mut output, &mut storage),
+	};
+	let result = match executed {
+		Ok(()) if output[31] == 1 => SnarkVerificationResult::Verified,
+		Ok(()) => SnarkVerificationResult::Rejected,
+		Err(BuiltinError(reason)) => SnarkVerificationResult::Error(reason.to_owned()),
+	};
+
+	SnarkVerification {
+		result: result,
+		gas: builtin.map(|b| b.cost(&call)),
+	}
+}
+
+/// Execute a call to the batch zkSNARK builtin: `(bytes vk, bytes[] proofs, bytes[] inputs)`.
+/// The output is a 256-bit big-endian bitmap whose `i`th lowest bit is set if the `i`th proof verifies.
+pub fn batch_exec(input: &[u8], output: &mut [u8], _storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	let (vk, pairs) = try!(decode_batch_args(input));
+	// the key is shared by the whole batch, so it is parsed only once
+	let vk = try!(VerifyingKey::from_bytes(&vk));
+	let mut bitmap = [0u8; 32];
+	for (i, &(ref proof, ref primary_input)) in pairs.iter().enumerate() {
+		if vk.verify(proof, primary_input) {
+			bitmap[31 - i / 8] |= 1 << (i % 8);
+		}
+	}
+	copy_to(&bitmap, output);
+	Ok(())
+}
+
+/// Execute a call to the verification key registry builtin: either `registerVk(bytes)`,
+/// returning the key's hash, or `verify(bytes32,bytes,bytes)`, returning the verification result.
+pub fn registry_exec(input: &[u8], output: &mut [u8], storage: &mut BuiltinStorage) -> Result<(), BuiltinError> {
+	if input.len() < 4 {
+		return Err(BuiltinError("input too short"));
+	}
+
+	if input[0..4] == selector(REGISTER_VK) {
+		let vk = match try!(decode_args(input, &[ParamType::Bytes])).pop() {
+			Some(Token::Bytes(vk)) => vk,
+			_ => return Err(BuiltinError("invalid ABI encoding")),
+		};
+		let hash = try!(register_vk(storage, &vk));
+		copy_to(&hash, output);
+		Ok(())
+	} else if input[0..4] == selector(VERIFY_REGISTERED) {
+		let (hash, proof, primary_input) = try!(decode_verify_registered_args(input));
+		let vk = try!(load_vk(storage, &hash));
+		write_result(vk.verify(&proof, &primary_input), output);
+		Ok(())
+	} else {
+		Err(BuiltinError("unknown function"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use super::*;
+
+	/// A key for one-element primary inputs, with a proof for the primary input `2`.
+	fn test_vectors() -> (Bytes, Bytes, Bytes) {
+		(
+			include_str!("../res/zksnark/vk.hex").from_hex().unwrap(),
+			include_str!("../res/zksnark/proof.hex").from_hex().unwrap(),
+			include_str!("../res/zksnark/input.hex").from_hex().unwrap(),
+		)
+	}
+
+	#[test]
+	fn verifies_proof() {
+		let (vk, proof, primary_input) = test_vectors();
+		let vk = VerifyingKey::from_bytes(&vk).unwrap();
+		assert!(vk.verify(&proof, &primary_input));
+
+		// another primary input
+		assert!(!vk.verify(&proof, &H256::from(U256::from(3))));
+		// primary input of the wrong length
+		assert!(!vk.verify(&proof, &[]));
+		// another proof
+		let mut other = proof.clone();
+		other.swap(0, 64);
+		assert!(!vk.verify(&other, &primary_input));
+		assert!(!vk.verify(&proof[1..], &primary_input));
+	}
+
+	#[test]
+	fn rejects_malformed_vk() {
+		let (vk, proof, primary_input) = test_vectors();
+		assert_eq!(verify(&vk[1..], &proof, &primary_input), Err(BuiltinError("invalid verification key length")));
+		assert_eq!(verify(&vk[..VK_FIXED_LEN], &proof, &primary_input), Err(BuiltinError("invalid verification key length")));
+
+		let mut off_curve = vk.clone();
+		off_curve[VK_FIXED_LEN + 63] ^= 1;
+		assert_eq!(verify(&off_curve, &proof, &primary_input), Err(BuiltinError("point not on curve")));
+	}
+
+	#[test]
+	fn registers_and_loads_vk() {
+		let mut storage: HashMap<H256, H256> = HashMap::new();
+		let (vk, proof, primary_input) = test_vectors();
+
+		let hash = register_vk(&mut storage, &vk).unwrap();
+		assert_eq!(hash, vk.sha3());
+		assert_eq!(storage.len(), register_slots(vk.len()));
+		let loaded = load_vk(&storage, &hash).unwrap();
+		assert!(*loaded == VerifyingKey::from_bytes(&vk).unwrap());
+		assert!(loaded.verify(&proof, &primary_input));
+
+		// registering again leaves storage untouched
+		let len = storage.len();
+		assert_eq!(register_vk(&mut storage, &vk).unwrap(), hash);
+		assert_eq!(storage.len(), len);
+	}
+
+	#[test]
+	fn unknown_vk_is_not_loaded() {
+		let storage: HashMap<H256, H256> = HashMap::new();
+		assert!(load_vk(&storage, &b"unknown".sha3()).is_err());
+	}
+
+	#[test]
+	fn cached_vk_requires_registration() {
+		let mut storage: HashMap<H256, H256> = HashMap::new();
+		let (vk, _, _) = test_vectors();
+		let hash = register_vk(&mut storage, &vk).unwrap();
+		assert!(load_vk(&storage, &hash).is_ok());
+
+		// e.g. registered on a fork which got reverted
+		let empty: HashMap<H256, H256> = HashMap::new();
+		assert!(load_vk(&empty, &hash).is_err());
+	}
+
+	#[test]
+	fn rejects_invalid_vk() {
+		let mut storage: HashMap<H256, H256> = HashMap::new();
+		assert_eq!(register_vk(&mut storage, &[]), Err(BuiltinError("invalid verification key length")));
+		assert_eq!(register_vk(&mut storage, &[1u8; 40]), Err(BuiltinError("invalid verification key length")));
+		assert!(storage.is_empty());
+	}
+
+	#[test]
+	fn registry_rejects_unknown_function() {
+		let mut storage: HashMap<H256, H256> = HashMap::new();
+		let mut output = [0u8; 32];
+		assert_eq!(registry_exec(&[1, 2, 3, 4], &mut output, &mut storage), Err(BuiltinError("unknown function")));
+		assert_eq!(registry_exec(&[1, 2, 3], &mut output, &mut storage), Err(BuiltinError("input too short")));
+	}
+
+	#[test]
+	fn encodes_verify_call() {
+		let call = encode_verify_call(&[1u8; 40], &[2u8; 8], &[3u8; 64]);
+		assert_eq!(&call[0..4], &selector(VERIFY));
+		assert_eq!(decode_verify_args(&call).unwrap(), (vec![1u8; 40], vec![2u8; 8], vec![3u8; 64]));
+		assert_eq!(verify_arg_lens(&call), Some((40, 64)));
+	}
+
+	#[test]
+	fn registry_arg_lens_by_function() {
+		let mut input = selector(REGISTER_VK).to_vec();
+		input.extend_from_slice(&H256::from(U256::from(0x20)));
+		input.extend_from_slice(&H256::from(U256::from(100)));
+		assert_eq!(registry_arg_lens(&input), Some((100, 0)));
+
+		let mut input = selector(VERIFY_REGISTERED).to_vec();
+		input.extend_from_slice(&H256::new());
+		input.extend_from_slice(&H256::from(U256::from(0x60)));
+		input.extend_from_slice(&H256::from(U256::from(0x80)));
+		input.extend_from_slice(&H256::from(U256::from(0)));
+		input.extend_from_slice(&H256::from(U256::from(64)));
+		assert_eq!(registry_arg_lens(&input), Some((0, 64)));
+
+		assert_eq!(registry_arg_lens(&[0u8; 4]), None);
+	}
+}
@@ -24,15 +24,17 @@
 
 extern crate test;
 extern crate ethcore;
-extern crate evm;
 extern crate ethcore_util;
 extern crate rustc_serialize;
 
 use self::test::{Bencher, black_box};
 
-use evm::run_vm;
 use ethcore::action_params::ActionParams;
-use ethcore_util::{U256, Uint};
+use ethcore::client::{EnvInfo, EvmTestClient};
+use ethcore::ethereum;
+use ethcore::pod_state::PodState;
+use ethcore::trace::NoopVMTracer;
+use ethcore_util::{U256, Uint, Bytes};
 use rustc_serialize::hex::FromHex;
 
 #[bench]
@@ -50,13 +52,7 @@ fn simple_loop(gas: U256, b: &mut Bencher) {
 		"606060405260005b620042408112156019575b6001016007565b600081905550600680602b6000396000f3606060405200".from_hex().unwrap()
 	);
 
-	b.iter(|| {
-		let mut params = ActionParams::default();
-		params.gas = gas;
-		params.code = Some(code.clone());
-
-		run_vm(params)
-	});
+	run(gas, code, b)
 }
 
 #[bench]
@@ -74,12 +70,20 @@ fn rng(gas: U256, b: &mut Bencher) {
 		"6060604052600360056007600b60005b62004240811215607f5767ffe7649d5eca84179490940267f47ed85c4b9a6379019367f8e5dd9a5c994bba9390930267f91d87e4b8b74e55019267ff97f6f3b29cda529290920267f393ada8dd75c938019167fe8d437c45bb3735830267f47d9a7b5428ffec019150600101600f565b838518831882186000555050505050600680609a6000396000f3606060405200".from_hex().unwrap()
 	);
 
+	run(gas, code, b)
+}
+
+
+fn run(gas: U256, code: Bytes, b: &mut Bencher) {
+	let client = EvmTestClient::new(ethereum::new_frontier_test());
+	let pre = client.prepare(PodState::default());
+	let env_info = EnvInfo::default();
+
 	b.iter(|| {
 		let mut params = ActionParams::default();
 		params.gas = gas;
 		params.code = Some(code.clone());
 
-		run_vm(params)
+		client.call_on(&env_info, &pre, params, NoopVMTracer)
 	});
 }
-
@@ -0,0 +1,125 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! VM tracer printing executed instructions as JSON.
+
+use std::io::Write;
+use std::sync::Arc;
+use util::{U256, Mutex};
+use ethcore::trace::{VMTracer, VMTrace};
+use ethcore::evm::instructions;
+
+/// Writes one line of JSON per executed instruction to `out`, so that traces can be diffed
+/// against those of other clients.
+pub struct JsonTracer<W: Write + Send> {
+	out: Arc<Mutex<W>>,
+	pc: usize,
+	instruction: u8,
+	gas_cost: U256,
+	depth: usize,
+}
+
+impl<W: Write + Send> JsonTracer<W> {
+	/// Create a tracer writing to `out`.
+	pub fn new(out: W) -> Self {
+		JsonTracer {
+			out: Arc::new(Mutex::new(out)),
+			pc: 0,
+			instruction: 0,
+			gas_cost: U256::zero(),
+			depth: 0,
+		}
+	}
+}
+
+impl<W: Write + Send> VMTracer for JsonTracer<W> {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		self.pc = pc;
+		self.instruction = instruction;
+		self.gas_cost = gas_cost.clone();
+		true
+	}
+
+	fn trace_state(&mut self, gas: &U256, stack: &[U256], mem: &[u8]) {
+		let stack = stack.iter().map(|item| format!("\"0x{:x}\"", item)).collect::<Vec<_>>().join(",");
+		writeln!(
+			self.out.lock(),
+			"{{\"pc\":{},\"op\":{},\"opName\":\"{}\",\"gas\":\"0x{:x}\",\"gasCost\":\"0x{:x}\",\"stack\":[{}],\"memSize\":{},\"depth\":{}}}",
+			self.pc,
+			self.instruction,
+			instructions::INSTRUCTIONS[self.instruction as usize].name,
+			gas,
+			self.gas_cost,
+			stack,
+			mem.len(),
+			self.depth
+		).expect("Failed to write the trace");
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		JsonTracer {
+			out: self.out.clone(),
+			pc: 0,
+			instruction: 0,
+			gas_cost: U256::zero(),
+			depth: self.depth + 1,
+		}
+	}
+
+	fn done_subtrace(&mut self, _sub: Self) {}
+
+	fn drain(self) -> Option<VMTrace> { None }
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{self, Write};
+	use std::sync::Arc;
+	use util::{Mutex, FromHex};
+	use ethcore::action_params::ActionParams;
+	use ethcore::client::{EnvInfo, EvmTestClient};
+	use ethcore::ethereum;
+	use ethcore::pod_state::PodState;
+	use super::JsonTracer;
+
+	#[derive(Clone)]
+	struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedBuffer {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.lock().write(buf) }
+		fn flush(&mut self) -> io::Result<()> { Ok(()) }
+	}
+
+	#[test]
+	fn writes_one_line_per_instruction() {
+		let mut params = ActionParams::default();
+		params.gas = 100_000.into();
+		// PUSH1 1, PUSH1 0, MSTORE, STOP
+		params.code = Some("6001600052".from_hex().unwrap());
+
+		let buffer = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+		let client = EvmTestClient::new(ethereum::new_frontier_test());
+		let result = client.call(&EnvInfo::default(), PodState::default(), params, JsonTracer::new(buffer.clone()));
+		assert!(result.gas_left.is_ok());
+
+		let output = String::from_utf8(buffer.0.lock().clone()).unwrap();
+		assert_eq!(output.lines().collect::<Vec<_>>(), vec![
+			r#"{"pc":0,"op":96,"opName":"PUSH1","gas":"0x186a0","gasCost":"0x3","stack":[],"memSize":0,"depth":0}"#,
+			r#"{"pc":2,"op":96,"opName":"PUSH1","gas":"0x1869d","gasCost":"0x3","stack":["0x1"],"memSize":0,"depth":0}"#,
+			r#"{"pc":4,"op":82,"opName":"MSTORE","gas":"0x1869a","gasCost":"0x6","stack":["0x1","0x0"],"memSize":0,"depth":0}"#,
+		]);
+	}
+}
@@ -21,17 +21,36 @@
 extern crate ethcore;
 extern crate rustc_serialize;
 extern crate docopt;
+extern crate ethjson;
 #[macro_use]
 extern crate ethcore_util as util;
 
-mod ext;
+mod json_tracer;
 
-use std::time::{Instant, Duration};
+use std::time::Duration;
 use std::str::FromStr;
+use std::fs::File;
+use std::io::{self, Read};
+use std::collections::BTreeMap;
 use docopt::Docopt;
-use util::{U256, FromHex, Uint, Bytes};
-use ethcore::evm::{Factory, VMType, Finalize};
-use ethcore::action_params::ActionParams;
+use rustc_serialize::hex::ToHex;
+use util::{U256, H256, Address, FromHex, Uint, Bytes};
+use ethcore::evm;
+use ethcore::action_params::{ActionParams, ActionValue};
+use ethcore::spec::Spec;
+use ethcore::ethereum;
+use ethcore::header::BlockNumber;
+use ethcore::transaction::SignedTransaction;
+use ethcore::pod_state::PodState;
+use ethcore::log_entry::LogEntry;
+use ethcore::account_diff::Diff;
+use ethcore::trace::{NoopVMTracer, ProfilingVMTracer};
+use ethcore::profile::{Profile, InstructionProfile};
+use ethcore::evm::instructions;
+use ethcore::evm::disassembler::Disassembly;
+use ethcore::zksnark;
+use ethcore::client::{SnarkVerificationResult, EvmTestClient, EnvInfo};
+use json_tracer::JsonTracer;
 
 const USAGE: &'static str = r#"
 EVM implementation for Parity.
@@ -39,12 +58,41 @@ EVM implementation for Parity.
 
 Usage:
     evmbin stats [options]
+    evmbin state-test <file> [options]
+    evmbin disasm --code CODE [options]
+    evmbin snark-verify --vk VK --proof PROOF [options]
     evmbin [-h | --help]
 
 Transaction options:
-    --code CODE        Contract code.
+    --code CODE        Contract code; by default the code of the --to
+                       account in the pre-state.
     --input DATA       Input data.
     --gas GAS          Supplied gas.
+    --gas-price WEI    Gas price.
+    --from ADDRESS     Sender of the call.
+    --to ADDRESS       Account the call is made to.
+    --value WEI        Value transferred with the call.
+    --profile          Print execution statistics of every instruction
+                       by code.
+
+Environment options:
+    --number NUMBER    Block number, also the block at which the chain's
+                       builtins are looked up [default: 0].
+    --timestamp TIME   Block timestamp [default: 0].
+    --pre FILE         JSON file with the accounts of the pre-state.
+
+State test options:
+    --json             Print a line of JSON for every executed instruction.
+
+zkSNARK options:
+    --vk VK            Verification key.
+    --proof PROOF      Proof to verify; the primary input is given by --input.
+
+Chain options:
+    --chain SPEC       Chain spec file whose rules code and state tests run
+                       under and disassembled code is priced by (Frontier
+                       by default), and whose zkSNARK builtin prices the
+                       verification.
 
 General options:
     -h, --help         Display this message and exit.
@@ -54,32 +102,175 @@ General options:
 fn main() {
 	let args: Args = Docopt::new(USAGE).and_then(|d| d.decode()).unwrap_or_else(|e| e.exit());
 
+	if args.cmd_snark_verify {
+		snark_verify(&args);
+		return;
+	}
+
+	if args.cmd_state_test {
+		state_test(&args);
+		return;
+	}
+
+	if args.cmd_disasm {
+		disasm(&args);
+		return;
+	}
+
+	let pre = args.pre();
 	let mut params = ActionParams::default();
+	params.sender = args.address(&args.flag_from, "Invalid sender.");
+	params.origin = params.sender.clone();
+	params.address = args.address(&args.flag_to, "Invalid recipient.");
+	params.code_address = params.address.clone();
 	params.gas = args.gas();
-	params.code = Some(args.code());
+	params.gas_price = args.u256(&args.flag_gas_price, "Invalid gas price.");
+	params.value = ActionValue::Transfer(args.u256(&args.flag_value, "Invalid value."));
+	params.code = args.code(&params.address, &pre);
 	params.data = args.data();
 
-	let result = run_vm(params);
-	println!("Gas used: {:?}", result.gas_used);
-	println!("Output: {:?}", result.output);
-	println!("Time: {}.{:.9}s", result.time.as_secs(), result.time.subsec_nanos());
+	let balance = pre.get().get(&params.sender).map_or_else(U256::zero, |account| account.balance);
+	if params.value.value() > balance {
+		die("Sender cannot afford the value.");
+	}
+
+	let mut env_info = EnvInfo::default();
+	env_info.number = args.number();
+	env_info.timestamp = args.timestamp();
+	env_info.gas_limit = params.gas;
+
+	let spec = args.spec().unwrap_or_else(ethereum::new_frontier_test);
+	let result = run_vm(spec, &env_info, pre, params, args.flag_profile);
+	match result.error {
+		Some(ref e) => println!("Error: {:?}", e),
+		None => println!("Gas used: {}", result.gas_used),
+	}
+	println!("Output: 0x{}", result.output.to_hex());
+	println!("Refund: {}", result.refund);
+	for log in &result.logs {
+		let topics = log.topics.iter().map(|t| format!("0x{:?}", t)).collect::<Vec<_>>().join(", ");
+		println!("Log: 0x{:?} [{}] 0x{}", log.address, topics, log.data.to_hex());
+	}
+	for (address, storage) in &result.storage_writes {
+		for (key, value) in storage {
+			println!("Storage: 0x{:?} 0x{:?} => 0x{:?}", address, key, value);
+		}
+	}
+	if let Some(profile) = result.profile {
+		print_profile(profile);
+	}
+	println!("Time: {}.{:09}s", result.time.as_secs(), result.time.subsec_nanos());
 }
 
-/// Execute VM with given `ActionParams`
-pub fn run_vm(params: ActionParams) -> ExecutionResults {
+/// Print instruction statistics of each code, the most time consuming first.
+fn print_profile(profile: Profile) {
+	for (code_hash, mut stats) in profile.code {
+		stats.sort_by(|a, b| b.time_ns.cmp(&a.time_ns));
+		println!("Profile of code 0x{:?}:", code_hash);
+		for &InstructionProfile { instruction, count, ref gas, time_ns } in &stats {
+			println!("  {:<14} count: {:<10} gas: {:<12} time: {}ns", instructions::INSTRUCTIONS[instruction as usize].name, count, gas, time_ns);
+		}
+	}
+}
+
+/// Verify a zkSNARK proof the way the zkSNARK builtin of the given chain would.
+fn snark_verify(args: &Args) {
+	let vk = args.hex(&args.flag_vk, "Invalid verification key.");
+	let proof = args.hex(&args.flag_proof, "Invalid proof.");
+	let input = args.data().unwrap_or_else(Vec::new);
+
+	let verification = match args.spec() {
+		Some(spec) => zksnark::verify_offline(spec.engine.builtins(), args.number(), &vk, &proof, &input),
+		None => zksnark::verify_offline(&BTreeMap::new(), args.number(), &vk, &proof, &input),
+	};
+
+	match verification.result {
+		SnarkVerificationResult::Verified => println!("Result: verified"),
+		SnarkVerificationResult::Rejected => println!("Result: rejected"),
+		SnarkVerificationResult::Error(reason) => println!("Result: error ({})", reason),
+	}
+	match verification.gas {
+		Some(gas) => println!("Gas: {}", gas),
+		None => println!("Gas: unknown (no zkSNARK builtin in chain spec)"),
+	}
+}
+
+/// Print the given code as assembly split into basic blocks, with the static gas of each
+/// by the rules of the given chain at the given block.
+fn disasm(args: &Args) {
+	let code = args.hex(&args.flag_code, "Invalid code.");
+	let spec = args.spec().unwrap_or_else(ethereum::new_frontier_test);
+	let mut env_info = EnvInfo::default();
+	env_info.number = args.number();
+	print!("{}", Disassembly::new(&code, &spec.engine.schedule(&env_info)));
+}
+
+/// Run the transactions of a state test file and check the resulting state roots.
+fn state_test(args: &Args) {
+	let file = File::open(&args.arg_file).unwrap_or_else(|e| die(&format!("{}: {}", args.arg_file, e)));
+	let tests = ethjson::state::Test::load(file).unwrap_or_else(|e| die(&format!("{}: {}", args.arg_file, e)));
+	let client = EvmTestClient::new(args.spec().unwrap_or_else(ethereum::new_frontier_test));
+
+	let mut failed = 0;
+	for (name, test) in tests {
+		let env_info: EnvInfo = test.env.into();
+		let pre: PodState = test.pre_state.into();
+		let transaction: SignedTransaction = test.transaction.into();
+		let expected_root: H256 = test.post_state_root.into();
+
+		let result = match args.flag_json {
+			true => client.transact(&env_info, pre, &transaction, JsonTracer::new(io::stdout())),
+			false => client.transact(&env_info, pre, &transaction, NoopVMTracer),
+		};
+
+		let pass = result.state_root == expected_root;
+		if !pass {
+			failed += 1;
+		}
+		match args.flag_json {
+			true => println!("{{\"test\":\"{}\",\"pass\":{},\"stateRoot\":\"0x{:?}\"}}", name, pass, result.state_root),
+			false if pass => println!("{}: ok", name),
+			false => println!("{}: FAIL (state root {:?}, expected {:?})", name, result.state_root, expected_root),
+		}
+	}
+
+	if failed > 0 {
+		die(&format!("{} tests failed.", failed));
+	}
+}
+
+/// Execute VM with given `ActionParams` on top of `pre`, under the rules of `spec`,
+/// optionally profiling every instruction.
+pub fn run_vm(spec: Spec, env_info: &EnvInfo, pre: PodState, params: ActionParams, profiling: bool) -> ExecutionResults {
 	let initial_gas = params.gas;
-	let factory = Factory::new(VMType::Interpreter);
-	let mut vm = factory.create(params.gas);
-	let mut ext = ext::FakeExt::default();
+	let client = EvmTestClient::new(spec);
+	let result = match profiling {
+		true => client.call(env_info, pre, params, ProfilingVMTracer::new(NoopVMTracer)),
+		false => client.call(env_info, pre, params, NoopVMTracer),
+	};
+
+	let storage_writes = result.state_diff.raw.into_iter()
+		.map(|(address, diff)| (address, diff.storage.into_iter().map(|(key, value)| (key, match value {
+			Diff::Born(v) | Diff::Changed(_, v) => v,
+			_ => H256::new(),
+		})).collect::<BTreeMap<_, _>>()))
+		.filter(|&(_, ref storage)| !storage.is_empty())
+		.collect();
 
-	let start = Instant::now();
-	let gas_left = vm.exec(params, &mut ext).finalize(ext).expect("OK");
-	let duration = start.elapsed();
+	let (gas_used, error) = match result.gas_left {
+		Ok(gas_left) => (initial_gas - gas_left, None),
+		Err(e) => (initial_gas, Some(e)),
+	};
 
 	ExecutionResults {
-		gas_used: initial_gas - gas_left,
-		output: Vec::new(),
-		time: duration,
+		gas_used: gas_used,
+		error: error,
+		output: result.output,
+		logs: result.logs,
+		refund: result.refund,
+		storage_writes: storage_writes,
+		profile: result.profile,
+		time: result.time,
 	}
 }
 
@@ -87,8 +278,18 @@ pub fn run_vm(params: ActionParams) -> ExecutionResults {
 pub struct ExecutionResults {
 	/// Used gas
 	pub gas_used: U256,
+	/// Error the execution failed with, if any
+	pub error: Option<evm::Error>,
 	/// Output as bytes
 	pub output: Vec<u8>,
+	/// Logs emitted
+	pub logs: Vec<LogEntry>,
+	/// Gas to be refunded for storage clears and suicides
+	pub refund: U256,
+	/// Storage values written, by account
+	pub storage_writes: BTreeMap<Address, BTreeMap<H256, H256>>,
+	/// Instruction statistics, if profiled
+	pub profile: Option<Profile>,
 	/// Time Taken
 	pub time: Duration,
 }
@@ -96,9 +297,25 @@ pub struct ExecutionResults {
 #[derive(Debug, RustcDecodable)]
 struct Args {
 	cmd_stats: bool,
+	cmd_snark_verify: bool,
+	cmd_state_test: bool,
+	cmd_disasm: bool,
+	arg_file: String,
 	flag_code: Option<String>,
 	flag_gas: Option<String>,
+	flag_gas_price: Option<String>,
+	flag_from: Option<String>,
+	flag_to: Option<String>,
+	flag_value: Option<String>,
 	flag_input: Option<String>,
+	flag_timestamp: String,
+	flag_pre: Option<String>,
+	flag_vk: Option<String>,
+	flag_proof: Option<String>,
+	flag_chain: Option<String>,
+	flag_number: String,
+	flag_json: bool,
+	flag_profile: bool,
 }
 
 impl Args {
@@ -109,11 +326,26 @@ impl Args {
 			.unwrap_or_else(|| !U256::zero())
 	}
 
-	pub fn code(&self) -> Bytes {
-		self.flag_code
-			.clone()
-			.and_then(|c| c.from_hex().ok())
-			.unwrap_or_else(|| die("Code is required."))
+	pub fn u256(&self, value: &Option<String>, err: &str) -> U256 {
+		value
+			.as_ref()
+			.map_or_else(U256::zero, |v| U256::from_str(v).unwrap_or_else(|_| die(err)))
+	}
+
+	pub fn address(&self, value: &Option<String>, err: &str) -> Address {
+		value
+			.as_ref()
+			.map_or_else(Address::new, |v| Address::from_str(v.trim_left_matches("0x")).unwrap_or_else(|_| die(err)))
+	}
+
+	pub fn code(&self, address: &Address, pre: &PodState) -> Option<Bytes> {
+		match self.flag_code {
+			Some(ref c) => Some(c.from_hex().unwrap_or_else(|_| die("Invalid code."))),
+			None => match pre.get().get(address) {
+				Some(account) if !account.code.is_empty() => None,
+				_ => die("Code is required."),
+			},
+		}
 	}
 
 	pub fn data(&self) -> Option<Bytes> {
@@ -121,10 +353,45 @@ impl Args {
 			.clone()
 			.and_then(|d| d.from_hex().ok())
 	}
+
+	pub fn number(&self) -> BlockNumber {
+		self.flag_number.parse().unwrap_or_else(|_| die("Invalid block number."))
+	}
+
+	pub fn timestamp(&self) -> u64 {
+		self.flag_timestamp.parse().unwrap_or_else(|_| die("Invalid timestamp."))
+	}
+
+	pub fn pre(&self) -> PodState {
+		match self.flag_pre {
+			Some(ref path) => {
+				let file = File::open(path).unwrap_or_else(|e| die(&format!("{}: {}", path, e)));
+				ethjson::blockchain::State::load(file).unwrap_or_else(|e| die(&format!("{}: {}", path, e))).into()
+			},
+			None => PodState::default(),
+		}
+	}
+
+	pub fn spec(&self) -> Option<Spec> {
+		self.flag_chain.as_ref().map(|path| {
+			let mut json = Vec::new();
+			File::open(path)
+				.and_then(|mut f| f.read_to_end(&mut json))
+				.unwrap_or_else(|e| die(&format!("{}: {}", path, e)));
+			Spec::load(&json).unwrap_or_else(|e| die(&format!("{}: {}", path, e)))
+		})
+	}
+
+	fn hex(&self, value: &Option<String>, err: &str) -> Bytes {
+		value
+			.clone()
+			.and_then(|v| v.from_hex().ok())
+			.unwrap_or_else(|| die(err))
+	}
 }
 
 
-fn die(msg: &'static str) -> ! {
+fn die(msg: &str) -> ! {
 	println!("{}", msg);
 	::std::process::exit(-1)
 }
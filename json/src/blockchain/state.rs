@@ -17,6 +17,9 @@
 //! Blockchain test state deserializer.
 
 use std::collections::BTreeMap;
+use std::io::Read;
+use serde_json;
+use serde_json::Error;
 use hash::Address;
 use blockchain::account::Account;
 
@@ -24,6 +27,13 @@ use blockchain::account::Account;
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 pub struct State(BTreeMap<Address, Account>);
 
+impl State {
+	/// Loads state from json.
+	pub fn load<R>(reader: R) -> Result<Self, Error> where R: Read {
+		serde_json::from_reader(reader)
+	}
+}
+
 impl IntoIterator for State {
 	type Item = <BTreeMap<Address, Account> as IntoIterator>::Item;
 	type IntoIter = <BTreeMap<Address, Account> as IntoIterator>::IntoIter;
@@ -0,0 +1,67 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Authority Round params deserialization.
+
+use uint::Uint;
+use spec::ValidatorSet;
+
+/// Authority Round params deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct AuthorityRoundParams {
+	/// Gas limit divisor.
+	#[serde(rename="gasLimitBoundDivisor")]
+	pub gas_limit_bound_divisor: Uint,
+	/// Duration of a step, in seconds.
+	#[serde(rename="stepDuration")]
+	pub step_duration: Uint,
+	/// Valid authorities, in the order in which they propose.
+	pub validators: ValidatorSet,
+}
+
+/// Authority Round engine deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct AuthorityRound {
+	/// Authority Round params.
+	pub params: AuthorityRoundParams,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::U256;
+	use uint::Uint;
+	use util::hash::Address as Hash160;
+	use hash::Address;
+	use std::str::FromStr;
+	use spec::ValidatorSet;
+	use spec::authority_round::AuthorityRound;
+
+	#[test]
+	fn authority_round_deserialization() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"stepDuration": "0x02",
+				"validators" : { "list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"] }
+			}
+		}"#;
+
+		let deserialized: AuthorityRound = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.step_duration, Uint(U256::from(2)));
+		assert_eq!(deserialized.params.validators, ValidatorSet::List(vec![Address(Hash160::from_str("c6d9d2cd449a754c494264e1809c50e34d64562b").unwrap())]));
+	}
+}
@@ -18,6 +18,7 @@
 
 use uint::Uint;
 use hash::Address;
+use spec::ValidatorSet;
 
 /// Ethash params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -28,8 +29,10 @@ pub struct BasicAuthorityParams {
 	/// Block duration.
 	#[serde(rename="durationLimit")]
 	pub duration_limit: Uint,
+	/// Valid authorities, as a fixed list. Superseded by `validators`; used only if that is missing.
+	pub authorities: Option<Vec<Address>>,
 	/// Valid authorities
-	pub authorities: Vec<Address>,
+	pub validators: Option<ValidatorSet>,
 }
 
 /// Ethash engine deserialization.
@@ -43,9 +46,28 @@ pub struct BasicAuthority {
 mod tests {
 	use serde_json;
 	use spec::basic_authority::BasicAuthority;
+	use spec::validator_set::ValidatorSet;
 
 	#[test]
 	fn basic_authority_deserialization() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"durationLimit": "0x0d",
+				"validators" : { "list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"] }
+			}
+		}"#;
+
+		let deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.authorities, None);
+		match deserialized.params.validators {
+			Some(ValidatorSet::List(ref validators)) => assert_eq!(validators.len(), 1),
+			_ => panic!("expected a list of validators"),
+		}
+	}
+
+	#[test]
+	fn basic_authority_authorities_deserialization() {
 		let s = r#"{
 			"params": {
 				"gasLimitBoundDivisor": "0x0400",
@@ -54,6 +76,8 @@ mod tests {
 			}
 		}"#;
 
-		let _deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+		let deserialized: BasicAuthority = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.authorities.map(|a| a.len()), Some(1));
+		assert_eq!(deserialized.params.validators, None);
 	}
 }
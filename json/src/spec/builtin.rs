@@ -16,6 +16,8 @@
 
 //! Spec builtin deserialization.
 
+use uint::Uint;
+
 /// Linear pricing.
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 pub struct Linear {
@@ -25,12 +27,62 @@ pub struct Linear {
 	pub word: usize,
 }
 
+/// Pricing for the alt_bn128 pairing check.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct AltBn128Pairing {
+	/// Base price.
+	pub base: usize,
+	/// Price for each (G1, G2) pair.
+	pub pair: usize,
+}
+
+/// Pricing for zkSNARK verification.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct ZkSnark {
+	/// Base price.
+	pub base: usize,
+	/// Price for word of the verification key.
+	#[serde(rename="vkWord")]
+	pub vk_word: usize,
+	/// Price for word of the primary input.
+	#[serde(rename="inputWord")]
+	pub input_word: usize,
+}
+
+/// Pricing for batch zkSNARK verification.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct ZkSnarkBatch {
+	/// Base price.
+	pub base: usize,
+	/// Price for word of the verification key, which is only set up once per batch.
+	#[serde(rename="vkWord")]
+	pub vk_word: usize,
+	/// Price for each proof.
+	pub proof: usize,
+	/// Price for word of the primary inputs.
+	#[serde(rename="inputWord")]
+	pub input_word: usize,
+}
+
 /// Pricing variants.
 #[derive(Debug, PartialEq, Deserialize, Clone)]
 pub enum Pricing {
 	/// Linear pricing.
 	#[serde(rename="linear")]
 	Linear(Linear),
+	/// Pairing check pricing.
+	#[serde(rename="alt_bn128_pairing")]
+	AltBn128Pairing(AltBn128Pairing),
+	/// zkSNARK verification pricing.
+	#[serde(rename="zksnark")]
+	ZkSnark(ZkSnark),
+	/// zkSNARK verification key registry pricing. Registrations are also charged as many
+	/// `SSTORE`s of a new value as the storage slots they write.
+	#[serde(rename="zksnark_registry")]
+	ZkSnarkRegistry(ZkSnark),
+	/// Batch zkSNARK verification pricing.
+	#[serde(rename="zksnark_batch")]
+	ZkSnarkBatch(ZkSnarkBatch),
 }
 
 /// Spec builtin.
@@ -40,12 +92,18 @@ pub struct Builtin {
 	pub name: String,
 	/// Builtin pricing.
 	pub pricing: Pricing,
+	/// Block number from which the builtin is active. Active from genesis if not given.
+	pub activate_at: Option<Uint>,
+	/// Block number from which the builtin is no longer active.
+	pub deactivate_at: Option<Uint>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use spec::builtin::Builtin;
+	use util::numbers::U256;
+	use uint::Uint;
+	use spec::builtin::{Builtin, Pricing, AltBn128Pairing, ZkSnark, ZkSnarkBatch};
 
 	#[test]
 	fn builtin_deserialization() {
@@ -56,4 +114,59 @@ mod tests {
 		let _deserialized: Builtin = serde_json::from_str(s).unwrap();
 		// TODO: validate all fields
 	}
+
+	#[test]
+	fn builtin_activation_deserialization() {
+		let s = r#"{
+			"name": "zkSNARK",
+			"pricing": { "linear": { "base": 3000, "word": 0 } },
+			"activate_at": "0x10",
+			"deactivate_at": 32
+		}"#;
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.activate_at, Some(Uint(U256::from(16))));
+		assert_eq!(deserialized.deactivate_at, Some(Uint(U256::from(32))));
+	}
+
+	#[test]
+	fn builtin_pairing_pricing_deserialization() {
+		let s = r#"{
+			"name": "alt_bn128_pairing",
+			"pricing": { "alt_bn128_pairing": { "base": 100000, "pair": 80000 } }
+		}"#;
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.pricing, Pricing::AltBn128Pairing(AltBn128Pairing {
+			base: 100000,
+			pair: 80000,
+		}));
+	}
+
+	#[test]
+	fn builtin_zksnark_pricing_deserialization() {
+		let s = r#"{
+			"name": "zkSNARK",
+			"pricing": { "zksnark": { "base": 1000000, "vkWord": 1000, "inputWord": 500 } }
+		}"#;
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.pricing, Pricing::ZkSnark(ZkSnark {
+			base: 1000000,
+			vk_word: 1000,
+			input_word: 500,
+		}));
+	}
+
+	#[test]
+	fn builtin_zksnark_batch_pricing_deserialization() {
+		let s = r#"{
+			"name": "zkSNARK_batch",
+			"pricing": { "zksnark_batch": { "base": 1000000, "vkWord": 1000, "proof": 200000, "inputWord": 500 } }
+		}"#;
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.pricing, Pricing::ZkSnarkBatch(ZkSnarkBatch {
+			base: 1000000,
+			vk_word: 1000,
+			proof: 200000,
+			input_word: 500,
+		}));
+	}
 }
@@ -18,16 +18,24 @@
 
 use spec::Ethash;
 use spec::BasicAuthority;
+use spec::AuthorityRound;
+use spec::Tendermint;
 
 /// Engine deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
 pub enum Engine {
 	/// Null engine.
 	Null,
+	/// Instantly sealing engine.
+	InstantSeal,
 	/// Ethash engine.
 	Ethash(Ethash),
 	/// BasicAuthority engine.
 	BasicAuthority(BasicAuthority),
+	/// AuthorityRound engine.
+	AuthorityRound(AuthorityRound),
+	/// Tendermint engine.
+	Tendermint(Tendermint),
 }
 
 #[cfg(test)]
@@ -44,6 +52,13 @@ mod tests {
 		let deserialized: Engine = serde_json::from_str(s).unwrap();
 		assert_eq!(Engine::Null, deserialized);
 
+		let s = r#"{
+			"InstantSeal": null
+		}"#;
+
+		let deserialized: Engine = serde_json::from_str(s).unwrap();
+		assert_eq!(Engine::InstantSeal, deserialized);
+
 		let s = r#"{
 			"Ethash": {
 				"params": {
@@ -62,6 +77,29 @@ mod tests {
 		}"#;
 
 		let _deserialized: Engine = serde_json::from_str(s).unwrap();
+
+		let s = r#"{
+			"AuthorityRound": {
+				"params": {
+					"gasLimitBoundDivisor": "0x0400",
+					"stepDuration": "0x02",
+					"validators" : { "list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"] }
+				}
+			}
+		}"#;
+
+		let _deserialized: Engine = serde_json::from_str(s).unwrap();
+
+		let s = r#"{
+			"Tendermint": {
+				"params": {
+					"gasLimitBoundDivisor": "0x0400",
+					"validators": { "list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"] }
+				}
+			}
+		}"#;
+
+		let _deserialized: Engine = serde_json::from_str(s).unwrap();
 	}
 }
 
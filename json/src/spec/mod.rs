@@ -26,9 +26,13 @@ pub mod engine;
 pub mod state;
 pub mod ethash;
 pub mod basic_authority;
+pub mod authority_round;
+pub mod tendermint;
+pub mod validator_set;
+pub mod schedule;
 
 pub use self::account::Account;
-pub use self::builtin::{Builtin, Pricing, Linear};
+pub use self::builtin::{Builtin, Pricing, Linear, AltBn128Pairing, ZkSnark, ZkSnarkBatch};
 pub use self::genesis::Genesis;
 pub use self::params::Params;
 pub use self::spec::Spec;
@@ -37,3 +41,7 @@ pub use self::engine::Engine;
 pub use self::state::State;
 pub use self::ethash::{Ethash, EthashParams};
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
+pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
+pub use self::tendermint::{Tendermint, TendermintParams};
+pub use self::validator_set::ValidatorSet;
+pub use self::schedule::ScheduleOverrides;
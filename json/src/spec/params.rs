@@ -16,7 +16,9 @@
 
 //! Spec params deserialization.
 
+use std::collections::BTreeMap;
 use uint::Uint;
+use spec::ScheduleOverrides;
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -30,14 +32,37 @@ pub struct Params {
 	/// Network id.
 	#[serde(rename="networkID")]
 	pub network_id: Uint,
+	/// Chain id for replay-protected transactions; the network id if not given.
+	#[serde(rename="chainID")]
+	pub chain_id: Option<Uint>,
+	/// Block from which replay-protected transactions are allowed.
+	#[serde(rename="eip155Transition")]
+	pub eip155_transition: Option<Uint>,
+	/// Block from which empty accounts touched by a transaction are removed.
+	#[serde(rename="eip161Transition")]
+	pub eip161_transition: Option<Uint>,
+	/// Block from which the `REVERT` instruction is enabled.
+	#[serde(rename="eip140Transition")]
+	pub eip140_transition: Option<Uint>,
+	/// Block from which the `RETURNDATASIZE` and `RETURNDATACOPY` instructions are enabled.
+	#[serde(rename="eip211Transition")]
+	pub eip211_transition: Option<Uint>,
+	/// Block from which the `STATICCALL` instruction is enabled.
+	#[serde(rename="eip214Transition")]
+	pub eip214_transition: Option<Uint>,
 	/// Minimum gas limit.
 	#[serde(rename="minGasLimit")]
 	pub min_gas_limit: Uint,
+	/// EVM schedule overrides, keyed by the block number from which they apply.
+	#[serde(rename="scheduleTransitions")]
+	pub schedule_transitions: Option<BTreeMap<Uint, ScheduleOverrides>>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use util::numbers::U256;
+	use uint::Uint;
 	use spec::params::Params;
 
 	#[test]
@@ -53,4 +78,30 @@ mod tests {
 		let _deserialized: Params = serde_json::from_str(s).unwrap();
 		// TODO: validate all fields
 	}
+
+	#[test]
+	fn params_transitions_deserialization() {
+		let s = r#"{
+			"maximumExtraDataSize": "0x20",
+			"networkID" : "0x1",
+			"minGasLimit": "0x1388",
+			"accountStartNonce": "0x00",
+			"chainID": "0x3d",
+			"eip155Transition": "0x0a",
+			"eip161Transition": "0x0b",
+			"eip140Transition": "0x0c",
+			"scheduleTransitions": {
+				"0x10": { "sloadGas": 200 },
+				"32": { "callGas": 700, "subGasCapDivisor": 64 }
+			}
+		}"#;
+
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.chain_id, Some(Uint(U256::from(0x3d))));
+		assert_eq!(deserialized.eip155_transition, Some(Uint(U256::from(10))));
+		assert_eq!(deserialized.eip161_transition, Some(Uint(U256::from(11))));
+		assert_eq!(deserialized.eip140_transition, Some(Uint(U256::from(12))));
+		assert_eq!(deserialized.eip214_transition, None);
+		assert_eq!(deserialized.schedule_transitions.unwrap().len(), 2);
+	}
 }
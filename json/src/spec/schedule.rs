@@ -0,0 +1,112 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EVM schedule overrides deserialization.
+
+use uint::{Uint, NonZeroUint};
+
+/// Spec overrides of the gas costs of the EVM schedule. Fields which are not given keep
+/// the values of the schedule of the engine.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ScheduleOverrides {
+	/// Gas price for loading from storage.
+	#[serde(rename="sloadGas")]
+	pub sload_gas: Option<Uint>,
+	/// Gas price for setting a zero storage value to non-zero.
+	#[serde(rename="sstoreSetGas")]
+	pub sstore_set_gas: Option<Uint>,
+	/// Gas price for altering a non-zero storage value.
+	#[serde(rename="sstoreResetGas")]
+	pub sstore_reset_gas: Option<Uint>,
+	/// Gas refund for clearing a non-zero storage value.
+	#[serde(rename="sstoreRefundGas")]
+	pub sstore_refund_gas: Option<Uint>,
+	/// Additional gas for `EXP` for each byte of the exponent.
+	#[serde(rename="expByteGas")]
+	pub exp_byte_gas: Option<Uint>,
+	/// Gas price for `CREATE`.
+	#[serde(rename="createGas")]
+	pub create_gas: Option<Uint>,
+	/// Gas price for the `*CALL*` opcodes.
+	#[serde(rename="callGas")]
+	pub call_gas: Option<Uint>,
+	/// If given, `*CALL*` and `CREATE` pass the callee at most all but `1/subGasCapDivisor` of the remaining gas. Must not be zero.
+	#[serde(rename="subGasCapDivisor")]
+	pub sub_gas_cap_divisor: Option<NonZeroUint>,
+	/// Additional gas for `CALL` and `CALLCODE` transferring value.
+	#[serde(rename="callValueTransferGas")]
+	pub call_value_transfer_gas: Option<Uint>,
+	/// Additional gas for `CALL` creating a new account.
+	#[serde(rename="callNewAccountGas")]
+	pub call_new_account_gas: Option<Uint>,
+	/// Gas price for `BALANCE`.
+	#[serde(rename="balanceGas")]
+	pub balance_gas: Option<Uint>,
+	/// Gas price for `EXTCODESIZE`.
+	#[serde(rename="extcodesizeGas")]
+	pub extcodesize_gas: Option<Uint>,
+	/// Base gas price for `EXTCODECOPY`.
+	#[serde(rename="extcodecopyBaseGas")]
+	pub extcodecopy_base_gas: Option<Uint>,
+	/// Gas price for `SUICIDE`.
+	#[serde(rename="suicideGas")]
+	pub suicide_gas: Option<Uint>,
+	/// Additional gas for `SUICIDE` when the refund address does not exist.
+	#[serde(rename="suicideToNewAccountCost")]
+	pub suicide_to_new_account_cost: Option<Uint>,
+	/// Gas refund for `SUICIDE`.
+	#[serde(rename="suicideRefundGas")]
+	pub suicide_refund_gas: Option<Uint>,
+	/// Transaction cost of each zero byte of data.
+	#[serde(rename="txDataZeroGas")]
+	pub tx_data_zero_gas: Option<Uint>,
+	/// Transaction cost of each non-zero byte of data.
+	#[serde(rename="txDataNonZeroGas")]
+	pub tx_data_non_zero_gas: Option<Uint>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::U256;
+	use uint::{Uint, NonZeroUint};
+	use spec::schedule::ScheduleOverrides;
+
+	#[test]
+	fn schedule_overrides_deserialization() {
+		let s = r#"{
+			"sloadGas": 200,
+			"callGas": "0x02bc",
+			"subGasCapDivisor": 64
+		}"#;
+
+		let deserialized: ScheduleOverrides = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.sload_gas, Some(Uint(U256::from(200))));
+		assert_eq!(deserialized.call_gas, Some(Uint(U256::from(700))));
+		assert_eq!(deserialized.sub_gas_cap_divisor, Some(NonZeroUint(U256::from(64))));
+		assert_eq!(deserialized.balance_gas, None);
+	}
+
+	#[test]
+	fn schedule_overrides_reject_zero_divisor() {
+		let s = r#"{
+			"subGasCapDivisor": "0x0"
+		}"#;
+
+		let deserialized: Result<ScheduleOverrides, _> = serde_json::from_str(s);
+		assert!(deserialized.is_err());
+	}
+}
@@ -0,0 +1,78 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tendermint params deserialization.
+
+use uint::Uint;
+use spec::ValidatorSet;
+
+/// Tendermint params deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct TendermintParams {
+	/// Gas limit divisor.
+	#[serde(rename="gasLimitBoundDivisor")]
+	pub gas_limit_bound_divisor: Uint,
+	/// Valid validators, in the order in which they propose.
+	pub validators: ValidatorSet,
+	/// Propose step timeout in milliseconds.
+	#[serde(rename="timeoutPropose")]
+	pub timeout_propose: Option<Uint>,
+	/// Prevote step timeout in milliseconds.
+	#[serde(rename="timeoutPrevote")]
+	pub timeout_prevote: Option<Uint>,
+	/// Precommit step timeout in milliseconds.
+	#[serde(rename="timeoutPrecommit")]
+	pub timeout_precommit: Option<Uint>,
+	/// Commit step timeout in milliseconds.
+	#[serde(rename="timeoutCommit")]
+	pub timeout_commit: Option<Uint>,
+}
+
+/// Tendermint engine deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Tendermint {
+	/// Tendermint params.
+	pub params: TendermintParams,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::U256;
+	use uint::Uint;
+	use util::hash::Address as Hash160;
+	use hash::Address;
+	use std::str::FromStr;
+	use spec::ValidatorSet;
+	use spec::tendermint::Tendermint;
+
+	#[test]
+	fn tendermint_deserialization() {
+		let s = r#"{
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"validators": { "list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"] },
+				"timeoutPropose": 3000
+			}
+		}"#;
+
+		let deserialized: Tendermint = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.gas_limit_bound_divisor, Uint(U256::from(0x0400)));
+		assert_eq!(deserialized.params.validators, ValidatorSet::List(vec![Address(Hash160::from_str("c6d9d2cd449a754c494264e1809c50e34d64562b").unwrap())]));
+		assert_eq!(deserialized.params.timeout_propose, Some(Uint(U256::from(3000))));
+		assert_eq!(deserialized.params.timeout_commit, None);
+	}
+}
@@ -0,0 +1,71 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator set deserialization.
+
+use std::collections::BTreeMap;
+use uint::Uint;
+use hash::Address;
+
+/// Validator set deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub enum ValidatorSet {
+	/// A fixed list of validators.
+	#[serde(rename="list")]
+	List(Vec<Address>),
+	/// Validators returned by `getValidators()` of a contract, at the parent of each block.
+	#[serde(rename="contract")]
+	Contract(Address),
+	/// Validator sets used from the block number they are keyed with until the next one.
+	#[serde(rename="multi")]
+	Multi(BTreeMap<Uint, ValidatorSet>),
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::numbers::U256;
+	use uint::Uint;
+	use spec::validator_set::ValidatorSet;
+
+	#[test]
+	fn validator_set_deserialization() {
+		let s = r#"[{
+			"list" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+		}, {
+			"contract" : "0xc6d9d2cd449a754c494264e1809c50e34d64562b"
+		}, {
+			"multi": {
+				"0": { "list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"] },
+				"0x64": { "contract": "0xc6d9d2cd449a754c494264e1809c50e34d64562b" }
+			}
+		}]"#;
+
+		let deserialized: Vec<ValidatorSet> = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.len(), 3);
+		match deserialized[0] {
+			ValidatorSet::List(ref validators) => assert_eq!(validators.len(), 1),
+			_ => panic!("expected a list of validators"),
+		}
+		match deserialized[2] {
+			ValidatorSet::Multi(ref sets) => {
+				assert_eq!(sets.len(), 2);
+				assert!(sets.contains_key(&Uint(U256::from(100))));
+			},
+			_ => panic!("expected multiple validator sets"),
+		}
+	}
+}
@@ -56,6 +56,33 @@ impl Deserialize for Uint {
 	}
 }
 
+/// Lenient uint json deserialization which rejects zero, for divisors.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NonZeroUint(pub U256);
+
+impl Into<U256> for NonZeroUint {
+	fn into(self) -> U256 {
+		self.0
+	}
+}
+
+impl Into<usize> for NonZeroUint {
+	fn into(self) -> usize {
+		u64::from(self.0) as usize
+	}
+}
+
+impl Deserialize for NonZeroUint {
+	fn deserialize<D>(deserializer: &mut D) -> Result<Self, D::Error>
+		where D: Deserializer {
+		let value = try!(Uint::deserialize(deserializer));
+		match value.0.is_zero() {
+			true => Err(Error::custom("Invalid zero value.")),
+			false => Ok(NonZeroUint(value.0)),
+		}
+	}
+}
+
 struct UintVisitor;
 
 impl Visitor for UintVisitor {
@@ -89,7 +116,7 @@ impl Visitor for UintVisitor {
 mod test {
 	use serde_json;
 	use util::numbers::U256;
-	use uint::Uint;
+	use uint::{Uint, NonZeroUint};
 
 	#[test]
 	fn uint_deserialization() {
@@ -104,6 +131,14 @@ mod test {
 		]);
 	}
 
+	#[test]
+	fn non_zero_uint_deserialization() {
+		let deserialized: Vec<NonZeroUint> = serde_json::from_str(r#"["0xa", 10]"#).unwrap();
+		assert_eq!(deserialized, vec![NonZeroUint(U256::from(10)), NonZeroUint(U256::from(10))]);
+		assert!(serde_json::from_str::<NonZeroUint>(r#""0x""#).is_err());
+		assert!(serde_json::from_str::<NonZeroUint>("0").is_err());
+	}
+
 	#[test]
 	fn uint_into() {
 		assert_eq!(U256::from(10), Uint(U256::from(10)).into());
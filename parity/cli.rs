@@ -47,8 +47,8 @@ Operating Options:
                            [default: 3600].
   --chain CHAIN            Specify the blockchain type. CHAIN may be either a
                            JSON chain specification file or olympic, frontier,
-                           homestead, mainnet, morden, homestead-dogmatic, or
-                           testnet [default: homestead].
+                           homestead, mainnet, morden, homestead-dogmatic,
+                           testnet, or dev [default: homestead].
   -d --db-path PATH        Specify the database & configuration directory path
                            [default: $HOME/.parity].
   --keys-path PATH         Specify the path for JSON key files to be found
@@ -105,7 +105,7 @@ API and Console Options:
   --jsonrpc-apis APIS      Specify the APIs available through the JSONRPC
                            interface. APIS is a comma-delimited list of API
                            name. Possible name are web3, eth, net, personal,
-                           ethcore, ethcore_set, traces.
+                           ethcore, ethcore_set, traces, debug.
                            [default: web3,eth,net,ethcore,personal,traces].
   --jsonrpc-hosts HOSTS    List of allowed Host header values. This option will
                            validate the Host header sent by the browser, it
@@ -228,6 +228,8 @@ Import/Export Options:
 
 Virtual Machine Options:
   --jitvm                  Enable the JIT VM.
+  --jump-table-size BYTES  Override the size of the cache of code analysis
+                           (jump destinations) results kept by the EVM.
 
 Legacy Options:
   --geth                   Run in Geth-compatibility mode. Sets the IPC path
@@ -353,6 +355,7 @@ pub struct Args {
 	pub flag_to: String,
 	pub flag_format: Option<String>,
 	pub flag_jitvm: bool,
+	pub flag_jump_table_size: Option<usize>,
 	pub flag_log_file: Option<String>,
 	pub flag_no_color: bool,
 	pub flag_no_network: bool,
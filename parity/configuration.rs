@@ -32,6 +32,7 @@ use ethcore::client::{append_path, get_db_path, Mode, ClientConfig, DatabaseComp
 use ethcore::miner::{MinerOptions, PendingSet, GasPricer, GasPriceCalibratorOptions};
 use ethcore::ethereum;
 use ethcore::spec::Spec;
+use ethcore::header::BlockNumber;
 use ethsync::SyncConfig;
 use rpc::IpcConfiguration;
 use ethcore_logger::Settings as LogSettings;
@@ -204,9 +205,10 @@ impl Configuration {
 			"homestead-dogmatic" => ethereum::new_frontier_dogmatic(),
 			"morden" | "testnet" => ethereum::new_morden(),
 			"olympic" => ethereum::new_olympic(),
+			"dev" => Spec::new_instant(),
 			f => Spec::load(contents(f).unwrap_or_else(|_| {
 				die!("{}: Couldn't read chain specification file. Sure it exists?", f)
-			}).as_ref()),
+			}).as_ref()).unwrap_or_else(|e| die!("{}: {}", f, e)),
 		}
 	}
 
@@ -361,8 +363,13 @@ impl Configuration {
 		};
 
 		if self.args.flag_jitvm {
-			client_config.vm_type = VMType::jit().unwrap_or_else(|| die!("Parity is built without the JIT EVM."))
+			client_config.vm_type = VMType::jit().unwrap_or_else(|| die!("Parity is built without the JIT EVM."));
+			let params = &spec.params;
+			if params.eip140_transition != BlockNumber::max_value() || params.eip211_transition != BlockNumber::max_value() || params.eip214_transition != BlockNumber::max_value() {
+				die!("The JIT EVM supports neither REVERT, RETURNDATASIZE/RETURNDATACOPY nor STATICCALL, which are enabled by this chain. Please re-run without --jitvm.");
+			}
 		}
+		client_config.jump_table_size = self.args.flag_jump_table_size;
 
 		trace!(target: "parity", "Using pruning strategy of {}", client_config.pruning);
 		client_config.name = self.args.flag_identity.clone();
@@ -613,6 +620,17 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn should_parse_dev_chain() {
+		// given
+
+		// when
+		let conf = parse(&["parity", "--chain", "dev"]);
+
+		// then
+		assert_eq!(conf.spec().engine.name(), "InstantSeal");
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
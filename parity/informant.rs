@@ -136,9 +136,10 @@ impl Informant {
 				),
 				_ => String::new(),
 			},
-			format!("{} db {} chain {} queue{}",
+			format!("{} db {} chain {} evm {} queue{}",
 				paint(Blue.bold(), format!("{:>8}", Informant::format_bytes(report.state_db_mem))),
 				paint(Blue.bold(), format!("{:>8}", Informant::format_bytes(cache_info.total()))),
+				paint(Blue.bold(), format!("{:>8}", Informant::format_bytes(report.evm_cache_mem))),
 				paint(Blue.bold(), format!("{:>8}", Informant::format_bytes(queue_info.mem_used))),
 				match sync_status {
 					Some(ref sync_info) => format!(" {} sync", paint(Blue.bold(), format!("{:>8}", Informant::format_bytes(sync_info.mem_used)))),
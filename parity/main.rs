@@ -245,6 +245,11 @@ fn execute_client(conf: Configuration, spec: Spec, client_config: ClientConfig,
 	panic_handler.forward_from(&service);
 	let client = service.client();
 
+	// BFT engines sign their consensus messages with the author account
+	if let Some(author) = conf.author() {
+		client.engine().set_signer(account_service.clone(), author);
+	}
+
 	let external_miner = Arc::new(ExternalMiner::default());
 	let network_settings = Arc::new(conf.network_settings());
 
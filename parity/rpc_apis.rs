@@ -38,6 +38,7 @@ pub enum Api {
 	Ethcore,
 	EthcoreSet,
 	Traces,
+	Debug,
 	Rpc,
 }
 
@@ -66,6 +67,7 @@ impl FromStr for Api {
 			"ethcore" => Ok(Ethcore),
 			"ethcore_set" => Ok(EthcoreSet),
 			"traces" => Ok(Traces),
+			"debug" => Ok(Debug),
 			"rpc" => Ok(Rpc),
 			e => Err(ApiError::UnknownApi(e.into())),
 		}
@@ -99,6 +101,7 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 			Api::Ethcore => ("ethcore", "1.0"),
 			Api::EthcoreSet => ("ethcore_set", "1.0"),
 			Api::Traces => ("traces", "1.0"),
+			Api::Debug => ("debug", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
@@ -165,6 +168,9 @@ pub fn setup_rpc<T: Extendable>(server: T, deps: Arc<Dependencies>, apis: ApiSet
 			Api::Traces => {
 				server.add_delegate(TracesClient::new(&deps.client, &deps.miner).to_delegate())
 			},
+			Api::Debug => {
+				server.add_delegate(DebugClient::new(&deps.client, &deps.miner).to_delegate())
+			},
 			Api::Rpc => {
 				let modules = to_modules(&apis);
 				server.add_delegate(RpcClient::new(modules).to_delegate());
@@ -0,0 +1,45 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transactions made up from call requests.
+
+use util::{Address, U256};
+use ethcore::client::BlockChainClient;
+use ethcore::miner::MinerService;
+use ethcore::transaction::{Transaction, SignedTransaction, Action};
+use v1::helpers::CallRequest;
+
+/// Gas price of transactions which don't set one: the median of recent transactions,
+/// or the price the miner finds sensible if there are too few of them.
+pub fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where C: BlockChainClient, M: MinerService {
+	client
+		.gas_price_statistics(100, 8)
+		.map(|x| x[4])
+		.unwrap_or_else(|_| miner.sensible_gas_price())
+}
+
+/// Transaction executing `request` as a call, fake-signed by its sender.
+pub fn sign_call<C, M>(client: &C, miner: &M, request: CallRequest) -> SignedTransaction where C: BlockChainClient, M: MinerService {
+	let from = request.from.unwrap_or(Address::zero());
+	Transaction {
+		nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
+		action: request.to.map_or(Action::Create, Action::Call),
+		gas: request.gas.unwrap_or(U256::from(50_000_000)),
+		gas_price: request.gas_price.unwrap_or_else(|| default_gas_price(client, miner)),
+		value: request.value.unwrap_or_else(U256::zero),
+		data: request.data.unwrap_or_else(Vec::new),
+	}.fake_sign(from)
+}
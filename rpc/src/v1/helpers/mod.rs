@@ -18,8 +18,10 @@ mod poll_manager;
 mod poll_filter;
 mod requests;
 mod signing_queue;
+mod calls;
 
-pub use self::poll_manager::PollManager;
+pub use self::poll_manager::{PollManager, POLL_LIFETIME};
 pub use self::poll_filter::PollFilter;
 pub use self::requests::{TransactionRequest, TransactionConfirmation, CallRequest};
 pub use self::signing_queue::{ConfirmationsQueue, ConfirmationPromise, ConfirmationResult, SigningQueue, QueueEvent};
+pub use self::calls::{default_gas_price, sign_call};
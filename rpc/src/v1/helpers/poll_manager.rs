@@ -19,7 +19,7 @@
 use transient_hashmap::{TransientHashMap, Timer, StandardTimer};
 
 /// Lifetime of poll (in seconds).
-const POLL_LIFETIME: u64 = 60;
+pub const POLL_LIFETIME: u64 = 60;
 
 pub type PollId = usize;
 
@@ -77,6 +77,11 @@ impl<F, T> PollManager<F, T> where T: Timer {
 	pub fn remove_poll(&mut self, id: &PollId) {
 		self.polls.remove(id);
 	}
+
+	/// Drops the polls which expired.
+	pub fn prune(&mut self) {
+		self.polls.prune();
+	}
 }
 
 #[cfg(test)]
@@ -0,0 +1,229 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug api implementation.
+
+use std::sync::{Weak, Arc};
+use jsonrpc_core::*;
+use rustc_serialize::hex::ToHex;
+use util::{Uint, Mutex};
+use ethcore::client::{DebuggingBlockChainClient, TransactionID, ReplayError, DebugSession, DebugCommand};
+use ethcore::error::ExecutionError;
+use ethcore::miner::MinerService;
+use v1::traits::Debug;
+use v1::helpers::{PollManager, POLL_LIFETIME, sign_call};
+use v1::types::{StructLog, TraceOptions, TransactionTrace, DebugState, CallRequest, Index, H256, U256};
+use super::error_codes;
+
+/// Maximal number of debugging sessions open at once, each of which holds a thread.
+const MAX_SESSIONS: usize = 16;
+
+/// Debug api implementation.
+pub struct DebugClient<C, M> where C: DebuggingBlockChainClient, M: MinerService {
+	client: Weak<C>,
+	miner: Weak<M>,
+	sessions: Mutex<PollManager<Arc<Mutex<DebugSession>>>>,
+	// sessions are only dropped by the poll manager once they expire, or are in use
+	live_sessions: Mutex<Vec<Weak<Mutex<DebugSession>>>>,
+}
+
+impl<C, M> DebugClient<C, M> where C: DebuggingBlockChainClient, M: MinerService {
+	/// Creates new Debug client.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+		DebugClient {
+			client: Arc::downgrade(client),
+			miner: Arc::downgrade(miner),
+			sessions: Mutex::new(PollManager::new()),
+			live_sessions: Mutex::new(Vec::new()),
+		}
+	}
+
+	fn check_session_limit(&self) -> Result<(), Error> {
+		self.sessions.lock().prune();
+		let mut live = self.live_sessions.lock();
+		live.retain(|session| session.upgrade().is_some());
+		match live.len() < MAX_SESSIONS {
+			true => Ok(()),
+			false => Err(Error {
+				code: ErrorCode::ServerError(error_codes::TOO_MANY_SESSIONS),
+				message: format!("Too many debugging sessions. Stop one of them or wait {} seconds for one to expire.", POLL_LIFETIME),
+				data: None,
+			}),
+		}
+	}
+
+	fn add_session(&self, session: DebugSession) -> Result<Value, Error> {
+		// sessions might have been started concurrently since the limit was checked
+		try!(self.check_session_limit());
+		let session = Arc::new(Mutex::new(session));
+		self.live_sessions.lock().push(Arc::downgrade(&session));
+		let id = self.sessions.lock().create_poll(session);
+		to_value(&U256::from(id))
+	}
+
+	fn session(&self, id: &Index) -> Option<Arc<Mutex<DebugSession>>> {
+		self.sessions.lock().poll(&id.value()).cloned()
+	}
+
+	fn resume_session(&self, id: &Index, command: DebugCommand) -> Result<Value, Error> {
+		// the session lock alone is held while executing, so that other sessions can go on
+		match self.session(id) {
+			Some(session) => to_value(&DebugState::from(session.lock().resume(command))),
+			None => Ok(Value::Null),
+		}
+	}
+
+	fn active(&self) -> Result<(), Error> {
+		// TODO: only call every 30s at most.
+		take_weak!(self.client).keep_alive();
+		Ok(())
+	}
+}
+
+fn execution_error(error: ExecutionError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(error_codes::TRANSACTION_ERROR),
+		message: format!("{}", error),
+		data: None,
+	}
+}
+
+fn replay_error(error: ReplayError) -> Error {
+	match error {
+		ReplayError::Execution(e) => execution_error(e),
+		e => Error {
+			code: ErrorCode::ServerError(error_codes::UNKNOWN_ERROR),
+			message: format!("{}", e),
+			data: None,
+		},
+	}
+}
+
+impl<C, M> Debug for DebugClient<C, M> where C: DebuggingBlockChainClient + 'static, M: MinerService + 'static {
+	fn trace_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		let with_options = match params {
+			Params::Array(ref vec) => vec.len() > 1,
+			_ => false,
+		};
+		let params = match with_options {
+			true => from_params::<(H256, TraceOptions)>(params),
+			false => from_params::<(H256,)>(params).map(|(hash,)| (hash, TraceOptions::default())),
+		};
+		params.and_then(|(transaction_hash, options)| {
+			let client = take_weak!(self.client);
+			match client.replay_frames(TransactionID::Hash(transaction_hash.into()), options.memory(), options.stack()) {
+				Ok((executed, frames)) => to_value(&TransactionTrace {
+					gas: executed.gas_used.low_u64(),
+					failed: executed.exception.is_some(),
+					return_value: executed.output.to_hex(),
+					struct_logs: frames.into_iter().map(|frame| StructLog::from_frame(frame, &options)).collect(),
+				}),
+				Err(ReplayError::TransactionNotFound) => Ok(Value::Null),
+				Err(e) => Err(replay_error(e)),
+			}
+		})
+	}
+
+	fn start_transaction(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H256,)>(params)
+			.and_then(|(transaction_hash,)| {
+				try!(self.check_session_limit());
+				match take_weak!(self.client).debug_transaction(TransactionID::Hash(transaction_hash.into())) {
+					Ok(session) => self.add_session(session),
+					Err(ReplayError::TransactionNotFound) => Ok(Value::Null),
+					Err(e) => Err(replay_error(e)),
+				}
+			})
+	}
+
+	fn start_call(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(CallRequest,)>(params)
+			.and_then(|(request,)| {
+				try!(self.check_session_limit());
+				let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
+				let signed = sign_call(&*client, &*miner, request.into());
+				match client.debug_call(&signed) {
+					Ok(session) => self.add_session(session),
+					Err(e) => Err(execution_error(e)),
+				}
+			})
+	}
+
+	fn session_state(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index,)>(params)
+			.and_then(|(id,)| match self.session(&id) {
+				Some(session) => to_value(&DebugState::from(session.lock().status())),
+				None => Ok(Value::Null),
+			})
+	}
+
+	fn step(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index,)>(params)
+			.and_then(|(id,)| self.resume_session(&id, DebugCommand::Step))
+	}
+
+	fn step_over(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index,)>(params)
+			.and_then(|(id,)| self.resume_session(&id, DebugCommand::StepOver))
+	}
+
+	fn continue_to(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index, Index)>(params)
+			.and_then(|(id, pc)| self.resume_session(&id, DebugCommand::ContinueTo(pc.value())))
+	}
+
+	fn resume(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index,)>(params)
+			.and_then(|(id,)| self.resume_session(&id, DebugCommand::Continue))
+	}
+
+	fn set_breakpoint(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index, H256, Index)>(params)
+			.and_then(|(id, code_hash, pc)| match self.session(&id) {
+				Some(session) => to_value(&session.lock().set_breakpoint(code_hash.into(), pc.value())),
+				None => Ok(Value::Null),
+			})
+	}
+
+	fn remove_breakpoint(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index, H256, Index)>(params)
+			.and_then(|(id, code_hash, pc)| match self.session(&id) {
+				Some(session) => to_value(&session.lock().remove_breakpoint(code_hash.into(), pc.value())),
+				None => Ok(Value::Null),
+			})
+	}
+
+	fn stop_session(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Index,)>(params)
+			.and_then(|(id,)| {
+				let mut sessions = self.sessions.lock();
+				let found = sessions.poll(&id.value()).is_some();
+				sessions.remove_poll(&id.value());
+				to_value(&found)
+			})
+	}
+}
@@ -21,14 +21,19 @@ use util::misc::version_data;
 use std::sync::{Arc, Weak};
 use std::ops::Deref;
 use std::collections::{BTreeMap};
-use ethcore::client::{MiningBlockChainClient};
+use ethcore::client::{MiningBlockChainClient, BlockID};
 use jsonrpc_core::*;
 use ethcore::miner::MinerService;
+use ethcore::evm::disassembler;
 use v1::traits::Ethcore;
-use v1::types::{Bytes, U256};
+use v1::types::{Bytes, U256, H160, BlockNumber, SnarkVerification, Disassembly};
 use v1::helpers::{SigningQueue, ConfirmationsQueue};
 use v1::impls::error_codes;
 
+/// Maximal combined size of the verification key, proof and primary input accepted by `ethcore_verifySnark`.
+/// Verification runs on the RPC thread and its cost grows with the primary input, so it must stay bounded.
+const MAX_SNARK_ARGS_SIZE: usize = 64 * 1024;
+
 /// Ethcore implementation.
 pub struct EthcoreClient<C, M> where
 	C: MiningBlockChainClient,
@@ -160,4 +165,34 @@ impl<C, M> Ethcore for EthcoreClient<C, M> where M: MinerService + 'static, C: M
 			Some(ref queue) => to_value(&queue.len()),
 		}
 	}
+
+	fn verify_snark(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(Bytes, Bytes, Bytes)>(params).and_then(|(vk, proof, input)| {
+			let (vk, proof, input) = (vk.to_vec(), proof.to_vec(), input.to_vec());
+			if vk.len() + proof.len() + input.len() > MAX_SNARK_ARGS_SIZE {
+				return Err(Error::invalid_params());
+			}
+			let verification = take_weak!(self.client).verify_snark(vk, proof, input);
+			to_value(&SnarkVerification::from(verification))
+		})
+	}
+
+	fn block_signer(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(BlockNumber,)>(params).and_then(|(number,)| {
+			to_value(&take_weak!(self.client).block_signer(number.into()).map(H160::from))
+		})
+	}
+
+	fn disassemble(&self, params: Params) -> Result<Value, Error> {
+		try!(self.active());
+		from_params::<(H160, BlockNumber)>(params).and_then(|(address, number)| {
+			let client = take_weak!(self.client);
+			let id: BlockID = number.into();
+			let disassembly = client.code_at(&address.into(), id.clone())
+				.and_then(|code| client.schedule_at(id).map(|schedule| Disassembly::from(disassembler::Disassembly::new(&code, &schedule))));
+			to_value(&disassembly)
+		})
+	}
 }
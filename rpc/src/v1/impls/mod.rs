@@ -39,6 +39,7 @@ mod personal_signer;
 mod ethcore;
 mod ethcore_set;
 mod traces;
+mod debug;
 mod rpc;
 
 pub use self::web3::Web3Client;
@@ -51,9 +52,10 @@ pub use self::personal_signer::SignerClient;
 pub use self::ethcore::EthcoreClient;
 pub use self::ethcore_set::EthcoreSetClient;
 pub use self::traces::TracesClient;
+pub use self::debug::DebugClient;
 pub use self::rpc::RpcClient;
 
-use v1::helpers::TransactionRequest;
+use v1::helpers::{TransactionRequest, default_gas_price};
 use v1::types::H256 as NH256;
 use ethcore::error::Error as EthcoreError;
 use ethcore::miner::MinerService;
@@ -75,6 +77,7 @@ mod error_codes {
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const SIGNER_DISABLED: i64 = -32030;
+	pub const TOO_MANY_SESSIONS: i64 = -32040;
 }
 
 fn dispatch_transaction<C, M>(client: &C, miner: &M, signed_transaction: SignedTransaction) -> Result<Value, Error>
@@ -109,9 +112,10 @@ fn unlock_sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionReq
 
 	let signed_transaction = {
 		let t = prepare_transaction(client, miner, request);
-		let hash = t.hash();
+		let chain_id = client.signing_chain_id();
+		let hash = t.hash(chain_id);
 		let signature = try!(account_provider.sign_with_password(address, password, hash).map_err(password_error));
-		t.with_signature(signature)
+		t.with_signature(signature, chain_id)
 	};
 
 	trace!(target: "miner", "send_transaction: dispatching tx: {}", encode(&signed_transaction).to_vec().pretty());
@@ -123,22 +127,16 @@ fn sign_and_dispatch<C, M>(client: &C, miner: &M, request: TransactionRequest, a
 
 	let signed_transaction = {
 		let t = prepare_transaction(client, miner, request);
-		let hash = t.hash();
+		let chain_id = client.signing_chain_id();
+		let hash = t.hash(chain_id);
 		let signature = try!(account_provider.sign(address, hash).map_err(signing_error));
-		t.with_signature(signature)
+		t.with_signature(signature, chain_id)
 	};
 
 	trace!(target: "miner", "send_transaction: dispatching tx: {}", encode(&signed_transaction).to_vec().pretty());
 	dispatch_transaction(&*client, &*miner, signed_transaction)
 }
 
-fn default_gas_price<C, M>(client: &C, miner: &M) -> U256 where C: MiningBlockChainClient, M: MinerService {
-	client
-		.gas_price_statistics(100, 8)
-		.map(|x| x[4])
-		.unwrap_or_else(|_| miner.sensible_gas_price())
-}
-
 
 fn signing_error(error: AccountError) -> Error {
 	Error {
@@ -179,6 +177,7 @@ fn transaction_error(error: EthcoreError) -> Error {
 				format!("Transaction cost exceeds current gas limit. Limit: {}, got: {}. Try decreasing supplied gas.", limit, got)
 			},
 			InvalidGasLimit(_) => "Supplied gas is beyond limit.".into(),
+			InvalidChainId(chain_id) => format!("Transaction is replay-protected for chain {}, which is not this one or is not yet allowed.", chain_id),
 		};
 		Error {
 			code: ErrorCode::ServerError(error_codes::TRANSACTION_ERROR),
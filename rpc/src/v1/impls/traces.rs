@@ -22,10 +22,9 @@ use std::collections::BTreeMap;
 //use util::H256;
 use ethcore::client::{BlockChainClient, CallAnalytics, TransactionID, TraceId};
 use ethcore::miner::MinerService;
-use ethcore::transaction::{Transaction as EthTransaction, SignedTransaction, Action};
 use v1::traits::Traces;
-use v1::helpers::CallRequest as CRequest;
-use v1::types::{TraceFilter, LocalizedTrace, Trace, BlockNumber, Index, CallRequest, Bytes, StateDiff, VMTrace, H256};
+use v1::helpers::sign_call;
+use v1::types::{TraceFilter, LocalizedTrace, Trace, BlockNumber, Index, CallRequest, Bytes, StateDiff, VMTrace, Profile, H256};
 
 /// Traces api implementation.
 pub struct TracesClient<C, M> where C: BlockChainClient, M: MinerService {
@@ -42,21 +41,6 @@ impl<C, M> TracesClient<C, M> where C: BlockChainClient, M: MinerService {
 		}
 	}
 
-	// TODO: share with eth.rs
-	fn sign_call(&self, request: CRequest) -> Result<SignedTransaction, Error> {
-		let client = take_weak!(self.client);
-		let miner = take_weak!(self.miner);
-		let from = request.from.unwrap_or(0.into());
-		Ok(EthTransaction {
-			nonce: request.nonce.unwrap_or_else(|| client.latest_nonce(&from)),
-			action: request.to.map_or(Action::Create, Action::Call),
-			gas: request.gas.unwrap_or(50_000_000.into()),
-			gas_price: request.gas_price.unwrap_or_else(|| miner.sensible_gas_price()),
-			value: request.value.unwrap_or(0.into()),
-			data: request.data.map_or_else(Vec::new, |d| d.to_vec())
-		}.fake_sign(from))
-	}
-
 	fn active(&self) -> Result<(), Error> {
 		// TODO: only call every 30s at most.
 		take_weak!(self.client).keep_alive();
@@ -124,9 +108,11 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 					transaction_tracing: flags.contains(&("trace".to_owned())),
 					vm_tracing: flags.contains(&("vmTrace".to_owned())),
 					state_diffing: flags.contains(&("stateDiff".to_owned())),
+					profiling: flags.contains(&("profile".to_owned())),
 				};
-				let signed = try!(self.sign_call(request));
-				let r = take_weak!(self.client).call(&signed, analytics);
+				let (client, miner) = (take_weak!(self.client), take_weak!(self.miner));
+				let signed = sign_call(&*client, &*miner, request);
+				let r = client.call(&signed, analytics);
 				if let Ok(executed) = r {
 					// TODO maybe add other stuff to this?
 					let mut ret = map!["output".to_owned() => to_value(&Bytes(executed.output)).unwrap()];
@@ -139,6 +125,9 @@ impl<C, M> Traces for TracesClient<C, M> where C: BlockChainClient + 'static, M:
 					if let Some(state_diff) = executed.state_diff {
 						ret.insert("stateDiff".to_owned(), to_value(&StateDiff::from(state_diff)).unwrap());
 					}
+					if let Some(profile) = executed.profile {
+						ret.insert("profile".to_owned(), to_value(&Profile::from(profile)).unwrap());
+					}
 					return Ok(Value::Object(ret))
 				}
 				Ok(Value::Null)
@@ -243,7 +243,7 @@ fn eth_transaction_count() {
 	use util::crypto::Secret;
 
 	let secret = Secret::from_str("8a283037bb19c4fed7b1c569e40c7dcff366165eb869110a1b11532963eb9cb2").unwrap();
-	let tester = EthTester::from_spec_provider(|| Spec::load(TRANSACTION_COUNT_SPEC));
+	let tester = EthTester::from_spec_provider(|| Spec::load(TRANSACTION_COUNT_SPEC).unwrap());
 	let address = tester.accounts.insert_account(secret, "").unwrap();
 	tester.accounts.unlock_account_permanently(address, "".into()).unwrap();
 
@@ -0,0 +1,130 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use jsonrpc_core::IoHandler;
+use util::numbers::*;
+use util::{Address, Hashable};
+use rustc_serialize::hex::{FromHex, ToHex};
+use ethcore::client::{TestBlockChainClient, Executed, DebugFrame};
+use v1::{Debug, DebugClient};
+use v1::tests::helpers::TestMinerService;
+
+fn io(client: &Arc<TestBlockChainClient>) -> IoHandler {
+	let miner = Arc::new(TestMinerService::default());
+	let io = IoHandler::new();
+	io.add_delegate(DebugClient::new(client, &miner).to_delegate());
+	io
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let client = Arc::new(TestBlockChainClient::default());
+	client.set_execution_result(Executed {
+		gas: U256::zero(),
+		gas_used: U256::from(21_005),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34],
+		trace: None,
+		vm_trace: None,
+		state_diff: None,
+		profile: None,
+		exception: None,
+	});
+	client.set_debug_frames(vec![DebugFrame {
+		code_hash: H256::zero(),
+		pc: 0,
+		instruction: 0x60,
+		gas: U256::from(100),
+		gas_cost: U256::from(3),
+		depth: 1,
+		stack: vec![],
+		memory: vec![],
+		storage: Default::default(),
+	}]);
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":21005,"returnValue":"1234","structLogs":[{"depth":1,"gas":100,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"stack":[],"storage":{}}]},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params":["0x0000000000000000000000000000000000000000000000000000000000000001", {"disableMemory": true, "disableStack": true}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":21005,"returnValue":"1234","structLogs":[{"depth":1,"gas":100,"gasCost":3,"memory":null,"op":"PUSH1","pc":0,"stack":null,"storage":{}}]},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_unknown_transaction() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_traceTransaction", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_session() {
+	let client = Arc::new(TestBlockChainClient::default());
+	// store 1 at 0
+	let code = "600160005500".from_hex().unwrap();
+	let code_hash = code.sha3();
+	client.set_code(Address::from(11), code);
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_startCall", "params":[{"from": "0x0000000000000000000000000000000000000001", "to": "0x000000000000000000000000000000000000000b", "gas": "0x186a0", "gasPrice": "0x0"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x00","id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_step", "params":["0x0"], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":{{"error":null,"gasUsed":null,"output":null,"paused":{{"codeHash":"0x{}","depth":1,"gas":"0x013495","gasCost":"0x03","memory":"0x","op":"PUSH1","pc":2,"stack":["0x01"],"storage":{{}}}}}},"id":1}}"#, code_hash.to_hex());
+	assert_eq!(io.handle_request(request), Some(response));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_continue", "params":["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"error":null,"gasUsed":"0xa02e","output":"0x","paused":null},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_stopSession", "params":["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_sessionState", "params":["0x0"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_sessions_limit() {
+	let client = Arc::new(TestBlockChainClient::default());
+	client.set_code(Address::from(11), "600160005500".from_hex().unwrap());
+	let io = io(&client);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "debug_startCall", "params":[{"from": "0x0000000000000000000000000000000000000001", "to": "0x000000000000000000000000000000000000000b", "gas": "0x186a0", "gasPrice": "0x0"}], "id": 1}"#;
+	for id in 0..16 {
+		let response = format!(r#"{{"jsonrpc":"2.0","result":"0x{:02x}","id":1}}"#, id);
+		assert_eq!(io.handle_request(request), Some(response));
+	}
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32040,"message":"Too many debugging sessions. Stop one of them or wait 60 seconds for one to expire.","data":null},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let stop = r#"{"jsonrpc": "2.0", "method": "debug_stopSession", "params":["0x0"], "id": 1}"#;
+	assert_eq!(io.handle_request(stop), Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.to_owned()));
+	let response = r#"{"jsonrpc":"2.0","result":"0x10","id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
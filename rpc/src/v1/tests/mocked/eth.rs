@@ -434,6 +434,8 @@ fn rpc_eth_call() {
 		trace: None,
 		vm_trace: None,
 		state_diff: None,
+		profile: None,
+		exception: None,
 	});
 
 	let request = r#"{
@@ -469,6 +471,8 @@ fn rpc_eth_call_default_block() {
 		trace: None,
 		vm_trace: None,
 		state_diff: None,
+		profile: None,
+		exception: None,
 	});
 
 	let request = r#"{
@@ -503,6 +507,8 @@ fn rpc_eth_estimate_gas() {
 		trace: None,
 		vm_trace: None,
 		state_diff: None,
+		profile: None,
+		exception: None,
 	});
 
 	let request = r#"{
@@ -538,6 +544,8 @@ fn rpc_eth_estimate_gas_default_block() {
 		trace: None,
 		vm_trace: None,
 		state_diff: None,
+		profile: None,
+		exception: None,
 	});
 
 	let request = r#"{
@@ -584,8 +592,8 @@ fn rpc_eth_send_transaction() {
 		value: U256::from(0x9184e72au64),
 		data: vec![]
 	};
-	let signature = tester.accounts_provider.sign(address, t.hash()).unwrap();
-	let t = t.with_signature(signature);
+	let signature = tester.accounts_provider.sign(address, t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
 
 	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
 
@@ -601,8 +609,8 @@ fn rpc_eth_send_transaction() {
 		value: U256::from(0x9184e72au64),
 		data: vec![]
 	};
-	let signature = tester.accounts_provider.sign(address, t.hash()).unwrap();
-	let t = t.with_signature(signature);
+	let signature = tester.accounts_provider.sign(address, t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
 
 	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
 
@@ -666,8 +674,8 @@ fn rpc_eth_send_raw_transaction() {
 		value: U256::from(0x9184e72au64),
 		data: vec![]
 	};
-	let signature = tester.accounts_provider.sign(address, t.hash()).unwrap();
-	let t = t.with_signature(signature);
+	let signature = tester.accounts_provider.sign(address, t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
 
 	let rlp = ::util::rlp::encode(&t).to_vec().to_hex();
 
@@ -241,3 +241,84 @@ fn rpc_ethcore_unsigned_transactions_count_when_signer_disabled() {
 
 	assert_eq!(io.handle_request(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_ethcore_verify_snark_requires_all_arguments() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_verifySnark", "params":["0x01", "0x02"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+fn snark_verify_request(primary_input: &str) -> String {
+	let vk = include_str!("../../../../../ethcore/res/zksnark/vk.hex");
+	let proof = include_str!("../../../../../ethcore/res/zksnark/proof.hex");
+	format!(r#"{{"jsonrpc": "2.0", "method": "ethcore_verifySnark", "params":["0x{}", "0x{}", "0x{}"], "id": 1}}"#, vk, proof, primary_input)
+}
+
+#[test]
+fn rpc_ethcore_verify_snark_rejected() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = snark_verify_request("0000000000000000000000000000000000000000000000000000000000000003");
+	let response = r#"{"jsonrpc":"2.0","result":{"error":null,"gas":null,"status":"rejected"},"id":1}"#;
+
+	assert_eq!(io.handle_request(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_verify_snark_rejects_oversized_arguments() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = snark_verify_request(&::std::iter::repeat('0').take(2 * 64 * 1024).collect::<String>());
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params","data":null},"id":1}"#;
+
+	assert_eq!(io.handle_request(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_block_signer_without_signing_engine() {
+	let miner = miner_service();
+	let client = client_service();
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_blockSigner", "params":["latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_ethcore_disassemble() {
+	use rustc_serialize::hex::FromHex;
+	use util::Address;
+
+	let miner = miner_service();
+	let client = client_service();
+	// PUSH1 3, JUMP, JUMPDEST, STOP
+	client.set_code(Address::from(1), "6003565b00".from_hex().unwrap());
+	let io = IoHandler::new();
+	io.add_delegate(ethcore_client(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_disassemble", "params":["0x0000000000000000000000000000000000000001", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blocks":[{"fallsThrough":false,"jump":3,"operations":[{"op":"PUSH1","pc":0,"pushData":"0x03"},{"op":"JUMP","pc":2,"pushData":null}],"start":0,"staticGas":11},{"fallsThrough":false,"jump":null,"operations":[{"op":"JUMPDEST","pc":3,"pushData":null},{"op":"STOP","pc":4,"pushData":null}],"start":3,"staticGas":1}],"invalidJumps":[],"jumpDestinations":[3]},"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "ethcore_disassemble", "params":["0x0000000000000000000000000000000000000001", "0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
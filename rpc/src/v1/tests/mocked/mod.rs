@@ -25,5 +25,7 @@ mod personal;
 mod personal_signer;
 mod ethcore;
 mod ethcore_set;
+mod debug;
+mod traces;
 mod rpc;
 mod manage_network;
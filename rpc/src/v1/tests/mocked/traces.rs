@@ -0,0 +1,57 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::collections::BTreeMap;
+use jsonrpc_core::IoHandler;
+use util::numbers::*;
+use ethcore::client::{TestBlockChainClient, Executed};
+use ethcore::profile::{Profile, InstructionProfile};
+use v1::{Traces, TracesClient};
+use v1::tests::helpers::TestMinerService;
+
+#[test]
+fn rpc_trace_call_with_profile() {
+	let client = Arc::new(TestBlockChainClient::default());
+	let miner = Arc::new(TestMinerService::default());
+	let mut code = BTreeMap::new();
+	code.insert(H256::from(1u64), vec![InstructionProfile {
+		instruction: 0x60,
+		count: 2,
+		gas: U256::from(6),
+		time_ns: 120,
+	}]);
+	client.set_execution_result(Executed {
+		gas: U256::zero(),
+		gas_used: U256::from(21_006),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34],
+		trace: None,
+		vm_trace: None,
+		state_diff: None,
+		profile: Some(Profile { code: code }),
+		exception: None,
+	});
+	let io = IoHandler::new();
+	io.add_delegate(TracesClient::new(&client, &miner).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "trace_call", "params":[{"to": "0x0000000000000000000000000000000000000001"}, ["profile"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"output":"0x1234","profile":{"0x0000000000000000000000000000000000000000000000000000000000000001":[{"op":96,"name":"PUSH1","count":2,"gas":"0x06","timeNs":120}]}},"id":1}"#;
+	assert_eq!(io.handle_request(request), Some(response.to_owned()));
+}
@@ -0,0 +1,73 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug rpc interface.
+use std::sync::Arc;
+use jsonrpc_core::*;
+
+/// Debug rpc interface.
+pub trait Debug: Sized + Send + Sync + 'static {
+	/// Replays the given transaction and returns every instruction it executed, as geth does.
+	fn trace_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Starts a debugging session of a transaction of the chain, paused before its first instruction.
+	fn start_transaction(&self, _: Params) -> Result<Value, Error>;
+
+	/// Starts a debugging session of a call on the latest state, paused before its first instruction.
+	fn start_call(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns where a debugging session is at.
+	fn session_state(&self, _: Params) -> Result<Value, Error>;
+
+	/// Executes one instruction, pausing inside the call it makes, if any.
+	fn step(&self, _: Params) -> Result<Value, Error>;
+
+	/// Executes one instruction, running the call it makes, if any, to completion.
+	fn step_over(&self, _: Params) -> Result<Value, Error>;
+
+	/// Runs until the code being executed reaches the given program counter, or a breakpoint is hit.
+	fn continue_to(&self, _: Params) -> Result<Value, Error>;
+
+	/// Runs until a breakpoint is hit.
+	fn resume(&self, _: Params) -> Result<Value, Error>;
+
+	/// Sets a breakpoint on a program counter of the code with the given hash.
+	fn set_breakpoint(&self, _: Params) -> Result<Value, Error>;
+
+	/// Removes a breakpoint.
+	fn remove_breakpoint(&self, _: Params) -> Result<Value, Error>;
+
+	/// Ends a debugging session.
+	fn stop_session(&self, _: Params) -> Result<Value, Error>;
+
+	/// Should be used to convert object to io delegate.
+	fn to_delegate(self) -> IoDelegate<Self> {
+		let mut delegate = IoDelegate::new(Arc::new(self));
+		delegate.add_method("debug_traceTransaction", Debug::trace_transaction);
+		delegate.add_method("debug_startTransaction", Debug::start_transaction);
+		delegate.add_method("debug_startCall", Debug::start_call);
+		delegate.add_method("debug_sessionState", Debug::session_state);
+		delegate.add_method("debug_step", Debug::step);
+		delegate.add_method("debug_stepOver", Debug::step_over);
+		delegate.add_method("debug_continueTo", Debug::continue_to);
+		delegate.add_method("debug_continue", Debug::resume);
+		delegate.add_method("debug_setBreakpoint", Debug::set_breakpoint);
+		delegate.add_method("debug_removeBreakpoint", Debug::remove_breakpoint);
+		delegate.add_method("debug_stopSession", Debug::stop_session);
+
+		delegate
+	}
+}
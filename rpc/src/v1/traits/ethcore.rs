@@ -67,6 +67,15 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 	/// Returns error when signer is disabled
 	fn unsigned_transactions_count(&self, _: Params) -> Result<Value, Error>;
 
+	/// Verifies a zkSNARK proof with the chain's zkSNARK builtin, without making a transaction.
+	fn verify_snark(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the account which sealed the given block, if its engine seals with a signature.
+	fn block_signer(&self, _: Params) -> Result<Value, Error>;
+
+	/// Returns the code of an account at the given block split into basic blocks, with their static gas.
+	fn disassemble(&self, _: Params) -> Result<Value, Error>;
+
 	/// Should be used to convert object to io delegate.
 	fn to_delegate(self) -> IoDelegate<Self> {
 		let mut delegate = IoDelegate::new(Arc::new(self));
@@ -86,6 +95,9 @@ pub trait Ethcore: Sized + Send + Sync + 'static {
 		delegate.add_method("ethcore_defaultExtraData", Ethcore::default_extra_data);
 		delegate.add_method("ethcore_gasPriceStatistics", Ethcore::gas_price_statistics);
 		delegate.add_method("ethcore_unsignedTransactionsCount", Ethcore::unsigned_transactions_count);
+		delegate.add_method("ethcore_verifySnark", Ethcore::verify_snark);
+		delegate.add_method("ethcore_blockSigner", Ethcore::block_signer);
+		delegate.add_method("ethcore_disassemble", Ethcore::disassemble);
 
 		delegate
 	}
@@ -0,0 +1,103 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Interactive debugging session state.
+
+use std::collections::BTreeMap;
+use ethcore::client::{DebugStatus, DebugFrame as EthDebugFrame};
+use ethcore::evm::instructions;
+use v1::types::{Bytes, H256, U256};
+
+/// State of the VM before it executes an instruction.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DebugFrame {
+	/// Hash of the code being executed.
+	#[serde(rename="codeHash")]
+	pub code_hash: H256,
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction mnemonic.
+	pub op: &'static str,
+	/// Gas left.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	#[serde(rename="gasCost")]
+	pub gas_cost: U256,
+	/// Call depth, starting at 1.
+	pub depth: usize,
+	/// Stack, bottom first.
+	pub stack: Vec<U256>,
+	/// Memory.
+	pub memory: Bytes,
+	/// Storage slots read or written so far by this call.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+impl From<EthDebugFrame> for DebugFrame {
+	fn from(f: EthDebugFrame) -> Self {
+		DebugFrame {
+			code_hash: f.code_hash.into(),
+			pc: f.pc,
+			op: instructions::INSTRUCTIONS[f.instruction as usize].name,
+			gas: f.gas.into(),
+			gas_cost: f.gas_cost.into(),
+			depth: f.depth,
+			stack: f.stack.into_iter().map(Into::into).collect(),
+			memory: f.memory.into(),
+			storage: f.storage.into_iter().map(|(k, v)| (H256::from(&k), H256::from(&v))).collect(),
+		}
+	}
+}
+
+/// Where a debugging session is at.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DebugState {
+	/// Instruction the session is paused at, unless it finished.
+	pub paused: Option<DebugFrame>,
+	/// Gas used by the transaction, once it finished.
+	#[serde(rename="gasUsed")]
+	pub gas_used: Option<U256>,
+	/// Data returned by the transaction, once it finished.
+	pub output: Option<Bytes>,
+	/// Why the transaction could not be executed, if it could not.
+	pub error: Option<String>,
+}
+
+impl<'a> From<&'a DebugStatus> for DebugState {
+	fn from(s: &'a DebugStatus) -> Self {
+		let empty = DebugState {
+			paused: None,
+			gas_used: None,
+			output: None,
+			error: None,
+		};
+		match *s {
+			DebugStatus::Paused(ref frame) => DebugState {
+				paused: Some(frame.clone().into()),
+				..empty
+			},
+			DebugStatus::Finished(Ok(ref executed)) => DebugState {
+				gas_used: Some(executed.gas_used.into()),
+				output: Some(executed.output.clone().into()),
+				..empty
+			},
+			DebugStatus::Finished(Err(ref e)) => DebugState {
+				error: Some(format!("{}", e)),
+				..empty
+			},
+		}
+	}
+}
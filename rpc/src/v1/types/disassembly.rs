@@ -0,0 +1,97 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Disassembled code.
+
+use ethcore::evm::disassembler;
+use v1::types::Bytes;
+
+/// Instruction of disassembled code.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Operation {
+	/// Position of the instruction in code.
+	pub pc: usize,
+	/// Instruction mnemonic.
+	pub op: &'static str,
+	/// Data pushed by a `PUSH*` instruction.
+	#[serde(rename="pushData")]
+	pub push_data: Option<Bytes>,
+}
+
+impl From<disassembler::Operation> for Operation {
+	fn from(o: disassembler::Operation) -> Self {
+		Operation {
+			pc: o.pc,
+			op: match o.name() {
+				"" => "INVALID",
+				name => name,
+			},
+			push_data: o.push_data.map(Into::into),
+		}
+	}
+}
+
+/// Run of code executed straight through.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BasicBlock {
+	/// Position of the first instruction.
+	pub start: usize,
+	/// Gas the instructions of the block cost at least.
+	#[serde(rename="staticGas")]
+	pub static_gas: usize,
+	/// Destination of the jump ending the block, if it is pushed right before the jump.
+	pub jump: Option<usize>,
+	/// Whether execution may go on with the next block.
+	#[serde(rename="fallsThrough")]
+	pub falls_through: bool,
+	/// Instructions of the block.
+	pub operations: Vec<Operation>,
+}
+
+impl From<disassembler::BasicBlock> for BasicBlock {
+	fn from(b: disassembler::BasicBlock) -> Self {
+		BasicBlock {
+			start: b.start,
+			static_gas: b.static_gas,
+			jump: b.jump,
+			falls_through: b.falls_through,
+			operations: b.operations.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Code split into basic blocks.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Disassembly {
+	/// Basic blocks, in code order.
+	pub blocks: Vec<BasicBlock>,
+	/// Positions of the `JUMPDEST` instructions.
+	#[serde(rename="jumpDestinations")]
+	pub jump_destinations: Vec<usize>,
+	/// Positions of the jumps known to fail, as their destination is not a `JUMPDEST`.
+	#[serde(rename="invalidJumps")]
+	pub invalid_jumps: Vec<usize>,
+}
+
+impl From<disassembler::Disassembly> for Disassembly {
+	fn from(d: disassembler::Disassembly) -> Self {
+		Disassembly {
+			invalid_jumps: d.invalid_jumps().into_iter().map(|(pc, _)| pc).collect(),
+			jump_destinations: d.jump_destinations.into_iter().collect(),
+			blocks: d.blocks.into_iter().map(Into::into).collect(),
+		}
+	}
+}
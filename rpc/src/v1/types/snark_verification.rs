@@ -0,0 +1,86 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use serde::{Serialize, Serializer};
+use ethcore::client::{SnarkVerification as EthSnarkVerification, SnarkVerificationResult};
+use v1::types::U256;
+
+/// Outcome of verifying a proof with the zkSNARK builtin.
+#[derive(Debug, PartialEq)]
+pub enum SnarkVerificationStatus {
+	/// The proof is valid.
+	Verified,
+	/// The proof is not valid.
+	Rejected,
+	/// The arguments could not be decoded.
+	Error,
+}
+
+impl Serialize for SnarkVerificationStatus {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		match *self {
+			SnarkVerificationStatus::Verified => "verified".serialize(serializer),
+			SnarkVerificationStatus::Rejected => "rejected".serialize(serializer),
+			SnarkVerificationStatus::Error => "error".serialize(serializer),
+		}
+	}
+}
+
+/// Result of an offline zkSNARK proof verification.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SnarkVerification {
+	/// Outcome of the verification.
+	pub status: SnarkVerificationStatus,
+	/// Reason the arguments could not be decoded.
+	pub error: Option<String>,
+	/// Gas the builtin would charge, if the chain has one.
+	pub gas: Option<U256>,
+}
+
+impl From<EthSnarkVerification> for SnarkVerification {
+	fn from(v: EthSnarkVerification) -> Self {
+		let (status, error) = match v.result {
+			SnarkVerificationResult::Verified => (SnarkVerificationStatus::Verified, None),
+			SnarkVerificationResult::Rejected => (SnarkVerificationStatus::Rejected, None),
+			SnarkVerificationResult::Error(reason) => (SnarkVerificationStatus::Error, Some(reason)),
+		};
+
+		SnarkVerification {
+			status: status,
+			error: error,
+			gas: v.gas.map(Into::into),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::U256;
+	use super::{SnarkVerification, SnarkVerificationStatus};
+
+	#[test]
+	fn test_serialize_snark_verification() {
+		let v = SnarkVerification {
+			status: SnarkVerificationStatus::Error,
+			error: Some("invalid ABI encoding".to_owned()),
+			gas: Some(U256::from(0x64)),
+		};
+		let serialized = serde_json::to_string(&v).unwrap();
+		assert_eq!(serialized, r#"{"status":"error","error":"invalid ABI encoding","gas":"0x64"}"#);
+	}
+}
@@ -0,0 +1,152 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Geth-compatible transaction traces.
+
+use std::collections::BTreeMap;
+use rustc_serialize::hex::ToHex;
+use ethcore::client::DebugFrame;
+use ethcore::evm::instructions;
+use util::{H256, U256, Uint};
+
+/// Options of `debug_traceTransaction`.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct TraceOptions {
+	/// Don't capture memory.
+	#[serde(rename="disableMemory")]
+	pub disable_memory: Option<bool>,
+	/// Don't capture the stack.
+	#[serde(rename="disableStack")]
+	pub disable_stack: Option<bool>,
+}
+
+impl TraceOptions {
+	/// Whether memory should be captured.
+	pub fn memory(&self) -> bool {
+		self.disable_memory != Some(true)
+	}
+
+	/// Whether the stack should be captured.
+	pub fn stack(&self) -> bool {
+		self.disable_stack != Some(true)
+	}
+}
+
+/// State of the VM before executing an instruction.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Instruction mnemonic.
+	pub op: &'static str,
+	/// Gas left.
+	pub gas: u64,
+	/// Gas cost of the instruction.
+	#[serde(rename="gasCost")]
+	pub gas_cost: u64,
+	/// Call depth, starting at 1.
+	pub depth: usize,
+	/// Stack, bottom first, unless disabled.
+	pub stack: Option<Vec<String>>,
+	/// Memory in 32-byte words, unless disabled.
+	pub memory: Option<Vec<String>>,
+	/// Storage slots accessed so far by the executing contract.
+	pub storage: BTreeMap<String, String>,
+}
+
+/// Result of `debug_traceTransaction`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TransactionTrace {
+	/// Gas used by the transaction.
+	pub gas: u64,
+	/// Whether the transaction failed.
+	pub failed: bool,
+	/// Data returned by the transaction.
+	#[serde(rename="returnValue")]
+	pub return_value: String,
+	/// Executed instructions, those of nested calls included.
+	#[serde(rename="structLogs")]
+	pub struct_logs: Vec<StructLog>,
+}
+
+fn word(value: &U256) -> String {
+	H256::from(value).to_hex()
+}
+
+impl StructLog {
+	/// Convert the state of the VM recorded before an instruction, leaving out what `options`
+	/// disable.
+	pub fn from_frame(frame: DebugFrame, options: &TraceOptions) -> StructLog {
+		StructLog {
+			pc: frame.pc,
+			op: instructions::INSTRUCTIONS[frame.instruction as usize].name,
+			gas: frame.gas.low_u64(),
+			gas_cost: frame.gas_cost.low_u64(),
+			depth: frame.depth,
+			stack: match options.stack() {
+				true => Some(frame.stack.iter().map(word).collect()),
+				false => None,
+			},
+			memory: match options.memory() {
+				true => Some(frame.memory.chunks(32).map(|w| w.to_hex()).collect()),
+				false => None,
+			},
+			storage: frame.storage.iter().map(|(k, v)| (word(k), word(v))).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::H256;
+	use ethcore::client::DebugFrame;
+	use super::{StructLog, TraceOptions};
+
+	fn frame() -> DebugFrame {
+		// SLOAD of slot 0 holding 7, after PUSH1 1, PUSH1 0, MSTORE8, PUSH1 0
+		DebugFrame {
+			code_hash: H256::new(),
+			pc: 7,
+			instruction: 0x54,
+			gas: 85.into(),
+			gas_cost: 50.into(),
+			depth: 1,
+			stack: vec![0.into()],
+			memory: {
+				let mut memory = vec![0u8; 32];
+				memory[0] = 1;
+				memory
+			},
+			storage: map![0.into() => 7.into()],
+		}
+	}
+
+	#[test]
+	fn should_serialize_frame() {
+		let log = StructLog::from_frame(frame(), &TraceOptions::default());
+		let serialized = serde_json::to_string(&log).unwrap();
+		assert_eq!(serialized, r#"{"pc":7,"op":"SLOAD","gas":85,"gasCost":50,"depth":1,"stack":["0000000000000000000000000000000000000000000000000000000000000000"],"memory":["0100000000000000000000000000000000000000000000000000000000000000"],"storage":{"0000000000000000000000000000000000000000000000000000000000000000":"0000000000000000000000000000000000000000000000000000000000000007"}}"#);
+	}
+
+	#[test]
+	fn should_respect_options() {
+		let options = TraceOptions { disable_memory: Some(true), disable_stack: Some(true) };
+		let log = StructLog::from_frame(frame(), &options);
+		assert!(log.stack.is_none() && log.memory.is_none());
+		assert_eq!(log.storage.len(), 1);
+	}
+}
@@ -21,6 +21,8 @@ use ethcore::trace::{Trace as EthTrace, LocalizedTrace as EthLocalizedTrace};
 use ethcore::trace as et;
 use ethcore::state_diff;
 use ethcore::account_diff;
+use ethcore::profile;
+use ethcore::evm::instructions;
 use util::Uint;
 use v1::types::{Bytes, H160, H256, U256};
 
@@ -209,6 +211,50 @@ impl From<state_diff::StateDiff> for StateDiff {
 	}
 }
 
+#[derive(Debug, Serialize)]
+/// Execution statistics of one instruction within some code.
+pub struct InstructionProfile {
+	/// The instruction.
+	pub op: u8,
+	/// Its mnemonic.
+	pub name: &'static str,
+	/// Number of times it was executed.
+	pub count: u64,
+	/// Total gas it cost.
+	pub gas: U256,
+	/// Total time spent executing it, in nanoseconds.
+	#[serde(rename="timeNs")]
+	pub time_ns: u64,
+}
+
+impl From<profile::InstructionProfile> for InstructionProfile {
+	fn from(c: profile::InstructionProfile) -> Self {
+		InstructionProfile {
+			op: c.instruction,
+			name: instructions::INSTRUCTIONS[c.instruction as usize].name,
+			count: c.count,
+			gas: c.gas.into(),
+			time_ns: c.time_ns,
+		}
+	}
+}
+
+/// Serde-friendly `Profile` shadow.
+pub struct Profile(BTreeMap<H256, Vec<InstructionProfile>>);
+
+impl Serialize for Profile {
+	fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+	where S: Serializer {
+		Serialize::serialize(&self.0, serializer)
+	}
+}
+
+impl From<profile::Profile> for Profile {
+	fn from(c: profile::Profile) -> Self {
+		Profile(c.code.into_iter().map(|(k, v)| (k.into(), v.into_iter().map(Into::into).collect())).collect())
+	}
+}
+
 /// Create response
 #[derive(Debug, Serialize)]
 pub struct Create {
@@ -336,6 +382,12 @@ pub enum Res {
 	/// Creation failure
 	#[serde(rename="failedCreate")]
 	FailedCreate,
+	/// Builtin call failure
+	#[serde(rename="failedBuiltinCall")]
+	FailedBuiltinCall(String),
+	/// Call or creation stopped by `REVERT`
+	#[serde(rename="reverted")]
+	Reverted(CallResult),
 }
 
 impl From<trace::Res> for Res {
@@ -345,6 +397,8 @@ impl From<trace::Res> for Res {
 			trace::Res::Create(create) => Res::Create(CreateResult::from(create)),
 			trace::Res::FailedCall => Res::FailedCall,
 			trace::Res::FailedCreate => Res::FailedCreate,
+			trace::Res::FailedBuiltinCall(reason) => Res::FailedBuiltinCall(reason),
+			trace::Res::Reverted(reverted) => Res::Reverted(CallResult::from(reverted)),
 		}
 	}
 }
@@ -511,6 +565,21 @@ mod tests {
 		assert_eq!(serialized, r#"{"0x000000000000000000000000000000000000002a":{"balance":{"=":[]},"nonce":{"+":"0x01"},"code":{"=":[]},"storage":{"0x000000000000000000000000000000000000000000000000000000000000002a":{"=":[]}}},"0x0000000000000000000000000000000000000045":{"balance":{"=":[]},"nonce":{"*":{"from":"0x01","to":"0x00"}},"code":{"-":"0x60"},"storage":{}}}"#);
 	}
 
+	#[test]
+	fn test_profile_serialize() {
+		let t = Profile(map![
+			42.into() => vec![InstructionProfile {
+				op: 0x60,
+				name: "PUSH1",
+				count: 2,
+				gas: 6.into(),
+				time_ns: 100,
+			}]
+		]);
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"0x000000000000000000000000000000000000000000000000000000000000002a":[{"op":96,"name":"PUSH1","count":2,"gas":"0x06","timeNs":100}]}"#);
+	}
+
 	#[test]
 	fn test_action_serialize() {
 		let actions = vec![Action::Call(Call {
@@ -544,9 +613,14 @@ mod tests {
 			}),
 			Res::FailedCall,
 			Res::FailedCreate,
+			Res::FailedBuiltinCall("invalid input".to_owned()),
+			Res::Reverted(CallResult {
+				gas_used: U256::from(4),
+				output: vec![0x67].into(),
+			}),
 		];
 
 		let serialized = serde_json::to_string(&results).unwrap();
-		assert_eq!(serialized, r#"[{"call":{"gasUsed":"0x01","output":"0x1234"}},{"create":{"gasUsed":"0x02","code":"0x4556","address":"0x0000000000000000000000000000000000000003"}},{"failedCall":[]},{"failedCreate":[]}]"#);
+		assert_eq!(serialized, r#"[{"call":{"gasUsed":"0x01","output":"0x1234"}},{"create":{"gasUsed":"0x02","code":"0x4556","address":"0x0000000000000000000000000000000000000003"}},{"failedCall":[]},{"failedCreate":[]},{"failedBuiltinCall":"invalid input"},{"reverted":{"gasUsed":"0x04","output":"0x67"}}]"#);
 	}
 }
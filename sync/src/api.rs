@@ -22,6 +22,7 @@ use util::{TimerToken, U256, H256, UtilError, Secret, Populatable};
 use ethcore::client::{BlockChainClient, ChainNotify};
 use io::NetSyncIo;
 use chain::{ChainSync, SyncStatus};
+use consensus::ConsensusSync;
 use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::mem;
@@ -30,6 +31,8 @@ use parking_lot::RwLock;
 
 /// Ethereum sync protocol
 pub const ETH_PROTOCOL: &'static str = "eth";
+/// Protocol of the messages of BFT consensus engines
+pub const CONSENSUS_PROTOCOL: &'static str = "bft";
 
 /// Sync configuration
 #[derive(Debug, Clone)]
@@ -64,6 +67,8 @@ pub struct EthSync {
 	network: NetworkService,
 	/// Protocol handler
 	handler: Arc<SyncProtocolHandler>,
+	/// Consensus protocol handler
+	consensus: Arc<ConsensusProtocolHandler>,
 }
 
 impl EthSync {
@@ -73,7 +78,8 @@ impl EthSync {
 		let service = try!(NetworkService::new(try!(network_config.into_basic())));
 		let sync = Arc::new(EthSync{
 			network: service,
-			handler: Arc::new(SyncProtocolHandler { sync: RwLock::new(chain_sync), chain: chain }),
+			handler: Arc::new(SyncProtocolHandler { sync: RwLock::new(chain_sync), chain: chain.clone() }),
+			consensus: Arc::new(ConsensusProtocolHandler { sync: RwLock::new(ConsensusSync::default()), chain: chain }),
 		});
 
 		Ok(sync)
@@ -119,6 +125,27 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 	}
 }
 
+struct ConsensusProtocolHandler {
+	/// Shared blockchain client, whose engine takes the messages.
+	chain: Arc<BlockChainClient>,
+	/// Consensus peers
+	sync: RwLock<ConsensusSync>,
+}
+
+impl NetworkProtocolHandler for ConsensusProtocolHandler {
+	fn read(&self, io: &NetworkContext, peer: &PeerId, packet_id: u8, data: &[u8]) {
+		self.sync.read().dispatch_packet(&mut NetSyncIo::new(io, self.chain.deref()), *peer, packet_id, data);
+	}
+
+	fn connected(&self, _io: &NetworkContext, peer: &PeerId) {
+		self.sync.write().on_peer_connected(*peer);
+	}
+
+	fn disconnected(&self, _io: &NetworkContext, peer: &PeerId) {
+		self.sync.write().on_peer_aborting(*peer);
+	}
+}
+
 impl ChainNotify for EthSync {
 	fn new_blocks(&self,
 		imported: Vec<H256>,
@@ -144,11 +171,20 @@ impl ChainNotify for EthSync {
 		self.network.start().unwrap_or_else(|e| warn!("Error starting network: {:?}", e));
 		self.network.register_protocol(self.handler.clone(), ETH_PROTOCOL, &[62u8, 63u8])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
+		self.network.register_protocol(self.consensus.clone(), CONSENSUS_PROTOCOL, &[1u8])
+			.unwrap_or_else(|e| warn!("Error registering consensus protocol: {:?}", e));
 	}
 
 	fn stop(&self) {
 		self.network.stop().unwrap_or_else(|e| warn!("Error stopping network: {:?}", e));
 	}
+
+	fn broadcast(&self, message: Vec<u8>) {
+		self.network.with_context(CONSENSUS_PROTOCOL, |context| {
+			let mut sync_io = NetSyncIo::new(context, self.consensus.chain.deref());
+			self.consensus.sync.read().broadcast(&mut sync_io, message.clone());
+		});
+	}
 }
 
 impl IpcConfig for ManageNetwork { }
@@ -0,0 +1,132 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Consensus protocol: relays the messages of BFT engines between validators.
+
+use util::{PeerId, PacketId, Bytes, H256, Hashable, Mutex};
+use std::collections::{HashSet, HashMap, VecDeque};
+use ethcore::error::EngineError;
+use io::SyncIo;
+
+/// Consensus message packet.
+pub const CONSENSUS_PACKET: PacketId = 0x00;
+
+/// Number of recent messages remembered to drop duplicates.
+const MAX_SEEN_MESSAGES: usize = 4096;
+
+/// Recent consensus messages by hash, with the peer each came from; `None` for our own.
+#[derive(Default)]
+struct SeenMessages {
+	senders: HashMap<H256, Option<PeerId>>,
+	order: VecDeque<H256>,
+}
+
+impl SeenMessages {
+	/// Remember a message, forgetting the oldest ones past the limit. Returns `false` if it was already known.
+	fn note(&mut self, hash: H256, sender: Option<PeerId>) -> bool {
+		if self.senders.contains_key(&hash) {
+			return false;
+		}
+		self.senders.insert(hash.clone(), sender);
+		self.order.push_back(hash);
+		while self.order.len() > MAX_SEEN_MESSAGES {
+			if let Some(oldest) = self.order.pop_front() {
+				self.senders.remove(&oldest);
+			}
+		}
+		true
+	}
+
+	fn forget(&mut self, hash: &H256) {
+		self.senders.remove(hash);
+		self.order.retain(|h| h != hash);
+	}
+}
+
+/// Tracks the peers talking the consensus protocol and passes their messages to the engine.
+pub struct ConsensusSync {
+	peers: HashSet<PeerId>,
+	seen: Mutex<SeenMessages>,
+}
+
+impl Default for ConsensusSync {
+	fn default() -> Self {
+		ConsensusSync {
+			peers: HashSet::new(),
+			seen: Mutex::new(SeenMessages::default()),
+		}
+	}
+}
+
+impl ConsensusSync {
+	/// Called when a new peer is connected.
+	pub fn on_peer_connected(&mut self, peer: PeerId) {
+		trace!(target: "consensus", "== Connected {}", peer);
+		self.peers.insert(peer);
+	}
+
+	/// Called by peer when it is disconnecting.
+	pub fn on_peer_aborting(&mut self, peer: PeerId) {
+		trace!(target: "consensus", "== Disconnecting {}", peer);
+		self.peers.remove(&peer);
+	}
+
+	/// Dispatch incoming packet.
+	pub fn dispatch_packet(&self, io: &mut SyncIo, peer: PeerId, packet_id: u8, data: &[u8]) {
+		match packet_id {
+			CONSENSUS_PACKET => self.on_message(io, peer, data),
+			_ => {
+				debug!(target: "consensus", "{}: Unknown packet {}", peer, packet_id);
+				io.disable_peer(peer);
+			},
+		}
+	}
+
+	fn on_message(&self, io: &mut SyncIo, peer: PeerId, data: &[u8]) {
+		let hash = data.sha3();
+		// the engine relays the message from within, so the sender has to be known by then.
+		if !self.seen.lock().note(hash.clone(), Some(peer)) {
+			return;
+		}
+		match io.chain().handle_consensus_message(data.to_vec()) {
+			Ok(()) => {},
+			Err(EngineError::WrongHeight(mis)) => {
+				// the message may be of use once this node catches up.
+				trace!(target: "consensus", "{}: Message for another height: {}", peer, mis);
+				self.seen.lock().forget(&hash);
+			},
+			Err(e) => {
+				debug!(target: "consensus", "{}: Bad consensus message: {}", peer, e);
+				io.disable_peer(peer);
+			},
+		}
+	}
+
+	/// Send a consensus message of the engine to all peers, except the one it came from.
+	pub fn broadcast(&self, io: &mut SyncIo, message: Bytes) {
+		let hash = message.sha3();
+		let sender = {
+			let mut seen = self.seen.lock();
+			seen.note(hash.clone(), None);
+			seen.senders.get(&hash).cloned().and_then(|sender| sender)
+		};
+		for peer in self.peers.iter().filter(|&peer| Some(*peer) != sender) {
+			if let Err(e) = io.send(*peer, CONSENSUS_PACKET, message.clone()) {
+				debug!(target: "consensus", "Error sending consensus message to {}: {:?}", peer, e);
+			}
+		}
+	}
+}
@@ -74,10 +74,13 @@ extern crate heapsize;
 extern crate ethcore_ipc as ipc;
 extern crate semver;
 extern crate parking_lot;
+#[cfg(test)]
+extern crate ethcore_devtools as devtools;
 
 mod chain;
 mod blocks;
 mod io;
+mod consensus;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,81 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use util::*;
+use ethcore::client::{BlockChainClient, BlockID};
+use ethcore::spec::Spec;
+use ethcore::tendermint::new_test_tendermint;
+use std::thread;
+use std::time::{Duration, Instant};
+use super::helpers::*;
+
+/// Test spec in which every step times out after 200ms.
+fn short_timeouts() -> Spec {
+	let spec = include_str!("../../../ethcore/res/tendermint.json").replace("0xffffffff", "0xc8");
+	Spec::load(spec.as_bytes()).unwrap()
+}
+
+fn validators(secrets: &[&str]) -> Vec<Secret> {
+	secrets.iter().map(|s| s.sha3()).collect()
+}
+
+#[test]
+fn validators_commit_blocks() {
+	::env_logger::init().ok();
+	let mut net = ConsensusNet::new(new_test_tendermint, validators(&["", "1", "2", "3"]));
+	let mut steps = 0;
+	while net.best_block_numbers().iter().any(|&n| n < 3) {
+		assert!(steps < 2000, "validators did not commit three blocks: {:?}", net.best_block_numbers());
+		net.consensus_step();
+		steps += 1;
+	}
+
+	let hashes: Vec<_> = net.peers.iter().map(|p| p.chain.block_hash(BlockID::Number(3)).unwrap()).collect();
+	assert!(hashes.iter().all(|h| h == &hashes[0]));
+	// the second validator proposed the first block
+	let signer = net.peers[0].chain.block_signer(BlockID::Number(1));
+	assert_eq!(signer, Some(KeyPair::from_secret("1".sha3()).unwrap().address()));
+}
+
+#[test]
+fn two_of_four_validators_commit_nothing() {
+	::env_logger::init().ok();
+	let mut net = ConsensusNet::new(new_test_tendermint, validators(&["1", "2"]));
+	for _ in 0..200 {
+		net.consensus_step();
+	}
+	assert_eq!(net.best_block_numbers(), vec![0, 0]);
+}
+
+#[test]
+fn validators_commit_in_later_round_without_proposer() {
+	::env_logger::init().ok();
+	// the second validator, which proposes in the first round, is offline
+	let mut net = ConsensusNet::new(short_timeouts, validators(&["", "2", "3"]));
+	let deadline = Instant::now() + Duration::from_secs(10);
+	while net.best_block_numbers().iter().any(|&n| n < 1) {
+		assert!(Instant::now() < deadline, "validators did not commit a block: {:?}", net.best_block_numbers());
+		net.consensus_step();
+		thread::sleep(Duration::from_millis(5));
+	}
+
+	// it was proposed in a later round, by another validator
+	let signer = net.peers[0].chain.block_signer(BlockID::Number(1));
+	assert!(signer.is_some());
+	assert!(signer != Some(KeyPair::from_secret("1".sha3()).unwrap().address()));
+	let hashes: Vec<_> = net.peers.iter().map(|p| p.chain.block_hash(BlockID::Number(1)).unwrap()).collect();
+	assert!(hashes.iter().all(|h| h == &hashes[0]));
+}
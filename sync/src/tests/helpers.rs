@@ -15,19 +15,25 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use util::*;
-use ethcore::client::{TestBlockChainClient, BlockChainClient};
+use ethcore::client::{TestBlockChainClient, BlockChainClient, Client, ClientConfig, ChainNotify};
+use ethcore::spec::Spec;
+use ethcore::header::BlockNumber;
+use ethcore::miner::{Miner, MinerService, GasPricer};
+use ethcore::account_provider::AccountProvider;
+use devtools::RandomTempPath;
 use io::SyncIo;
 use chain::ChainSync;
+use consensus::ConsensusSync;
 use ::SyncConfig;
 
-pub struct TestIo<'p> {
-	pub chain: &'p mut TestBlockChainClient,
+pub struct TestIo<'p, C: 'p = TestBlockChainClient> where C: BlockChainClient {
+	pub chain: &'p C,
 	pub queue: &'p mut VecDeque<TestPacket>,
 	pub sender: Option<PeerId>,
 }
 
-impl<'p> TestIo<'p> {
-	pub fn new(chain: &'p mut TestBlockChainClient, queue: &'p mut VecDeque<TestPacket>, sender: Option<PeerId>) -> TestIo<'p> {
+impl<'p, C> TestIo<'p, C> where C: BlockChainClient {
+	pub fn new(chain: &'p C, queue: &'p mut VecDeque<TestPacket>, sender: Option<PeerId>) -> TestIo<'p, C> {
 		TestIo {
 			chain: chain,
 			queue: queue,
@@ -36,7 +42,7 @@ impl<'p> TestIo<'p> {
 	}
 }
 
-impl<'p> SyncIo for TestIo<'p> {
+impl<'p, C> SyncIo for TestIo<'p, C> where C: BlockChainClient {
 	fn disable_peer(&mut self, _peer_id: PeerId) {
 	}
 
@@ -176,3 +182,106 @@ impl TestNet {
 		peer.sync.write().chain_new_blocks(&mut TestIo::new(&mut peer.chain, &mut peer.queue, None), &[], &[], &[], &[], &[]);
 	}
 }
+
+/// Collects the consensus messages a client broadcasts.
+#[derive(Default)]
+pub struct TestNotify {
+	pub messages: RwLock<Vec<Bytes>>,
+}
+
+impl ChainNotify for TestNotify {
+	fn broadcast(&self, message: Vec<u8>) {
+		self.messages.write().push(message);
+	}
+}
+
+/// Validator with a full client, connected to the others over the consensus and the eth protocols.
+pub struct ConsensusPeer {
+	pub chain: Arc<Client>,
+	pub sync: RwLock<ConsensusSync>,
+	pub notify: Arc<TestNotify>,
+	pub queue: VecDeque<TestPacket>,
+	pub eth: RwLock<ChainSync>,
+	pub eth_queue: VecDeque<TestPacket>,
+	_path: RandomTempPath,
+}
+
+/// Network of validators running the consensus engine of a spec.
+pub struct ConsensusNet {
+	pub peers: Vec<ConsensusPeer>,
+}
+
+impl ConsensusNet {
+	/// Creates a validator for each secret, all connected with each other.
+	pub fn new<F>(spec: F, secrets: Vec<Secret>) -> ConsensusNet where F: Fn() -> Spec {
+		let mut net = ConsensusNet {
+			peers: Vec::new(),
+		};
+		for secret in secrets {
+			let path = RandomTempPath::new();
+			let accounts = Arc::new(AccountProvider::transient_provider());
+			let address = accounts.insert_account(secret, "").unwrap();
+			accounts.unlock_account_permanently(address, "".into()).unwrap();
+			let miner = Miner::new(Default::default(), GasPricer::new_fixed(U256::zero()), spec(), Some(accounts.clone()));
+			miner.set_author(address);
+			let chain = Client::new(ClientConfig::default(), spec(), path.as_path(), miner, IoChannel::disconnected()).unwrap();
+			chain.engine().set_signer(accounts, address);
+			let notify = Arc::new(TestNotify::default());
+			chain.add_notify(notify.clone());
+			let eth = ChainSync::new(SyncConfig::default(), &*chain);
+			net.peers.push(ConsensusPeer {
+				chain: chain,
+				sync: RwLock::new(ConsensusSync::default()),
+				notify: notify,
+				queue: VecDeque::new(),
+				eth: RwLock::new(eth),
+				eth_queue: VecDeque::new(),
+				_path: path,
+			});
+		}
+		for peer in 0..net.peers.len() {
+			for other in 0..net.peers.len() {
+				if peer != other {
+					let p = &mut net.peers[peer];
+					p.sync.write().on_peer_connected(other as PeerId);
+					p.eth.write().on_peer_connected(&mut TestIo::new(&*p.chain, &mut p.eth_queue, Some(other as PeerId)), other as PeerId);
+				}
+			}
+		}
+		net
+	}
+
+	/// Lets every engine act on its timeouts, sends the messages it broadcast and delivers a packet of each protocol to each peer.
+	pub fn consensus_step(&mut self) {
+		for peer in 0..self.peers.len() {
+			{
+				let p = &mut self.peers[peer];
+				p.chain.engine().tick();
+				let messages = mem::replace(&mut *p.notify.messages.write(), Vec::new());
+				for message in messages {
+					p.sync.read().broadcast(&mut TestIo::new(&*p.chain, &mut p.queue, None), message);
+				}
+			}
+			if let Some(packet) = self.peers[peer].queue.pop_front() {
+				let p = &mut self.peers[packet.recipient];
+				trace!("--- {} -> {} ---", peer, packet.recipient);
+				p.sync.read().dispatch_packet(&mut TestIo::new(&*p.chain, &mut p.queue, Some(peer as PeerId)), peer as PeerId, packet.packet_id, &packet.data);
+			}
+			if let Some(packet) = self.peers[peer].eth_queue.pop_front() {
+				let p = &mut self.peers[packet.recipient];
+				ChainSync::dispatch_packet(&p.eth, &mut TestIo::new(&*p.chain, &mut p.eth_queue, Some(peer as PeerId)), peer as PeerId, packet.packet_id, &packet.data);
+			}
+		}
+		// import the committed blocks and sync them to the validators which lack them.
+		for p in &mut self.peers {
+			p.chain.flush_queue();
+			let mut io = TestIo::new(&*p.chain, &mut p.eth_queue, None);
+			p.eth.write().chain_new_blocks(&mut io, &[], &[], &[], &[], &[]);
+			p.eth.write().maintain_sync(&mut io);
+		}
+	}
+
+	pub fn best_block_numbers(&self) -> Vec<BlockNumber> {
+		self.peers.iter().map(|p| p.chain.chain_info().best_block_number).collect()
+	}
+}
@@ -17,3 +17,4 @@
 pub mod helpers;
 mod chain;
 mod rpc;
+mod consensus;
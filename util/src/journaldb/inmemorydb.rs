@@ -0,0 +1,157 @@
+// Copyright 2015, 2016 Ethcore (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Memory-backed `JournalDB` implementation.
+
+use common::*;
+use hashdb::*;
+use memorydb::*;
+use super::traits::JournalDB;
+
+/// Implementation of the `JournalDB` trait keeping everything in memory, for short-lived
+/// states which should not touch the disk.
+///
+/// Like `ArchiveDB`, removals never take effect: `commit()` moves the inserts of the overlay
+/// into the backing map and drops everything else.
+#[derive(Clone)]
+pub struct InMemoryDB {
+	overlay: MemoryDB,
+	backing: HashMap<H256, Bytes>,
+	backing_aux: HashMap<Bytes, Bytes>,
+	latest_era: Option<u64>,
+}
+
+impl InMemoryDB {
+	/// Create a new, empty instance.
+	pub fn new() -> InMemoryDB {
+		InMemoryDB {
+			overlay: MemoryDB::new(),
+			backing: HashMap::new(),
+			backing_aux: HashMap::new(),
+			latest_era: None,
+		}
+	}
+}
+
+impl HashDB for InMemoryDB {
+	fn keys(&self) -> HashMap<H256, i32> {
+		let mut ret: HashMap<H256, i32> = self.backing.keys().map(|key| (key.clone(), 1)).collect();
+		for (key, refs) in self.overlay.keys().into_iter() {
+			let refs = *ret.get(&key).unwrap_or(&0) + refs;
+			ret.insert(key, refs);
+		}
+		ret
+	}
+
+	fn get(&self, key: &H256) -> Option<&[u8]> {
+		match self.overlay.raw(key) {
+			Some(&(ref d, rc)) if rc > 0 => Some(d),
+			_ => self.backing.get(key).map(|v| &v[..]),
+		}
+	}
+
+	fn contains(&self, key: &H256) -> bool {
+		self.get(key).is_some()
+	}
+
+	fn insert(&mut self, value: &[u8]) -> H256 {
+		self.overlay.insert(value)
+	}
+
+	fn emplace(&mut self, key: H256, value: Bytes) {
+		self.overlay.emplace(key, value);
+	}
+
+	fn remove(&mut self, key: &H256) {
+		self.overlay.remove(key);
+	}
+
+	fn insert_aux(&mut self, hash: Vec<u8>, value: Vec<u8>) {
+		self.overlay.insert_aux(hash, value);
+	}
+
+	fn get_aux(&self, hash: &[u8]) -> Option<Vec<u8>> {
+		self.overlay.get_aux(hash).or_else(|| self.backing_aux.get(hash).cloned())
+	}
+
+	fn remove_aux(&mut self, hash: &[u8]) {
+		self.overlay.remove_aux(hash);
+	}
+}
+
+impl JournalDB for InMemoryDB {
+	fn boxed_clone(&self) -> Box<JournalDB> {
+		Box::new(self.clone())
+	}
+
+	fn mem_used(&self) -> usize {
+		self.overlay.mem_used() + self.backing.heap_size_of_children() + self.backing_aux.heap_size_of_children()
+	}
+
+	fn is_empty(&self) -> bool {
+		self.latest_era.is_none()
+	}
+
+	fn commit(&mut self, now: u64, _: &H256, _: Option<(u64, H256)>) -> Result<u32, UtilError> {
+		let mut inserts = 0usize;
+		for (key, (value, rc)) in self.overlay.drain().into_iter() {
+			if rc > 0 {
+				self.backing.insert(key, value);
+				inserts += 1;
+			}
+		}
+
+		for (key, value) in self.overlay.drain_aux().into_iter() {
+			self.backing_aux.insert(key, value);
+		}
+
+		if self.latest_era.map_or(true, |e| now > e) {
+			self.latest_era = Some(now);
+		}
+		Ok(inserts as u32)
+	}
+
+	fn latest_era(&self) -> Option<u64> { self.latest_era }
+
+	fn state(&self, _id: &H256) -> Option<Bytes> { None }
+
+	fn is_pruned(&self) -> bool { false }
+}
+
+#[cfg(test)]
+mod tests {
+	use common::*;
+	use super::*;
+	use hashdb::*;
+	use journaldb::traits::JournalDB;
+
+	#[test]
+	fn keeps_removed_keys_and_clones_independently() {
+		let mut jdb = InMemoryDB::new();
+		let foo = jdb.insert(b"foo");
+		jdb.commit(0, &b"0".sha3(), None).unwrap();
+		jdb.remove(&foo);
+		jdb.commit(1, &b"1".sha3(), None).unwrap();
+		assert!(jdb.contains(&foo));
+
+		let mut other = jdb.boxed_clone();
+		let bar = other.insert(b"bar");
+		other.commit(2, &b"2".sha3(), None).unwrap();
+		assert!(other.contains(&bar));
+		assert!(!jdb.contains(&bar));
+		assert_eq!(jdb.latest_era(), Some(1));
+	}
+}
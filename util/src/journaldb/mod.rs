@@ -25,6 +25,7 @@ mod archivedb;
 mod earlymergedb;
 mod overlayrecentdb;
 mod refcounteddb;
+mod inmemorydb;
 
 /// Export the `JournalDB` trait.
 pub use self::traits::JournalDB;
@@ -81,6 +82,11 @@ pub fn new(path: &str, algorithm: Algorithm, config: DatabaseConfig) -> Box<Jour
 	}
 }
 
+/// Create a new `JournalDB` trait object kept in memory only. Nothing is ever pruned.
+pub fn new_in_memory() -> Box<JournalDB> {
+	Box::new(inmemorydb::InMemoryDB::new())
+}
+
 // all keys must be at least 12 bytes
 const DB_PREFIX_LEN : usize = 12;
 const LATEST_ERA_KEY : [u8; DB_PREFIX_LEN] = [ b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0 ];